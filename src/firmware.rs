@@ -0,0 +1,157 @@
+//! Firmware images: sparse ROM content produced by assembler or loaded from files
+
+pub mod easypdk;
+mod intel_hex;
+
+#[cfg(test)]
+mod test;
+
+use core::iter::once;
+
+use failure::Fail;
+
+use crate::isa::pdk13::{RomAddr, Word};
+use self::easypdk::EasyPdkImage;
+
+/// Size of pdk13 ROM address space in words
+pub const ROM_IMAGE_WORDS: usize = 0x400;
+
+#[derive(Debug, Fail, Eq, PartialEq)]
+pub enum FirmwareError {
+    #[fail(display = "Line {}: invalid Intel HEX record", _0)]
+    InvalidRecord(usize),
+    #[fail(display = "Line {}: record checksum mismatch", _0)]
+    ChecksumMismatch(usize),
+    #[fail(display = "Line {}: unsupported record type {:#04X}", _0, _1)]
+    UnsupportedRecordType(usize, u8),
+    #[fail(display = "Line {}: byte address {:#06X} is out of ROM address space", _0, _1)]
+    AddressOutOfRange(usize, u32),
+    #[fail(display = "Intel HEX end of file record is missing")]
+    MissingEndOfFile,
+    #[fail(display = "Intel HEX image is not a valid text")]
+    InvalidText,
+    #[fail(display = "Binary image size should be multiple of 2, got {} bytes", _0)]
+    OddImageSize(usize),
+    #[fail(display = "Binary image is too big: {} bytes", _0)]
+    ImageTooBig(usize),
+    #[fail(display = "Too many calibration placeholders, extra one at address {:#05X}", _0)]
+    TooManyCalibrations(RomAddr),
+    #[fail(display = "Padauk .pdk container is not supported, convert it to Intel HEX")]
+    UnsupportedContainer,
+    #[fail(display = "Unknown firmware file format, expected .ihx, .hex, .bin or .fw file")]
+    UnknownFileFormat,
+}
+
+pub type FirmwareResult<T> = Result<T, FirmwareError>;
+
+/// ROM content with the set of written words. Words are stored in little-endian byte order
+/// in binary images, so word address `n` corresponds to byte address `2 * n`
+#[derive(Clone)]
+pub struct RomImage {
+    words: [Word; ROM_IMAGE_WORDS],
+    written: [u32; ROM_IMAGE_WORDS / 32],
+}
+
+impl RomImage {
+    pub fn new() -> Self {
+        Self {
+            words: [0; ROM_IMAGE_WORDS],
+            written: [0; ROM_IMAGE_WORDS / 32],
+        }
+    }
+
+    /// Writes word to the image, returns false if address is out of ROM address space
+    pub fn set(&mut self, address: RomAddr, word: Word) -> bool {
+        let address = address as usize;
+        if address >= ROM_IMAGE_WORDS {
+            return false;
+        }
+        self.words[address] = word;
+        self.written[address / 32] |= 1 << (address % 32);
+        true
+    }
+
+    /// Creates image from raw binary with little-endian words
+    pub fn from_binary(bytes: &[u8]) -> FirmwareResult<Self> {
+        if bytes.len() & 1 != 0 {
+            return Err(FirmwareError::OddImageSize(bytes.len()));
+        }
+        if bytes.len() > ROM_IMAGE_WORDS * 2 {
+            return Err(FirmwareError::ImageTooBig(bytes.len()));
+        }
+        let mut image = Self::new();
+        for (address, word) in bytes.chunks(2).enumerate() {
+            image.set(address as RomAddr, word[0] as Word | (word[1] as Word) << 8);
+        }
+        Ok(image)
+    }
+
+    /// Returns word at the given address if it has been written
+    pub fn get(&self, address: RomAddr) -> Option<Word> {
+        if self.is_written(address) {
+            Some(self.words[address as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_written(&self, address: RomAddr) -> bool {
+        let address = address as usize;
+        address < ROM_IMAGE_WORDS && self.written[address / 32] & (1 << (address % 32)) != 0
+    }
+
+    /// Returns address of the last written word plus one
+    pub fn size(&self) -> usize {
+        (0..ROM_IMAGE_WORDS)
+            .rev()
+            .find(|address| self.is_written(*address as RomAddr))
+            .map_or(0, |address| address + 1)
+    }
+
+    /// Returns written words with their addresses
+    pub fn words(&self) -> impl Iterator<Item = (RomAddr, Word)> + '_ {
+        (0..ROM_IMAGE_WORDS as RomAddr)
+            .filter_map(move |address| self.get(address).map(|word| (address, word)))
+    }
+
+    /// Returns raw little-endian binary image up to the last written word; gaps are zeroed
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.words[..self.size()]
+            .iter()
+            .flat_map(|word| once(*word as u8).chain(once((*word >> 8) as u8)))
+    }
+}
+
+impl Default for RomImage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Content of firmware file, the format is chosen by the file name extension. Padauk `.pdk`
+/// containers written by the vendor IDE are obfuscated and are not supported
+pub enum FirmwareFile {
+    /// easy-pdk Intel HEX image from `.ihx`/`.hex` files
+    EasyPdk(EasyPdkImage),
+    /// Raw binary image from `.bin`/`.fw` files or files without extension
+    Binary(RomImage),
+}
+
+impl FirmwareFile {
+    /// Parses file content, `file_name` could be either the name or the path of the file
+    pub fn parse(file_name: &str, content: &[u8]) -> FirmwareResult<Self> {
+        let name = file_name.rsplit(|c| c == '/' || c == '\\').next().unwrap_or(file_name);
+        let extension = name.rfind('.').map(|dot| &name[dot + 1..]);
+        match extension {
+            Some("ihx") | Some("hex") => {
+                let text = core::str::from_utf8(content).map_err(|_| FirmwareError::InvalidText)?;
+                EasyPdkImage::from_intel_hex(text).map(FirmwareFile::EasyPdk)
+            }
+            None | Some("bin") | Some("fw") => {
+                RomImage::from_binary(content).map(FirmwareFile::Binary)
+            }
+            Some("pdk") => Err(FirmwareError::UnsupportedContainer),
+            Some(_) => Err(FirmwareError::UnknownFileFormat),
+        }
+    }
+}
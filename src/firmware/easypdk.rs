@@ -0,0 +1,158 @@
+//! Images prepared for easy-pdk programmer. Besides the code they carry fuse word at the top
+//! of ROM and IHRC calibration placeholders, which are replaced by the programmer with code
+//! loading tuned IHRCR value:
+//!
+//! ```text
+//! and a, #'R'
+//! and a, #'C'
+//! and a, #1             ; IHRC calibration
+//! and a, #f0 ... #f3    ; target frequency in Hz, little-endian
+//! and a, #v0 ... #v1    ; target VDD in millivolts, little-endian
+//! and a, #reg           ; IO address of the calibration register
+//! ```
+
+use super::{FirmwareError, FirmwareResult, RomImage, ROM_IMAGE_WORDS};
+use crate::isa::pdk13::{Byte, Instruction, IoAddr, RomAddr, Word};
+
+/// ROM address of the fuse word
+pub const FUSE_ADDRESS: RomAddr = ROM_IMAGE_WORDS as RomAddr - 1;
+
+/// Maximal count of calibration placeholders in the image
+pub const MAX_CALIBRATIONS: usize = 4;
+
+/// Size of calibration placeholder in words
+pub const CALIBRATION_WORDS: usize = 10;
+const CALIBRATION_IHRC: Byte = 1;
+
+/// IHRC calibration placeholder found in the image
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Calibration {
+    pub address: RomAddr,
+    pub frequency: u32,
+    pub millivolts: u16,
+    pub register: IoAddr,
+}
+
+impl Calibration {
+    /// Returns head of the code written by programmer in place of the placeholder before
+    /// tuning. The rest of the placeholder is left intact
+    pub fn stub(&self) -> [Word; 2] {
+        [
+            Instruction::MovAK(0xFF).encode(),
+            Instruction::MovIoA(self.register).encode(),
+        ]
+    }
+
+    /// Returns code which replaces the placeholder after calibration: calibrated value is
+    /// loaded to the calibration register and the rest is filled with nops. The patch only
+    /// clears bits of the stub and placeholder words, so it could be programmed to OTP ROM
+    pub fn patch(&self, value: Byte) -> [Word; CALIBRATION_WORDS] {
+        let mut code = [Instruction::Nop.encode(); CALIBRATION_WORDS];
+        code[0] = Instruction::MovAK(value).encode();
+        code[1] = Instruction::MovIoA(self.register).encode();
+        code
+    }
+}
+
+pub struct EasyPdkImage {
+    code: RomImage,
+    fuse: Option<Word>,
+    calibrations: [Option<Calibration>; MAX_CALIBRATIONS],
+}
+
+impl EasyPdkImage {
+    /// Splits ROM image to the code and fuse word, calibration placeholders are left in code
+    pub fn from_rom(rom: RomImage) -> FirmwareResult<Self> {
+        let fuse = rom.get(FUSE_ADDRESS);
+        let mut code = RomImage::new();
+        for (address, word) in rom.words().filter(|(address, _)| *address != FUSE_ADDRESS) {
+            code.set(address, word);
+        }
+        let mut calibrations = [None; MAX_CALIBRATIONS];
+        let mut count = 0;
+        let mut address = 0;
+        while address + CALIBRATION_WORDS <= FUSE_ADDRESS as usize {
+            match find_calibration(&code, address as RomAddr) {
+                Some(calibration) => {
+                    if count == MAX_CALIBRATIONS {
+                        return Err(FirmwareError::TooManyCalibrations(calibration.address));
+                    }
+                    calibrations[count] = Some(calibration);
+                    count += 1;
+                    address += CALIBRATION_WORDS;
+                }
+                None => address += 1,
+            }
+        }
+        Ok(Self {
+            code,
+            fuse,
+            calibrations,
+        })
+    }
+
+    pub fn from_intel_hex(text: &str) -> FirmwareResult<Self> {
+        Self::from_rom(RomImage::from_intel_hex(text)?)
+    }
+
+    /// Returns fuse word if it is present in the image
+    pub fn fuse(&self) -> Option<Word> {
+        self.fuse
+    }
+
+    pub fn calibrations(&self) -> impl Iterator<Item = &Calibration> {
+        self.calibrations.iter().flatten()
+    }
+
+    /// Returns code without fuse word. Calibration placeholders are left as is
+    pub fn code(&self) -> &RomImage {
+        &self.code
+    }
+
+    /// Returns code as it is written by programmer before tuning, heads of calibration
+    /// placeholders are replaced with stubs
+    pub fn programmed_code(&self) -> RomImage {
+        let mut code = self.code.clone();
+        for calibration in self.calibrations() {
+            for (offset, word) in calibration.stub().iter().copied().enumerate() {
+                code.set(calibration.address + offset as RomAddr, word);
+            }
+        }
+        code
+    }
+
+    /// Returns code with calibration placeholders replaced the same way as programmer does
+    pub fn calibrated_code(&self, value: Byte) -> RomImage {
+        let mut code = self.code.clone();
+        for calibration in self.calibrations() {
+            for (offset, word) in calibration.patch(value).iter().copied().enumerate() {
+                code.set(calibration.address + offset as RomAddr, word);
+            }
+        }
+        code
+    }
+}
+
+/// Returns immediate value of the `and a, #k` instruction
+fn and_immediate(code: &RomImage, address: RomAddr) -> Option<Byte> {
+    match code.get(address).and_then(Instruction::decode) {
+        Some(Instruction::AndAK(value)) => Some(value),
+        _ => None,
+    }
+}
+
+fn find_calibration(code: &RomImage, address: RomAddr) -> Option<Calibration> {
+    let mut bytes = [0; CALIBRATION_WORDS];
+    for (offset, byte) in bytes.iter_mut().enumerate() {
+        *byte = and_immediate(code, address + offset as RomAddr)?;
+    }
+    if bytes[..3] != [b'R', b'C', CALIBRATION_IHRC] {
+        return None;
+    }
+    Some(Calibration {
+        address,
+        frequency: u32::from_le_bytes([bytes[3], bytes[4], bytes[5], bytes[6]]),
+        millivolts: u16::from_le_bytes([bytes[7], bytes[8]]),
+        register: bytes[9],
+    })
+}
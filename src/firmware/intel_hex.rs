@@ -0,0 +1,167 @@
+//! Intel HEX reader and writer. SDCC pdk toolchain emits `.ihx` images with byte addresses
+//! and little-endian words
+
+use core::fmt::{self, Write};
+
+use super::{FirmwareError, FirmwareResult, RomImage, ROM_IMAGE_WORDS};
+use crate::isa::pdk13::RomAddr;
+
+// Count of data bytes in a single Intel HEX record written by emulator
+const INTEL_HEX_RECORD_BYTES: usize = 16;
+// Maximal count of data bytes in a record
+const MAX_RECORD_DATA: usize = 0xFF;
+
+const RECORD_DATA: u8 = 0x00;
+const RECORD_END_OF_FILE: u8 = 0x01;
+const RECORD_EXTENDED_SEGMENT_ADDRESS: u8 = 0x02;
+const RECORD_START_SEGMENT_ADDRESS: u8 = 0x03;
+const RECORD_EXTENDED_LINEAR_ADDRESS: u8 = 0x04;
+const RECORD_START_LINEAR_ADDRESS: u8 = 0x05;
+
+impl RomImage {
+    /// Parses Intel HEX image. Records could be sparse and come in any order, words which are
+    /// not covered by records are left unwritten. Start address records are ignored
+    pub fn from_intel_hex(text: &str) -> FirmwareResult<Self> {
+        let mut image = Self::new();
+        let mut base_address = 0u32;
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut data = [0u8; MAX_RECORD_DATA];
+            let record =
+                parse_record(line, &mut data).ok_or(FirmwareError::InvalidRecord(line_number))?;
+            if !record.checksum_valid {
+                return Err(FirmwareError::ChecksumMismatch(line_number));
+            }
+            let payload = &data[..record.len];
+            match record.record_type {
+                RECORD_DATA => {
+                    for (offset, byte) in payload.iter().copied().enumerate() {
+                        let address = base_address + record.address as u32 + offset as u32;
+                        if !image.set_byte(address, byte) {
+                            return Err(FirmwareError::AddressOutOfRange(line_number, address));
+                        }
+                    }
+                }
+                RECORD_END_OF_FILE => return Ok(image),
+                RECORD_EXTENDED_SEGMENT_ADDRESS | RECORD_EXTENDED_LINEAR_ADDRESS => {
+                    if payload.len() != 2 {
+                        return Err(FirmwareError::InvalidRecord(line_number));
+                    }
+                    let value = (payload[0] as u32) << 8 | payload[1] as u32;
+                    base_address = if record.record_type == RECORD_EXTENDED_SEGMENT_ADDRESS {
+                        value << 4
+                    } else {
+                        value << 16
+                    };
+                }
+                RECORD_START_SEGMENT_ADDRESS | RECORD_START_LINEAR_ADDRESS => {}
+                unsupported => {
+                    return Err(FirmwareError::UnsupportedRecordType(
+                        line_number,
+                        unsupported,
+                    ));
+                }
+            }
+        }
+        Err(FirmwareError::MissingEndOfFile)
+    }
+
+    /// Writes image in Intel HEX format. Only written words are emitted
+    pub fn write_intel_hex(&self, out: &mut impl Write) -> fmt::Result {
+        let mut address = 0;
+        while address < ROM_IMAGE_WORDS {
+            if !self.is_written(address as RomAddr) {
+                address += 1;
+                continue;
+            }
+            let mut record = [0u8; INTEL_HEX_RECORD_BYTES];
+            let mut len = 0;
+            let start = address * 2;
+            while len < INTEL_HEX_RECORD_BYTES
+                && address < ROM_IMAGE_WORDS
+                && self.is_written(address as RomAddr)
+            {
+                let word = self.words[address];
+                record[len] = word as u8;
+                record[len + 1] = (word >> 8) as u8;
+                len += 2;
+                address += 1;
+            }
+            write_intel_hex_record(out, start as u16, 0x00, &record[..len])?;
+        }
+        write_intel_hex_record(out, 0, 0x01, &[])
+    }
+
+    /// Writes single byte of the little-endian word, returns false if address is out of ROM
+    fn set_byte(&mut self, byte_address: u32, value: u8) -> bool {
+        let address = (byte_address / 2) as usize;
+        if address >= ROM_IMAGE_WORDS {
+            return false;
+        }
+        let word = self.get(address as RomAddr).unwrap_or(0);
+        let word = if byte_address & 1 == 0 {
+            (word & 0xFF00) | value as u16
+        } else {
+            (word & 0x00FF) | (value as u16) << 8
+        };
+        self.set(address as RomAddr, word)
+    }
+}
+
+struct Record {
+    len: usize,
+    address: u16,
+    record_type: u8,
+    checksum_valid: bool,
+}
+
+/// Parses `:LLAAAATT<data>CC` record, data bytes are written to `data`
+fn parse_record(line: &str, data: &mut [u8; MAX_RECORD_DATA]) -> Option<Record> {
+    let hex = line.strip_prefix(':')?.as_bytes();
+    if hex.len() < 10 || hex.len() & 1 != 0 {
+        return None;
+    }
+    let byte = |index: usize| -> Option<u8> {
+        let pair = core::str::from_utf8(&hex[index * 2..index * 2 + 2]).ok()?;
+        u8::from_str_radix(pair, 16).ok()
+    };
+    let len = byte(0)? as usize;
+    if hex.len() != (len + 5) * 2 {
+        return None;
+    }
+    let mut checksum = 0u8;
+    for index in 0..len + 5 {
+        checksum = checksum.wrapping_add(byte(index)?);
+    }
+    for (index, dst) in data.iter_mut().take(len).enumerate() {
+        *dst = byte(index + 4)?;
+    }
+    Some(Record {
+        len,
+        address: (byte(1)? as u16) << 8 | byte(2)? as u16,
+        record_type: byte(3)?,
+        checksum_valid: checksum == 0,
+    })
+}
+
+fn write_intel_hex_record(
+    out: &mut impl Write,
+    address: u16,
+    record_type: u8,
+    data: &[u8],
+) -> fmt::Result {
+    let mut checksum = (data.len() as u8)
+        .wrapping_add((address >> 8) as u8)
+        .wrapping_add(address as u8)
+        .wrapping_add(record_type);
+    write!(out, ":{:02X}{:04X}{:02X}", data.len(), address, record_type)?;
+    for byte in data.iter().copied() {
+        checksum = checksum.wrapping_add(byte);
+        write!(out, "{:02X}", byte)?;
+    }
+    writeln!(out, "{:02X}", checksum.wrapping_neg())
+}
@@ -0,0 +1,280 @@
+use core::fmt::Write;
+
+use crate::{
+    firmware::{
+        easypdk::{Calibration, EasyPdkImage},
+        FirmwareError, FirmwareFile, RomImage,
+    },
+    isa::pdk13::asm::assemble,
+    test_utils::TextBuffer,
+};
+
+// IHRC calibration to 8 MHz at 5V, IHRCR register is at 0x0B
+const CALIBRATION_PLACEHOLDER: &str = "
+    and a, #0x52
+    and a, #0x43
+    and a, #1
+    and a, #0x00
+    and a, #0x12
+    and a, #0x7A
+    and a, #0x00
+    and a, #0x88
+    and a, #0x13
+    and a, #0x0B
+";
+
+#[test]
+fn image_tracks_written_words() {
+    let mut image = RomImage::new();
+    assert_eq!(0, image.size());
+    assert!(image.set(0x010, 0x1234));
+    assert!(!image.set(0x400, 0x1234));
+    assert_eq!(Some(0x1234), image.get(0x010));
+    assert_eq!(None, image.get(0x00F));
+    assert_eq!(0x11, image.size());
+}
+
+#[test]
+fn binary_image_is_little_endian() {
+    let mut image = RomImage::new();
+    image.set(0, 0x1755);
+    image.set(2, 0x0090);
+    let mut bytes = [0u8; 8];
+    let mut len = 0;
+    for (dst, src) in bytes.iter_mut().zip(image.bytes()) {
+        *dst = src;
+        len += 1;
+    }
+    assert_eq!(6, len);
+    assert_eq!([0x55, 0x17, 0x00, 0x00, 0x90, 0x00], bytes[..6]);
+}
+
+#[test]
+fn intel_hex_contains_only_written_words() {
+    let mut image = RomImage::new();
+    for address in 0..9 {
+        image.set(address, 0x1700 | address);
+    }
+    image.set(0x10, 0x003B);
+    let mut text = TextBuffer::new();
+    image.write_intel_hex(&mut text).unwrap();
+    let mut expected = TextBuffer::new();
+    write!(
+        expected,
+        ":10000000001701170217031704170517061707171C\n\
+         :020010000817CF\n\
+         :020020003B00A3\n\
+         :00000001FF\n"
+    )
+    .unwrap();
+    assert_eq!(expected.as_str(), text.as_str());
+}
+
+#[test]
+fn intel_hex_sparse_records_are_loaded() {
+    let text = ":0400200055173B0035\n\
+                :01000400807B\n\
+                :0100050000FA\n\
+                :0400000300000000F9\n\
+                :00000001FF\n";
+    let image = RomImage::from_intel_hex(text).unwrap();
+    assert_eq!(Some(0x1755), image.get(0x10));
+    assert_eq!(Some(0x003B), image.get(0x11));
+    assert_eq!(Some(0x0080), image.get(0x02));
+    assert_eq!(None, image.get(0x00));
+    assert_eq!(None, image.get(0x03));
+    assert_eq!(0x12, image.size());
+}
+
+#[test]
+fn intel_hex_records_after_end_of_file_are_ignored() {
+    let image = RomImage::from_intel_hex(":00000001FF\n:01000400807B\n").unwrap();
+    assert_eq!(0, image.size());
+}
+
+#[test]
+fn intel_hex_round_trip() {
+    let mut image = RomImage::new();
+    for address in 0..20 {
+        image.set(address * 3, 0x1700 | address);
+    }
+    image.set(0x3FF, 0x3FFF);
+    let mut text = TextBuffer::new();
+    image.write_intel_hex(&mut text).unwrap();
+    let loaded = RomImage::from_intel_hex(text.as_str()).unwrap();
+    assert!(image.words().eq(loaded.words()));
+}
+
+#[test]
+fn intel_hex_errors_are_reported_with_line_number() {
+    assert_eq!(
+        Some(FirmwareError::ChecksumMismatch(2)),
+        RomImage::from_intel_hex(":0100050000FA\n:01000400807C\n:00000001FF\n").err()
+    );
+    assert_eq!(
+        Some(FirmwareError::InvalidRecord(1)),
+        RomImage::from_intel_hex("0100050000FA\n").err()
+    );
+    assert_eq!(
+        Some(FirmwareError::InvalidRecord(1)),
+        RomImage::from_intel_hex(":0200050000FA\n").err()
+    );
+    assert_eq!(
+        Some(FirmwareError::InvalidRecord(1)),
+        RomImage::from_intel_hex(":01000500ZZFA\n").err()
+    );
+    assert_eq!(
+        Some(FirmwareError::UnsupportedRecordType(1, 0x06)),
+        RomImage::from_intel_hex(":00000006FA\n").err()
+    );
+    assert_eq!(
+        Some(FirmwareError::MissingEndOfFile),
+        RomImage::from_intel_hex(":0100050000FA\n").err()
+    );
+}
+
+#[test]
+fn intel_hex_addresses_out_of_rom_are_rejected() {
+    assert_eq!(
+        Some(FirmwareError::AddressOutOfRange(1, 0x800)),
+        RomImage::from_intel_hex(":0307FE00010203F2\n:00000001FF\n").err()
+    );
+    assert_eq!(
+        Some(FirmwareError::AddressOutOfRange(2, 0x10004)),
+        RomImage::from_intel_hex(":020000040001F9\n:01000400807B\n:00000001FF\n").err()
+    );
+    assert_eq!(
+        Some(FirmwareError::AddressOutOfRange(2, 0x804)),
+        RomImage::from_intel_hex(":0200000200807C\n:01000400807B\n:00000001FF\n").err()
+    );
+}
+
+#[test]
+fn binary_image_size_is_validated() {
+    let image = RomImage::from_binary(&[0x55, 0x17, 0x3B, 0x00]).unwrap();
+    assert_eq!(Some(0x1755), image.get(0));
+    assert_eq!(Some(0x003B), image.get(1));
+    assert_eq!(2, image.size());
+    assert_eq!(
+        Some(FirmwareError::OddImageSize(3)),
+        RomImage::from_binary(&[0; 3]).err()
+    );
+    assert_eq!(
+        Some(FirmwareError::ImageTooBig(0x802)),
+        RomImage::from_binary(&[0; 0x802]).err()
+    );
+}
+
+#[test]
+fn firmware_file_format_is_chosen_by_extension() {
+    let hex = b":0400000055173B0055\n:00000001FF\n";
+    for name in &["blink.ihx", "/tmp/v1.0/blink.hex"] {
+        match FirmwareFile::parse(name, hex) {
+            Ok(FirmwareFile::EasyPdk(image)) => assert_eq!(Some(0x003B), image.code().get(1)),
+            _ => panic!("{} is not loaded as Intel HEX", name),
+        }
+    }
+    for name in &["blink.bin", "blink.fw", "blink", "hex/blink", "/tmp/v1.0/blink"] {
+        match FirmwareFile::parse(name, &[0x55, 0x17, 0x3B, 0x00]) {
+            Ok(FirmwareFile::Binary(image)) => assert_eq!(Some(0x1755), image.get(0)),
+            _ => panic!("{} is not loaded as binary", name),
+        }
+    }
+    assert_eq!(
+        Some(FirmwareError::InvalidText),
+        FirmwareFile::parse("blink.ihx", &[0xFF, 0xFE]).err()
+    );
+    assert_eq!(
+        Some(FirmwareError::OddImageSize(3)),
+        FirmwareFile::parse("blink.ihx.bin", b":00").err()
+    );
+    // Header of the container should not be loaded as code
+    assert_eq!(
+        Some(FirmwareError::UnsupportedContainer),
+        FirmwareFile::parse("blink.pdk", &[0; 0x40]).err()
+    );
+    assert_eq!(
+        Some(FirmwareError::UnknownFileFormat),
+        FirmwareFile::parse("blink.elf", &[0; 0x40]).err()
+    );
+}
+
+#[test]
+fn easypdk_image_splits_fuse_and_code() {
+    let mut rom = assemble("mov a, #0x55\n goto 0\n .org 0x3FF\n .dw 0x1FEB").unwrap();
+    rom.set(0x3FE, 0x0000);
+    let image = EasyPdkImage::from_rom(rom).unwrap();
+    assert_eq!(Some(0x1FEB), image.fuse());
+    assert_eq!(None, image.code().get(0x3FF));
+    assert_eq!(Some(0x0000), image.code().get(0x3FE));
+    assert_eq!(Some(0x1755), image.code().get(0x000));
+    assert_eq!(0, image.calibrations().count());
+
+    let image = EasyPdkImage::from_rom(assemble("goto 0").unwrap()).unwrap();
+    assert_eq!(None, image.fuse());
+}
+
+#[test]
+fn easypdk_calibration_placeholder_is_found() {
+    let mut source = TextBuffer::new();
+    write!(source, "mov a, #0x55\n{}goto 0", CALIBRATION_PLACEHOLDER).unwrap();
+    let image = EasyPdkImage::from_rom(assemble(source.as_str()).unwrap()).unwrap();
+    let mut calibrations = image.calibrations();
+    assert_eq!(
+        Some(&Calibration {
+            address: 0x001,
+            frequency: 8_000_000,
+            millivolts: 5_000,
+            register: 0x0B,
+        }),
+        calibrations.next()
+    );
+    assert_eq!(None, calibrations.next());
+}
+
+#[test]
+fn easypdk_calibration_placeholder_is_patched() {
+    let mut source = TextBuffer::new();
+    write!(source, "mov a, #0x55\n{}goto 0", CALIBRATION_PLACEHOLDER).unwrap();
+    let image = EasyPdkImage::from_rom(assemble(source.as_str()).unwrap()).unwrap();
+    let code = image.calibrated_code(0x42);
+    assert_eq!(Some(0x1755), code.get(0x000));
+    // mov a, #0x42; mov ihrcr, a
+    assert_eq!(Some(0x1742), code.get(0x001));
+    assert_eq!(Some(0x008B), code.get(0x002));
+    for address in 0x003..0x00B {
+        assert_eq!(Some(0x0000), code.get(address));
+    }
+    assert_eq!(Some(0x1800), code.get(0x00B));
+    // Original code is kept intact
+    assert_eq!(Some(0x1452), image.code().get(0x001));
+    // mov a, #0xff; mov ihrcr, a
+    let programmed = image.programmed_code();
+    assert_eq!(Some(0x17FF), programmed.get(0x001));
+    assert_eq!(Some(0x008B), programmed.get(0x002));
+    assert_eq!(Some(0x1401), programmed.get(0x003));
+    // Patch is programmed by clearing bits only
+    for (address, word) in programmed.words() {
+        assert_eq!(0, code.get(address).unwrap() & !word);
+    }
+}
+
+#[test]
+fn easypdk_unknown_calibration_is_ignored() {
+    let source = "and a, #0x52\n and a, #0x43\n and a, #2\n .dw 0x1400, 0x1400, 0x1400, \
+                  0x1400, 0x1400, 0x1400, 0x1400";
+    let image = EasyPdkImage::from_rom(assemble(source).unwrap()).unwrap();
+    assert_eq!(0, image.calibrations().count());
+}
+
+#[test]
+fn easypdk_calibration_count_is_limited() {
+    let mut source = TextBuffer::new();
+    for _ in 0..5 {
+        source.write_str(CALIBRATION_PLACEHOLDER).unwrap();
+    }
+    assert_eq!(
+        Some(FirmwareError::TooManyCalibrations(40)),
+        EasyPdkImage::from_rom(assemble(source.as_str()).unwrap()).err()
+    );
+}
@@ -1,4 +1,7 @@
+pub mod asm;
 mod bus;
+pub mod disasm;
+mod instruction;
 pub mod ir;
 mod opcode_stamp;
 mod ops;
@@ -9,16 +12,28 @@ pub mod regs;
 #[cfg(test)]
 mod test;
 
+use core::time::Duration;
+
 use failure::Fail;
 
 pub use bus::{Bus, BusExt};
+pub use instruction::Instruction;
 pub use ir::{IrOpcode, IrSlot, IrSlotBuilder};
-pub use pdk_core::PdkCore;
+pub use pdk_core::{DecodeMode, PdkCore};
 
 #[derive(Debug, Fail)]
 pub enum Pdk13Error {
     #[fail(display = "Too big address: {}, address space size: {}", _0, _1)]
     TooBigAddress(usize, usize),
+    #[fail(display = "Illegal instruction {:#06X} at address {:#05X}", word, pc)]
+    IllegalInstruction { pc: RomAddr, word: Word },
+    #[fail(
+        display = "ROM verify failed at address {:#05X}: expected {:#06X}, read {:#06X}",
+        address, expected, actual
+    )]
+    RomVerifyFailed { address: RomAddr, expected: Word, actual: Word },
+    #[fail(display = "Pin input queue is full, change at {:?} is dropped", _0)]
+    InputQueueFull(Duration),
 }
 
 pub type Pdk13Result<T> = Result<T, Pdk13Error>;
@@ -0,0 +1,762 @@
+//! Two pass assembler for pdk13 source in SDAS/free-pdk syntax (the same syntax which is
+//! produced by the disassembler). Supported features:
+//! - labels (`loop:`), constants (`name = expr` or `.equ name, expr`);
+//! - `.org expr` and `.dw expr, ...` directives;
+//! - expressions with `+ - * / % & | ^ << >> ~`, parentheses, current address `.`, IO register
+//!   names and numbers in decimal, `0x` hexadecimal and `0b` binary notation;
+//! - `;` comments.
+//!
+//! IO operands are detected by register names (`mov a, pa`), constants defined as register
+//! names (`led = pa`) or by `.io` mnemonic suffix (`mov.io 0x07, a`, `set1.io pa, #4`). Assembler works without allocator, symbols are kept
+//! in the fixed capacity table
+
+use failure::Fail;
+
+use super::{disasm::io_register_name, Instruction, IoAddr, RamAddr, RomAddr, Word};
+use crate::firmware::{RomImage, ROM_IMAGE_WORDS};
+
+const SYMBOL_TABLE_CAPACITY: usize = 256;
+// Limits recursion while evaluating constants defined through other constants
+const MAX_EXPRESSION_DEPTH: u8 = 16;
+const MAX_MNEMONIC_LENGTH: usize = 16;
+
+#[derive(Debug, Fail, Eq, PartialEq)]
+pub enum AsmErrorKind {
+    #[fail(display = "unknown mnemonic")]
+    UnknownMnemonic,
+    #[fail(display = "unknown directive")]
+    UnknownDirective,
+    #[fail(display = "invalid operands")]
+    InvalidOperands,
+    #[fail(display = "invalid expression")]
+    InvalidExpression,
+    #[fail(display = "undefined symbol")]
+    UndefinedSymbol,
+    #[fail(display = "duplicate symbol")]
+    DuplicateSymbol,
+    #[fail(display = "symbol table is full")]
+    SymbolTableFull,
+    #[fail(display = "operand is out of range")]
+    OperandOutOfRange,
+    #[fail(display = "address is out of ROM address space")]
+    AddressOutOfRange,
+}
+
+#[derive(Debug, Fail, Eq, PartialEq)]
+#[fail(display = "Line {}: {}", line, kind)]
+pub struct AsmError {
+    /// Line number, starting from 1
+    pub line: usize,
+    pub kind: AsmErrorKind,
+}
+
+type AsmResult<T> = Result<T, AsmErrorKind>;
+
+/// Assembles source to ROM image
+pub fn assemble(source: &str) -> Result<RomImage, AsmError> {
+    let mut symbols = SymbolTable::new();
+    let mut image = RomImage::new();
+    run_pass(source, &mut symbols, None)?;
+    run_pass(source, &mut symbols, Some(&mut image))?;
+    Ok(image)
+}
+
+/// Collects symbols on the first pass (`image` is `None`) and emits words on the second one
+fn run_pass<'a>(
+    source: &'a str,
+    symbols: &mut SymbolTable<'a>,
+    mut image: Option<&mut RomImage>,
+) -> Result<(), AsmError> {
+    let mut address = 0;
+    for (index, line) in source.lines().enumerate() {
+        let emit = image.as_deref_mut();
+        assemble_line(line, symbols, &mut address, emit).map_err(|kind| AsmError {
+            line: index + 1,
+            kind,
+        })?;
+    }
+    Ok(())
+}
+
+fn assemble_line<'a>(
+    line: &'a str,
+    symbols: &mut SymbolTable<'a>,
+    address: &mut usize,
+    image: Option<&mut RomImage>,
+) -> AsmResult<()> {
+    let first_pass = image.is_none();
+    let mut statement = strip_comment(line).trim();
+
+    if let Some((label, rest)) = split_label(statement) {
+        if first_pass {
+            symbols.define(label, Symbol::Value(*address as i32))?;
+        }
+        statement = rest.trim();
+    }
+    if statement.is_empty() {
+        return Ok(());
+    }
+
+    if let Some((name, expr)) = split_assignment(statement) {
+        return define_constant(name, expr, symbols, first_pass);
+    }
+
+    let (mnemonic, operands) = split_mnemonic(statement);
+    if mnemonic.starts_with('.') {
+        return assemble_directive(mnemonic, operands, symbols, address, image);
+    }
+
+    if *address >= ROM_IMAGE_WORDS {
+        return Err(AsmErrorKind::AddressOutOfRange);
+    }
+    if let Some(image) = image {
+        let context = Context {
+            symbols,
+            address: *address as i32,
+        };
+        let instruction = parse_instruction(mnemonic, operands, &context)?;
+        image.set(*address as RomAddr, instruction.encode());
+    }
+    *address += 1;
+    Ok(())
+}
+
+fn define_constant<'a>(
+    name: &'a str,
+    expr: &'a str,
+    symbols: &mut SymbolTable<'a>,
+    first_pass: bool,
+) -> AsmResult<()> {
+    if !is_identifier(name) {
+        return Err(AsmErrorKind::InvalidExpression);
+    }
+    if first_pass {
+        // Constants are evaluated lazily, so they could refer to labels defined later
+        symbols.define(name, Symbol::Expression(expr.trim()))?;
+    }
+    Ok(())
+}
+
+fn assemble_directive<'a>(
+    directive: &'a str,
+    operands: &'a str,
+    symbols: &mut SymbolTable<'a>,
+    address: &mut usize,
+    image: Option<&mut RomImage>,
+) -> AsmResult<()> {
+    let first_pass = image.is_none();
+    if directive.eq_ignore_ascii_case(".org") {
+        let context = Context {
+            symbols,
+            address: *address as i32,
+        };
+        let origin = context.evaluate(operands)?;
+        if origin < 0 || origin as usize > ROM_IMAGE_WORDS {
+            return Err(AsmErrorKind::AddressOutOfRange);
+        }
+        *address = origin as usize;
+    } else if directive.eq_ignore_ascii_case(".equ") {
+        let mut parts = operands.splitn(2, ',');
+        let name = parts.next().unwrap_or("").trim();
+        let expr = parts.next().ok_or(AsmErrorKind::InvalidOperands)?;
+        define_constant(name, expr, symbols, first_pass)?;
+    } else if directive.eq_ignore_ascii_case(".dw") || directive.eq_ignore_ascii_case(".word") {
+        let mut image = image;
+        for expr in operands.split(',') {
+            if *address >= ROM_IMAGE_WORDS {
+                return Err(AsmErrorKind::AddressOutOfRange);
+            }
+            if let Some(image) = image.as_mut() {
+                let context = Context {
+                    symbols,
+                    address: *address as i32,
+                };
+                let word = context.evaluate_in_range(expr, 0, 0x1FFF)?;
+                image.set(*address as RomAddr, word as Word);
+            }
+            *address += 1;
+        }
+    } else {
+        return Err(AsmErrorKind::UnknownDirective);
+    }
+    Ok(())
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(';') {
+        Some(position) => &line[..position],
+        None => line,
+    }
+}
+
+/// Splits `label: rest` statement
+fn split_label(statement: &str) -> Option<(&str, &str)> {
+    let position = statement.find(':')?;
+    let label = statement[..position].trim();
+    if is_identifier(label) {
+        Some((label, &statement[position + 1..]))
+    } else {
+        None
+    }
+}
+
+/// Splits `name = expr` statement
+fn split_assignment(statement: &str) -> Option<(&str, &str)> {
+    let position = statement.find('=')?;
+    let name = statement[..position].trim();
+    if is_identifier(name) {
+        Some((name, &statement[position + 1..]))
+    } else {
+        None
+    }
+}
+
+fn split_mnemonic(statement: &str) -> (&str, &str) {
+    match statement.find(char::is_whitespace) {
+        Some(position) => (&statement[..position], statement[position..].trim()),
+        None => (statement, ""),
+    }
+}
+
+fn is_identifier_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
+}
+
+fn is_identifier_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+fn is_identifier(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    !bytes.is_empty()
+        && is_identifier_start(bytes[0])
+        && bytes.iter().all(|c| is_identifier_char(*c))
+}
+
+fn io_register_address(name: &str) -> Option<IoAddr> {
+    (0..0x20).find(|addr| match io_register_name(*addr) {
+        Some(register) => register.eq_ignore_ascii_case(name),
+        None => false,
+    })
+}
+
+#[derive(Copy, Clone)]
+enum Symbol<'a> {
+    Value(i32),
+    Expression(&'a str),
+}
+
+struct SymbolTable<'a> {
+    names: [&'a str; SYMBOL_TABLE_CAPACITY],
+    symbols: [Symbol<'a>; SYMBOL_TABLE_CAPACITY],
+    len: usize,
+}
+
+impl<'a> SymbolTable<'a> {
+    fn new() -> Self {
+        Self {
+            names: [""; SYMBOL_TABLE_CAPACITY],
+            symbols: [Symbol::Value(0); SYMBOL_TABLE_CAPACITY],
+            len: 0,
+        }
+    }
+
+    fn define(&mut self, name: &'a str, symbol: Symbol<'a>) -> AsmResult<()> {
+        if self.get(name).is_some() || io_register_address(name).is_some() {
+            return Err(AsmErrorKind::DuplicateSymbol);
+        }
+        if self.len == SYMBOL_TABLE_CAPACITY {
+            return Err(AsmErrorKind::SymbolTableFull);
+        }
+        self.names[self.len] = name;
+        self.symbols[self.len] = symbol;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Option<Symbol<'a>> {
+        self.names[..self.len]
+            .iter()
+            .position(|defined| *defined == name)
+            .map(|index| self.symbols[index])
+    }
+}
+
+/// Symbols and current address used for expression evaluation
+struct Context<'t, 'a> {
+    symbols: &'t SymbolTable<'a>,
+    address: i32,
+}
+
+impl<'t, 'a> Context<'t, 'a> {
+    fn evaluate(&self, expr: &str) -> AsmResult<i32> {
+        ExpressionParser::new(expr, self, 0).parse()
+    }
+
+    fn evaluate_in_range(&self, expr: &str, min: i32, max: i32) -> AsmResult<i32> {
+        let value = self.evaluate(expr)?;
+        if value < min || value > max {
+            return Err(AsmErrorKind::OperandOutOfRange);
+        }
+        Ok(value)
+    }
+
+    /// Returns true if operand is IO register name or constant which is defined as register
+    /// name directly or through other constants
+    fn is_io_register(&self, operand: &str) -> bool {
+        let mut name = operand.trim();
+        for _ in 0..MAX_EXPRESSION_DEPTH {
+            if io_register_address(name).is_some() {
+                return true;
+            }
+            match self.symbols.get(name) {
+                Some(Symbol::Expression(expr)) => name = expr.trim(),
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    fn resolve(&self, name: &str, depth: u8) -> AsmResult<i32> {
+        match self.symbols.get(name) {
+            Some(Symbol::Value(value)) => Ok(value),
+            Some(Symbol::Expression(expr)) => {
+                if depth >= MAX_EXPRESSION_DEPTH {
+                    return Err(AsmErrorKind::InvalidExpression);
+                }
+                ExpressionParser::new(expr, self, depth + 1).parse()
+            }
+            None => io_register_address(name)
+                .map(|addr| addr as i32)
+                .ok_or(AsmErrorKind::UndefinedSymbol),
+        }
+    }
+}
+
+/// Recursive descent parser with C operator precedence
+struct ExpressionParser<'e, 'c, 't, 'a> {
+    text: &'e [u8],
+    position: usize,
+    context: &'c Context<'t, 'a>,
+    depth: u8,
+}
+
+impl<'e, 'c, 't, 'a> ExpressionParser<'e, 'c, 't, 'a> {
+    fn new(expr: &'e str, context: &'c Context<'t, 'a>, depth: u8) -> Self {
+        Self {
+            text: expr.as_bytes(),
+            position: 0,
+            context,
+            depth,
+        }
+    }
+
+    fn parse(mut self) -> AsmResult<i32> {
+        let value = self.parse_or()?;
+        self.skip_whitespace();
+        if self.position != self.text.len() {
+            return Err(AsmErrorKind::InvalidExpression);
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.position < self.text.len() && self.text[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+    }
+
+    /// Consumes operator if it is next in the input
+    fn accept(&mut self, operator: &str) -> bool {
+        self.skip_whitespace();
+        if self.text[self.position..].starts_with(operator.as_bytes()) {
+            self.position += operator.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> AsmResult<i32> {
+        let mut value = self.parse_xor()?;
+        while self.accept("|") {
+            value |= self.parse_xor()?;
+        }
+        Ok(value)
+    }
+
+    fn parse_xor(&mut self) -> AsmResult<i32> {
+        let mut value = self.parse_and()?;
+        while self.accept("^") {
+            value ^= self.parse_and()?;
+        }
+        Ok(value)
+    }
+
+    fn parse_and(&mut self) -> AsmResult<i32> {
+        let mut value = self.parse_shift()?;
+        while self.accept("&") {
+            value &= self.parse_shift()?;
+        }
+        Ok(value)
+    }
+
+    fn parse_shift(&mut self) -> AsmResult<i32> {
+        let mut value = self.parse_sum()?;
+        loop {
+            if self.accept("<<") {
+                value = value.wrapping_shl(self.parse_sum()? as u32);
+            } else if self.accept(">>") {
+                value = value.wrapping_shr(self.parse_sum()? as u32);
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_sum(&mut self) -> AsmResult<i32> {
+        let mut value = self.parse_product()?;
+        loop {
+            if self.accept("+") {
+                value = value.wrapping_add(self.parse_product()?);
+            } else if self.accept("-") {
+                value = value.wrapping_sub(self.parse_product()?);
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_product(&mut self) -> AsmResult<i32> {
+        let mut value = self.parse_unary()?;
+        loop {
+            if self.accept("*") {
+                value = value.wrapping_mul(self.parse_unary()?);
+            } else if self.accept("/") {
+                value = value
+                    .checked_div(self.parse_unary()?)
+                    .ok_or(AsmErrorKind::InvalidExpression)?;
+            } else if self.accept("%") {
+                value = value
+                    .checked_rem(self.parse_unary()?)
+                    .ok_or(AsmErrorKind::InvalidExpression)?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> AsmResult<i32> {
+        if self.accept("-") {
+            Ok(self.parse_unary()?.wrapping_neg())
+        } else if self.accept("~") {
+            Ok(!self.parse_unary()?)
+        } else if self.accept("+") {
+            self.parse_unary()
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> AsmResult<i32> {
+        if self.accept("(") {
+            let value = self.parse_or()?;
+            if !self.accept(")") {
+                return Err(AsmErrorKind::InvalidExpression);
+            }
+            return Ok(value);
+        }
+        self.skip_whitespace();
+        let start = self.position;
+        while self.position < self.text.len() && is_identifier_char(self.text[self.position]) {
+            self.position += 1;
+        }
+        let token = &self.text[start..self.position];
+        match token.first() {
+            None if self.accept(".") => Ok(self.context.address),
+            None => Err(AsmErrorKind::InvalidExpression),
+            Some(c) if c.is_ascii_digit() => parse_number(token),
+            Some(_) => {
+                // Identifier is built from ASCII characters only
+                let name =
+                    core::str::from_utf8(token).map_err(|_| AsmErrorKind::InvalidExpression)?;
+                self.context.resolve(name, self.depth)
+            }
+        }
+    }
+}
+
+fn parse_number(token: &[u8]) -> AsmResult<i32> {
+    let (digits, radix) = match token {
+        [b'0', b'x', digits @ ..] | [b'0', b'X', digits @ ..] => (digits, 16),
+        [b'0', b'b', digits @ ..] | [b'0', b'B', digits @ ..] => (digits, 2),
+        _ => (token, 10),
+    };
+    let digits = core::str::from_utf8(digits).map_err(|_| AsmErrorKind::InvalidExpression)?;
+    i32::from_str_radix(digits, radix).map_err(|_| AsmErrorKind::InvalidExpression)
+}
+
+#[derive(Copy, Clone)]
+enum Operand<'a> {
+    /// Accumulator `a`
+    Acc,
+    /// `#expr`
+    Immediate(&'a str),
+    /// Memory address, IO address or ROM address expression
+    Address(&'a str),
+}
+
+impl<'a> Operand<'a> {
+    fn parse(text: &'a str) -> AsmResult<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            Err(AsmErrorKind::InvalidOperands)
+        } else if text.eq_ignore_ascii_case("a") {
+            Ok(Operand::Acc)
+        } else if let Some(expr) = text.strip_prefix('#') {
+            Ok(Operand::Immediate(expr))
+        } else {
+            Ok(Operand::Address(text))
+        }
+    }
+}
+
+/// Operand shapes used to select instruction encoding
+enum Operands<'a> {
+    None,
+    Acc,
+    Address(&'a str),
+    Immediate(&'a str),
+    AccImmediate(&'a str),
+    AccAddress(&'a str),
+    AddressAcc(&'a str),
+    AddressImmediate(&'a str, &'a str),
+}
+
+fn parse_operands(text: &str) -> AsmResult<Operands<'_>> {
+    if text.is_empty() {
+        return Ok(Operands::None);
+    }
+    let mut parts = text.splitn(2, ',');
+    let first = Operand::parse(parts.next().unwrap_or(""))?;
+    let second = match parts.next() {
+        Some(text) => Some(Operand::parse(text)?),
+        None => None,
+    };
+    let operands = match (first, second) {
+        (Operand::Acc, None) => Operands::Acc,
+        (Operand::Address(addr), None) => Operands::Address(addr),
+        (Operand::Immediate(value), None) => Operands::Immediate(value),
+        (Operand::Acc, Some(Operand::Immediate(value))) => Operands::AccImmediate(value),
+        (Operand::Acc, Some(Operand::Address(addr))) => Operands::AccAddress(addr),
+        (Operand::Address(addr), Some(Operand::Acc)) => Operands::AddressAcc(addr),
+        (Operand::Address(addr), Some(Operand::Immediate(bit))) => {
+            Operands::AddressImmediate(addr, bit)
+        }
+        _ => return Err(AsmErrorKind::InvalidOperands),
+    };
+    Ok(operands)
+}
+
+fn lowercase_mnemonic(mnemonic: &str, buffer: &mut [u8; MAX_MNEMONIC_LENGTH]) -> AsmResult<usize> {
+    let bytes = mnemonic.as_bytes();
+    if bytes.len() > buffer.len() {
+        return Err(AsmErrorKind::UnknownMnemonic);
+    }
+    for (dst, src) in buffer.iter_mut().zip(bytes.iter()) {
+        *dst = src.to_ascii_lowercase();
+    }
+    Ok(bytes.len())
+}
+
+fn parse_instruction(mnemonic: &str, operands: &str, context: &Context) -> AsmResult<Instruction> {
+    use Instruction::*;
+
+    let mut buffer = [0u8; MAX_MNEMONIC_LENGTH];
+    let len = lowercase_mnemonic(mnemonic, &mut buffer)?;
+    let mnemonic =
+        core::str::from_utf8(&buffer[..len]).map_err(|_| AsmErrorKind::UnknownMnemonic)?;
+    let (mnemonic, io_suffix) = match mnemonic.strip_suffix(".io") {
+        Some(mnemonic) => (mnemonic, true),
+        None => (mnemonic, false),
+    };
+    let is_io = |addr: &str| io_suffix || context.is_io_register(addr);
+    let mem = |addr: &str| {
+        context
+            .evaluate_in_range(addr, 0, 0x3F)
+            .map(|addr| addr as RamAddr)
+    };
+    let io = |addr: &str| {
+        context
+            .evaluate_in_range(addr, 0, 0x1F)
+            .map(|addr| addr as IoAddr)
+    };
+    let immediate = |value: &str| {
+        context
+            .evaluate_in_range(value, -0x80, 0xFF)
+            .map(|value| value as u8)
+    };
+    let bit = |value: &str| {
+        context
+            .evaluate_in_range(value, 0, 7)
+            .map(|value| value as u8)
+    };
+    let rom = |addr: &str| {
+        context
+            .evaluate_in_range(addr, 0, 0x3FF)
+            .map(|addr| addr as RomAddr)
+    };
+    // 16 bit memory operations address word aligned memory
+    let word_mem = |addr: &str| {
+        let addr = context.evaluate_in_range(addr, 0, 0x1E)?;
+        if addr % 2 != 0 {
+            return Err(AsmErrorKind::OperandOutOfRange);
+        }
+        Ok(addr as RamAddr)
+    };
+
+    let operands = parse_operands(operands)?;
+    if io_suffix && !matches!(mnemonic, "mov" | "xor" | "t0sn" | "t1sn" | "set0" | "set1") {
+        return Err(AsmErrorKind::UnknownMnemonic);
+    }
+
+    let instruction = match (mnemonic, operands) {
+        ("nop", Operands::None) => Nop,
+        ("ldsptl", Operands::None) => Ldsptl,
+        ("ldspth", Operands::None) => Ldspth,
+        ("wdreset", Operands::None) => Wdreset,
+        ("pushaf", Operands::None) => PushAf,
+        ("popaf", Operands::None) => PopAf,
+        ("push", Operands::Address(af)) if af.eq_ignore_ascii_case("af") => PushAf,
+        ("pop", Operands::Address(af)) if af.eq_ignore_ascii_case("af") => PopAf,
+        ("reset", Operands::None) => Reset,
+        ("stopsys", Operands::None) => Stopsys,
+        ("stopexe", Operands::None) => Stopexe,
+        ("engint", Operands::None) => Engint,
+        ("disgint", Operands::None) => Disgint,
+        ("ret", Operands::None) => Ret,
+        ("ret", Operands::Immediate(value)) => RetK(immediate(value)?),
+        ("reti", Operands::None) => Reti,
+        ("mul", Operands::None) => Mul,
+
+        ("addc", Operands::Acc) => AddcA,
+        ("subc", Operands::Acc) => SubcA,
+        ("izsn", Operands::Acc) => IzsnA,
+        ("dzsn", Operands::Acc) => DzsnA,
+        ("pcadd", Operands::Acc) => PcaddA,
+        ("not", Operands::Acc) => NotA,
+        ("neg", Operands::Acc) => NegA,
+        ("sr", Operands::Acc) => SrA,
+        ("sl", Operands::Acc) => SlA,
+        ("src", Operands::Acc) => SrcA,
+        ("slc", Operands::Acc) => SlcA,
+        ("swap", Operands::Acc) => SwapA,
+
+        ("xor", Operands::AddressAcc(addr)) if is_io(addr) => XorIoA(io(addr)?),
+        ("mov", Operands::AddressAcc(addr)) if is_io(addr) => MovIoA(io(addr)?),
+        ("mov", Operands::AccAddress(addr)) if is_io(addr) => MovAIo(io(addr)?),
+
+        ("stt16", Operands::Address(addr)) => Stt16(word_mem(addr)?),
+        ("ldt16", Operands::Address(addr)) => Ldt16(word_mem(addr)?),
+        ("idxm", Operands::AddressAcc(addr)) => IdxmMA(word_mem(addr)?),
+        ("idxm", Operands::AccAddress(addr)) => IdxmAM(word_mem(addr)?),
+
+        ("t0sn", Operands::AddressImmediate(addr, n)) if is_io(addr) => T0snIo {
+            addr: io(addr)?,
+            bit: bit(n)?,
+        },
+        ("t1sn", Operands::AddressImmediate(addr, n)) if is_io(addr) => T1snIo {
+            addr: io(addr)?,
+            bit: bit(n)?,
+        },
+        ("set0", Operands::AddressImmediate(addr, n)) if is_io(addr) => Set0Io {
+            addr: io(addr)?,
+            bit: bit(n)?,
+        },
+        ("set1", Operands::AddressImmediate(addr, n)) if is_io(addr) => Set1Io {
+            addr: io(addr)?,
+            bit: bit(n)?,
+        },
+        // `.io` suffix requires IO operand, it is never applied to other forms
+        _ if io_suffix => return Err(AsmErrorKind::InvalidOperands),
+        ("t0sn", Operands::AddressImmediate(addr, n)) => T0snM {
+            addr: bit_mem(context, addr)?,
+            bit: bit(n)?,
+        },
+        ("t1sn", Operands::AddressImmediate(addr, n)) => T1snM {
+            addr: bit_mem(context, addr)?,
+            bit: bit(n)?,
+        },
+        ("set0", Operands::AddressImmediate(addr, n)) => Set0M {
+            addr: bit_mem(context, addr)?,
+            bit: bit(n)?,
+        },
+        ("set1", Operands::AddressImmediate(addr, n)) => Set1M {
+            addr: bit_mem(context, addr)?,
+            bit: bit(n)?,
+        },
+
+        ("add", Operands::AddressAcc(addr)) => AddMA(mem(addr)?),
+        ("sub", Operands::AddressAcc(addr)) => SubMA(mem(addr)?),
+        ("addc", Operands::AddressAcc(addr)) => AddcMA(mem(addr)?),
+        ("subc", Operands::AddressAcc(addr)) => SubcMA(mem(addr)?),
+        ("and", Operands::AddressAcc(addr)) => AndMA(mem(addr)?),
+        ("or", Operands::AddressAcc(addr)) => OrMA(mem(addr)?),
+        ("xor", Operands::AddressAcc(addr)) => XorMA(mem(addr)?),
+        ("mov", Operands::AddressAcc(addr)) => MovMA(mem(addr)?),
+        ("add", Operands::AccAddress(addr)) => AddAM(mem(addr)?),
+        ("sub", Operands::AccAddress(addr)) => SubAM(mem(addr)?),
+        ("addc", Operands::AccAddress(addr)) => AddcAM(mem(addr)?),
+        ("subc", Operands::AccAddress(addr)) => SubcAM(mem(addr)?),
+        ("and", Operands::AccAddress(addr)) => AndAM(mem(addr)?),
+        ("or", Operands::AccAddress(addr)) => OrAM(mem(addr)?),
+        ("xor", Operands::AccAddress(addr)) => XorAM(mem(addr)?),
+        ("mov", Operands::AccAddress(addr)) => MovAM(mem(addr)?),
+        ("ceqsn", Operands::AccAddress(addr)) => CeqsnAM(mem(addr)?),
+
+        ("addc", Operands::Address(addr)) => AddcM(mem(addr)?),
+        ("subc", Operands::Address(addr)) => SubcM(mem(addr)?),
+        ("izsn", Operands::Address(addr)) => IzsnM(mem(addr)?),
+        ("dzsn", Operands::Address(addr)) => DzsnM(mem(addr)?),
+        ("inc", Operands::Address(addr)) => IncM(mem(addr)?),
+        ("dec", Operands::Address(addr)) => DecM(mem(addr)?),
+        ("clear", Operands::Address(addr)) => ClearM(mem(addr)?),
+        ("xch", Operands::Address(addr)) => XchM(mem(addr)?),
+        ("not", Operands::Address(addr)) => NotM(mem(addr)?),
+        ("neg", Operands::Address(addr)) => NegM(mem(addr)?),
+        ("sr", Operands::Address(addr)) => SrM(mem(addr)?),
+        ("sl", Operands::Address(addr)) => SlM(mem(addr)?),
+        ("src", Operands::Address(addr)) => SrcM(mem(addr)?),
+        ("slc", Operands::Address(addr)) => SlcM(mem(addr)?),
+
+        ("add", Operands::AccImmediate(value)) => AddAK(immediate(value)?),
+        ("sub", Operands::AccImmediate(value)) => SubAK(immediate(value)?),
+        ("ceqsn", Operands::AccImmediate(value)) => CeqsnAK(immediate(value)?),
+        ("and", Operands::AccImmediate(value)) => AndAK(immediate(value)?),
+        ("or", Operands::AccImmediate(value)) => OrAK(immediate(value)?),
+        ("xor", Operands::AccImmediate(value)) => XorAK(immediate(value)?),
+        ("mov", Operands::AccImmediate(value)) => MovAK(immediate(value)?),
+
+        ("goto", Operands::Address(addr)) => Goto(rom(addr)?),
+        ("call", Operands::Address(addr)) => Call(rom(addr)?),
+
+        (
+            "nop" | "ldsptl" | "ldspth" | "wdreset" | "pushaf" | "popaf" | "push" | "pop" | "reset"
+            | "stopsys" | "stopexe" | "engint" | "disgint" | "ret" | "reti" | "mul" | "addc"
+            | "subc" | "izsn" | "dzsn" | "pcadd" | "not" | "neg" | "sr" | "sl" | "src" | "slc"
+            | "swap" | "xor" | "mov" | "stt16" | "ldt16" | "idxm" | "t0sn" | "t1sn" | "set0"
+            | "set1" | "add" | "sub" | "and" | "or" | "ceqsn" | "inc" | "dec" | "clear" | "xch"
+            | "goto" | "call",
+            _,
+        ) => return Err(AsmErrorKind::InvalidOperands),
+        _ => return Err(AsmErrorKind::UnknownMnemonic),
+    };
+    Ok(instruction)
+}
+
+/// Bit operations address only first 16 bytes of memory
+fn bit_mem(context: &Context, addr: &str) -> AsmResult<RamAddr> {
+    context
+        .evaluate_in_range(addr, 0, 0x0F)
+        .map(|addr| addr as RamAddr)
+}
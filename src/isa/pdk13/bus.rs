@@ -1,6 +1,6 @@
 use super::{
     ir::IrSlot,
-    regs::{IO_ADDR_FLAGS, IO_ADDR_SP},
+    regs::{IO_ADDR_FLAGS, IO_ADDR_INTEN, IO_ADDR_INTRQ, IO_ADDR_SP},
     Byte, IoAddr, RamAddr, RomAddr, Word,
 };
 use crate::isa::pdk13::regs::{
@@ -24,6 +24,24 @@ pub trait Bus {
     fn stop_exe(&mut self);
     fn stop_sys(&mut self);
     fn wdt_reset(&mut self);
+
+    // Internal accesses of the core and peripherals, implementations could override them to
+    // keep the accesses apart from the ones made by the program
+
+    fn read_flags(&self) -> Byte {
+        self.read_io(IO_ADDR_FLAGS)
+    }
+
+    /// Latches interrupt request bits into INTRQ. Request bits are set regardless of INTEN,
+    /// the same way as peripherals do it on the real hardware
+    fn request_interrupt(&mut self, mask: Byte) {
+        self.write_io(IO_ADDR_INTRQ, self.read_io(IO_ADDR_INTRQ) | mask);
+    }
+
+    /// Returns interrupt requests which are both requested and enabled
+    fn pending_interrupts(&self) -> Byte {
+        self.read_io(IO_ADDR_INTEN) & self.read_io(IO_ADDR_INTRQ)
+    }
 }
 
 pub trait BusExt {
@@ -33,7 +51,6 @@ pub trait BusExt {
     fn read_sp(&self) -> RamAddr;
     fn write_sp(&mut self, addr: RamAddr);
 
-    fn read_flags(&self) -> Byte;
     fn write_flags(&mut self, flags: Byte);
 
     fn is_zero_flag(&self) -> bool;
@@ -70,10 +87,6 @@ where
         self.write_io(IO_ADDR_SP, addr)
     }
 
-    fn read_flags(&self) -> Byte {
-        self.read_io(IO_ADDR_FLAGS)
-    }
-
     fn write_flags(&mut self, flags: u8) {
         self.write_io(IO_ADDR_FLAGS, flags)
     }
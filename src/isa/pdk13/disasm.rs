@@ -0,0 +1,266 @@
+//! Disassembler producing SDAS/free-pdk mnemonic syntax, e.g. `mov a, #0x55`,
+//! `t1sn.io pa, #4` or `goto 0x012`. Output is written to `core::fmt::Write`, so it could be
+//! used without allocator
+
+use core::fmt::{self, Write};
+
+use super::{regs::*, Instruction, IoAddr, RomAddr, Word};
+
+const ROM_ADDRESS_SPACE: usize = 0x400;
+
+/// Returns name of the IO register at the given address
+pub fn io_register_name(addr: IoAddr) -> Option<&'static str> {
+    let name = match addr {
+        IO_ADDR_FLAGS => "flag",
+        IO_ADDR_SP => "sp",
+        IO_ADDR_CLKMD => "clkmd",
+        IO_ADDR_INTEN => "inten",
+        IO_ADDR_INTRQ => "intrq",
+        IO_ADDR_T16M => "t16m",
+        IO_ADDR_TM2B => "tm2b",
+        IO_ADDR_EOSCR => "eoscr",
+        IO_ADDR_IHRCR => "ihrcr",
+        IO_ADDR_INTEGS => "integs",
+        IO_ADDR_PADIER => "padier",
+        IO_ADDR_PA => "pa",
+        IO_ADDR_PAC => "pac",
+        IO_ADDR_PAPH => "paph",
+        IO_ADDR_TM2S => "tm2s",
+        IO_ADDR_GPCC => "gpcc",
+        IO_ADDR_MISC => "misc",
+        IO_ADDR_TM2C => "tm2c",
+        IO_ADDR_TM2CT => "tm2ct",
+        IO_ADDR_GPCS => "gpcs",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Set of `goto`/`call` targets inside ROM image, which get labels in the listing
+pub struct BranchTargets {
+    bits: [u32; ROM_ADDRESS_SPACE / 32],
+    rom_size: usize,
+}
+
+impl BranchTargets {
+    pub fn from_rom(rom: &[Word]) -> Self {
+        let mut targets = Self {
+            bits: [0; ROM_ADDRESS_SPACE / 32],
+            rom_size: rom.len().min(ROM_ADDRESS_SPACE),
+        };
+        for word in rom.iter().copied() {
+            match Instruction::decode(word) {
+                Some(Instruction::Goto(addr)) | Some(Instruction::Call(addr)) => {
+                    targets.insert(addr)
+                }
+                _ => {}
+            }
+        }
+        targets
+    }
+
+    pub fn contains(&self, addr: RomAddr) -> bool {
+        let addr = addr as usize;
+        addr < self.rom_size && self.bits[addr / 32] & (1 << (addr % 32)) != 0
+    }
+
+    fn insert(&mut self, addr: RomAddr) {
+        let addr = addr as usize;
+        if addr < self.rom_size {
+            self.bits[addr / 32] |= 1 << (addr % 32);
+        }
+    }
+}
+
+/// Writes single instruction word. Words which do not encode any instruction are written as
+/// `.dw` data directive
+pub fn disassemble_word(out: &mut impl Write, word: Word) -> fmt::Result {
+    match Instruction::decode(word) {
+        Some(instruction) => write_instruction(out, &instruction, None),
+        None => write!(out, ".dw {:#06x}", word),
+    }
+}
+
+/// Writes listing of the whole ROM image. Branch targets are labeled as `l_XXX`, each line is
+/// followed by the address and original word in the comment
+pub fn disassemble_rom(out: &mut impl Write, rom: &[Word]) -> fmt::Result {
+    let targets = BranchTargets::from_rom(rom);
+    for (addr, word) in rom.iter().copied().enumerate() {
+        if targets.contains(addr as RomAddr) {
+            write_label(out, addr as RomAddr)?;
+            writeln!(out, ":")?;
+        }
+        let mut line = LineWriter::new(out);
+        write!(line, "    ")?;
+        match Instruction::decode(word) {
+            Some(instruction) => write_instruction(&mut line, &instruction, Some(&targets))?,
+            None => write!(line, ".dw {:#06x}", word)?,
+        }
+        let padding = 32usize.saturating_sub(line.written);
+        writeln!(out, "{:padding$}; {:03x}: {:04x}", "", addr, word, padding = padding)?;
+    }
+    Ok(())
+}
+
+/// Writes instruction mnemonic with operands. If `targets` are given, branch targets are
+/// written as labels
+pub fn write_instruction(
+    out: &mut impl Write,
+    instruction: &Instruction,
+    targets: Option<&BranchTargets>,
+) -> fmt::Result {
+    use Instruction::*;
+
+    match *instruction {
+        Nop => write!(out, "nop"),
+        Ldsptl => write!(out, "ldsptl"),
+        Ldspth => write!(out, "ldspth"),
+        AddcA => write!(out, "addc a"),
+        SubcA => write!(out, "subc a"),
+        IzsnA => write!(out, "izsn a"),
+        DzsnA => write!(out, "dzsn a"),
+        PcaddA => write!(out, "pcadd a"),
+        NotA => write!(out, "not a"),
+        NegA => write!(out, "neg a"),
+        SrA => write!(out, "sr a"),
+        SlA => write!(out, "sl a"),
+        SrcA => write!(out, "src a"),
+        SlcA => write!(out, "slc a"),
+        SwapA => write!(out, "swap a"),
+        Wdreset => write!(out, "wdreset"),
+        PushAf => write!(out, "pushaf"),
+        PopAf => write!(out, "popaf"),
+        Reset => write!(out, "reset"),
+        Stopsys => write!(out, "stopsys"),
+        Stopexe => write!(out, "stopexe"),
+        Engint => write!(out, "engint"),
+        Disgint => write!(out, "disgint"),
+        Ret => write!(out, "ret"),
+        Reti => write!(out, "reti"),
+        Mul => write!(out, "mul"),
+        XorIoA(addr) => write!(out, "xor{} {}, a", io_suffix(addr), Io(addr)),
+        MovIoA(addr) => write!(out, "mov{} {}, a", io_suffix(addr), Io(addr)),
+        MovAIo(addr) => write!(out, "mov{} a, {}", io_suffix(addr), Io(addr)),
+        Stt16(addr) => write!(out, "stt16 {:#04x}", addr),
+        Ldt16(addr) => write!(out, "ldt16 {:#04x}", addr),
+        IdxmMA(addr) => write!(out, "idxm {:#04x}, a", addr),
+        IdxmAM(addr) => write!(out, "idxm a, {:#04x}", addr),
+        RetK(value) => write!(out, "ret #{:#04x}", value),
+        T0snM { addr, bit } => write!(out, "t0sn {:#04x}, #{}", addr, bit),
+        T1snM { addr, bit } => write!(out, "t1sn {:#04x}, #{}", addr, bit),
+        Set0M { addr, bit } => write!(out, "set0 {:#04x}, #{}", addr, bit),
+        Set1M { addr, bit } => write!(out, "set1 {:#04x}, #{}", addr, bit),
+        AddMA(addr) => write!(out, "add {:#04x}, a", addr),
+        SubMA(addr) => write!(out, "sub {:#04x}, a", addr),
+        AddcMA(addr) => write!(out, "addc {:#04x}, a", addr),
+        SubcMA(addr) => write!(out, "subc {:#04x}, a", addr),
+        AndMA(addr) => write!(out, "and {:#04x}, a", addr),
+        OrMA(addr) => write!(out, "or {:#04x}, a", addr),
+        XorMA(addr) => write!(out, "xor {:#04x}, a", addr),
+        MovMA(addr) => write!(out, "mov {:#04x}, a", addr),
+        AddAM(addr) => write!(out, "add a, {:#04x}", addr),
+        SubAM(addr) => write!(out, "sub a, {:#04x}", addr),
+        AddcAM(addr) => write!(out, "addc a, {:#04x}", addr),
+        SubcAM(addr) => write!(out, "subc a, {:#04x}", addr),
+        AndAM(addr) => write!(out, "and a, {:#04x}", addr),
+        OrAM(addr) => write!(out, "or a, {:#04x}", addr),
+        XorAM(addr) => write!(out, "xor a, {:#04x}", addr),
+        MovAM(addr) => write!(out, "mov a, {:#04x}", addr),
+        AddcM(addr) => write!(out, "addc {:#04x}", addr),
+        SubcM(addr) => write!(out, "subc {:#04x}", addr),
+        IzsnM(addr) => write!(out, "izsn {:#04x}", addr),
+        DzsnM(addr) => write!(out, "dzsn {:#04x}", addr),
+        IncM(addr) => write!(out, "inc {:#04x}", addr),
+        DecM(addr) => write!(out, "dec {:#04x}", addr),
+        ClearM(addr) => write!(out, "clear {:#04x}", addr),
+        XchM(addr) => write!(out, "xch {:#04x}", addr),
+        NotM(addr) => write!(out, "not {:#04x}", addr),
+        NegM(addr) => write!(out, "neg {:#04x}", addr),
+        SrM(addr) => write!(out, "sr {:#04x}", addr),
+        SlM(addr) => write!(out, "sl {:#04x}", addr),
+        SrcM(addr) => write!(out, "src {:#04x}", addr),
+        SlcM(addr) => write!(out, "slc {:#04x}", addr),
+        CeqsnAM(addr) => write!(out, "ceqsn a, {:#04x}", addr),
+        T0snIo { addr, bit } => write!(out, "t0sn.io {}, #{}", Io(addr), bit),
+        T1snIo { addr, bit } => write!(out, "t1sn.io {}, #{}", Io(addr), bit),
+        Set0Io { addr, bit } => write!(out, "set0.io {}, #{}", Io(addr), bit),
+        Set1Io { addr, bit } => write!(out, "set1.io {}, #{}", Io(addr), bit),
+        AddAK(value) => write!(out, "add a, #{:#04x}", value),
+        SubAK(value) => write!(out, "sub a, #{:#04x}", value),
+        CeqsnAK(value) => write!(out, "ceqsn a, #{:#04x}", value),
+        AndAK(value) => write!(out, "and a, #{:#04x}", value),
+        OrAK(value) => write!(out, "or a, #{:#04x}", value),
+        XorAK(value) => write!(out, "xor a, #{:#04x}", value),
+        MovAK(value) => write!(out, "mov a, #{:#04x}", value),
+        Goto(addr) => {
+            write!(out, "goto ")?;
+            write_rom_address(out, addr, targets)
+        }
+        Call(addr) => {
+            write!(out, "call ")?;
+            write_rom_address(out, addr, targets)
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_instruction(f, self, None)
+    }
+}
+
+/// IO address operand, written as register name if it is known
+struct Io(IoAddr);
+
+impl fmt::Display for Io {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match io_register_name(self.0) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:#04x}", self.0),
+        }
+    }
+}
+
+/// Unnamed IO addresses of `mov` and `xor` are marked with `.io` suffix to distinguish them
+/// from memory addresses
+fn io_suffix(addr: IoAddr) -> &'static str {
+    if io_register_name(addr).is_some() {
+        ""
+    } else {
+        ".io"
+    }
+}
+
+fn write_label(out: &mut impl Write, addr: RomAddr) -> fmt::Result {
+    write!(out, "l_{:03x}", addr)
+}
+
+fn write_rom_address(
+    out: &mut impl Write,
+    addr: RomAddr,
+    targets: Option<&BranchTargets>,
+) -> fmt::Result {
+    match targets {
+        Some(targets) if targets.contains(addr) => write_label(out, addr),
+        _ => write!(out, "{:#05x}", addr),
+    }
+}
+
+/// Counts characters written to the line to align listing comments
+struct LineWriter<'a, W: Write> {
+    out: &'a mut W,
+    written: usize,
+}
+
+impl<'a, W: Write> LineWriter<'a, W> {
+    fn new(out: &'a mut W) -> Self {
+        Self { out, written: 0 }
+    }
+}
+
+impl<'a, W: Write> Write for LineWriter<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.written += s.len();
+        self.out.write_str(s)
+    }
+}
@@ -0,0 +1,408 @@
+//! Typed representation of pdk13 instructions for tools (disassemblers, analysers, test
+//! generators). Emulator itself uses packed `IrSlot` representation
+
+use super::{
+    ir::{generate_ir, IrOpcode},
+    opcode_stamp::{Operands, OPCODE_STAMPS},
+    Byte, IoAddr, RamAddr, RomAddr, Word,
+};
+
+const PDK13_WORD_MASK: Word = 0b0001111111111111;
+
+/// Decoded pdk13 instruction with typed operands. Memory operand of 16 bit memory operations
+/// (`stt16`, `ldt16`, `idxm`) is word aligned
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Instruction {
+    /// `nop`
+    Nop,
+    /// `ldsptl`
+    Ldsptl,
+    /// `ldspth`
+    Ldspth,
+    /// `addc a`
+    AddcA,
+    /// `subc a`
+    SubcA,
+    /// `izsn a`
+    IzsnA,
+    /// `dzsn a`
+    DzsnA,
+    /// `pcadd a`
+    PcaddA,
+    /// `not a`
+    NotA,
+    /// `neg a`
+    NegA,
+    /// `sr a`
+    SrA,
+    /// `sl a`
+    SlA,
+    /// `src a`
+    SrcA,
+    /// `slc a`
+    SlcA,
+    /// `swap a`
+    SwapA,
+    /// `wdreset`
+    Wdreset,
+    /// `pushaf`
+    PushAf,
+    /// `popaf`
+    PopAf,
+    /// `reset`
+    Reset,
+    /// `stopsys`
+    Stopsys,
+    /// `stopexe`
+    Stopexe,
+    /// `engint`
+    Engint,
+    /// `disgint`
+    Disgint,
+    /// `ret`
+    Ret,
+    /// `reti`
+    Reti,
+    /// `mul`
+    Mul,
+    /// `xor io, a`
+    XorIoA(IoAddr),
+    /// `mov io, a`
+    MovIoA(IoAddr),
+    /// `mov a, io`
+    MovAIo(IoAddr),
+    /// `stt16 m`
+    Stt16(RamAddr),
+    /// `ldt16 m`
+    Ldt16(RamAddr),
+    /// `idxm m, a`
+    IdxmMA(RamAddr),
+    /// `idxm a, m`
+    IdxmAM(RamAddr),
+    /// `ret k`
+    RetK(Byte),
+    /// `t0sn m.n`
+    T0snM { addr: RamAddr, bit: u8 },
+    /// `t1sn m.n`
+    T1snM { addr: RamAddr, bit: u8 },
+    /// `set0 m.n`
+    Set0M { addr: RamAddr, bit: u8 },
+    /// `set1 m.n`
+    Set1M { addr: RamAddr, bit: u8 },
+    /// `add m, a`
+    AddMA(RamAddr),
+    /// `sub m, a`
+    SubMA(RamAddr),
+    /// `addc m, a`
+    AddcMA(RamAddr),
+    /// `subc m, a`
+    SubcMA(RamAddr),
+    /// `and m, a`
+    AndMA(RamAddr),
+    /// `or m, a`
+    OrMA(RamAddr),
+    /// `xor m, a`
+    XorMA(RamAddr),
+    /// `mov m, a`
+    MovMA(RamAddr),
+    /// `add a, m`
+    AddAM(RamAddr),
+    /// `sub a, m`
+    SubAM(RamAddr),
+    /// `addc a, m`
+    AddcAM(RamAddr),
+    /// `subc a, m`
+    SubcAM(RamAddr),
+    /// `and a, m`
+    AndAM(RamAddr),
+    /// `or a, m`
+    OrAM(RamAddr),
+    /// `xor a, m`
+    XorAM(RamAddr),
+    /// `mov a, m`
+    MovAM(RamAddr),
+    /// `addc m`
+    AddcM(RamAddr),
+    /// `subc m`
+    SubcM(RamAddr),
+    /// `izsn m`
+    IzsnM(RamAddr),
+    /// `dzsn m`
+    DzsnM(RamAddr),
+    /// `inc m`
+    IncM(RamAddr),
+    /// `dec m`
+    DecM(RamAddr),
+    /// `clear m`
+    ClearM(RamAddr),
+    /// `xch m`
+    XchM(RamAddr),
+    /// `not m`
+    NotM(RamAddr),
+    /// `neg m`
+    NegM(RamAddr),
+    /// `sr m`
+    SrM(RamAddr),
+    /// `sl m`
+    SlM(RamAddr),
+    /// `src m`
+    SrcM(RamAddr),
+    /// `slc m`
+    SlcM(RamAddr),
+    /// `ceqsn a, m`
+    CeqsnAM(RamAddr),
+    /// `t0sn io.n`
+    T0snIo { addr: IoAddr, bit: u8 },
+    /// `t1sn io.n`
+    T1snIo { addr: IoAddr, bit: u8 },
+    /// `set0 io.n`
+    Set0Io { addr: IoAddr, bit: u8 },
+    /// `set1 io.n`
+    Set1Io { addr: IoAddr, bit: u8 },
+    /// `add a, k`
+    AddAK(Byte),
+    /// `sub a, k`
+    SubAK(Byte),
+    /// `ceqsn a, k`
+    CeqsnAK(Byte),
+    /// `and a, k`
+    AndAK(Byte),
+    /// `or a, k`
+    OrAK(Byte),
+    /// `xor a, k`
+    XorAK(Byte),
+    /// `mov a, k`
+    MovAK(Byte),
+    /// `goto k`
+    Goto(RomAddr),
+    /// `call k`
+    Call(RomAddr),
+}
+
+impl Instruction {
+    /// Decodes 13 bit instruction word, higher bits are ignored. Returns `None` if word does
+    /// not encode any instruction
+    pub fn decode(word: Word) -> Option<Self> {
+        let ir = generate_ir(word & PDK13_WORD_MASK);
+        let instruction = match ir.ir_opcode() {
+            IrOpcode::Nop => Self::Nop,
+            IrOpcode::Ldsptl => Self::Ldsptl,
+            IrOpcode::Ldspth => Self::Ldspth,
+            IrOpcode::Addca => Self::AddcA,
+            IrOpcode::Subca => Self::SubcA,
+            IrOpcode::Izsna => Self::IzsnA,
+            IrOpcode::Dzsna => Self::DzsnA,
+            IrOpcode::Pcadda => Self::PcaddA,
+            IrOpcode::Nota => Self::NotA,
+            IrOpcode::Nega => Self::NegA,
+            IrOpcode::Sra => Self::SrA,
+            IrOpcode::Sla => Self::SlA,
+            IrOpcode::Srca => Self::SrcA,
+            IrOpcode::Slca => Self::SlcA,
+            IrOpcode::Swapa => Self::SwapA,
+            IrOpcode::Wdreset => Self::Wdreset,
+            IrOpcode::Pushaf => Self::PushAf,
+            IrOpcode::Popaf => Self::PopAf,
+            IrOpcode::Reset => Self::Reset,
+            IrOpcode::Stopsys => Self::Stopsys,
+            IrOpcode::Stopexe => Self::Stopexe,
+            IrOpcode::Engint => Self::Engint,
+            IrOpcode::Disgint => Self::Disgint,
+            IrOpcode::Ret => Self::Ret,
+            IrOpcode::Reti => Self::Reti,
+            IrOpcode::Mul => Self::Mul,
+            IrOpcode::Xorioa => Self::XorIoA(ir.io_address()),
+            IrOpcode::Movioa => Self::MovIoA(ir.io_address()),
+            IrOpcode::Movaio => Self::MovAIo(ir.io_address()),
+            IrOpcode::Stt16 => Self::Stt16(ir.mem_address()),
+            IrOpcode::Ldt16 => Self::Ldt16(ir.mem_address()),
+            IrOpcode::Idxmma => Self::IdxmMA(ir.mem_address()),
+            IrOpcode::Idxmam => Self::IdxmAM(ir.mem_address()),
+            IrOpcode::Retk => Self::RetK(ir.immediate()),
+            IrOpcode::T0snm => Self::T0snM {
+                addr: ir.mem_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::T1snm => Self::T1snM {
+                addr: ir.mem_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::Set0m => Self::Set0M {
+                addr: ir.mem_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::Set1m => Self::Set1M {
+                addr: ir.mem_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::Addma => Self::AddMA(ir.mem_address()),
+            IrOpcode::Subma => Self::SubMA(ir.mem_address()),
+            IrOpcode::Addcma => Self::AddcMA(ir.mem_address()),
+            IrOpcode::Subcma => Self::SubcMA(ir.mem_address()),
+            IrOpcode::Andma => Self::AndMA(ir.mem_address()),
+            IrOpcode::Orma => Self::OrMA(ir.mem_address()),
+            IrOpcode::Xorma => Self::XorMA(ir.mem_address()),
+            IrOpcode::Movma => Self::MovMA(ir.mem_address()),
+            IrOpcode::Addam => Self::AddAM(ir.mem_address()),
+            IrOpcode::Subam => Self::SubAM(ir.mem_address()),
+            IrOpcode::Addcam => Self::AddcAM(ir.mem_address()),
+            IrOpcode::Subcam => Self::SubcAM(ir.mem_address()),
+            IrOpcode::Andam => Self::AndAM(ir.mem_address()),
+            IrOpcode::Oram => Self::OrAM(ir.mem_address()),
+            IrOpcode::Xoram => Self::XorAM(ir.mem_address()),
+            IrOpcode::Movam => Self::MovAM(ir.mem_address()),
+            IrOpcode::Addcm => Self::AddcM(ir.mem_address()),
+            IrOpcode::Subcm => Self::SubcM(ir.mem_address()),
+            IrOpcode::Izsnm => Self::IzsnM(ir.mem_address()),
+            IrOpcode::Dzsnm => Self::DzsnM(ir.mem_address()),
+            IrOpcode::Incm => Self::IncM(ir.mem_address()),
+            IrOpcode::Decm => Self::DecM(ir.mem_address()),
+            IrOpcode::Clearm => Self::ClearM(ir.mem_address()),
+            IrOpcode::Xchm => Self::XchM(ir.mem_address()),
+            IrOpcode::Notm => Self::NotM(ir.mem_address()),
+            IrOpcode::Negm => Self::NegM(ir.mem_address()),
+            IrOpcode::Srm => Self::SrM(ir.mem_address()),
+            IrOpcode::Slm => Self::SlM(ir.mem_address()),
+            IrOpcode::Srcm => Self::SrcM(ir.mem_address()),
+            IrOpcode::Slcm => Self::SlcM(ir.mem_address()),
+            IrOpcode::Ceqsnam => Self::CeqsnAM(ir.mem_address()),
+            IrOpcode::T0snio => Self::T0snIo {
+                addr: ir.io_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::T1snio => Self::T1snIo {
+                addr: ir.io_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::Set0io => Self::Set0Io {
+                addr: ir.io_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::Set1io => Self::Set1Io {
+                addr: ir.io_address(),
+                bit: ir.bit_index(),
+            },
+            IrOpcode::Addak => Self::AddAK(ir.immediate()),
+            IrOpcode::Subak => Self::SubAK(ir.immediate()),
+            IrOpcode::Ceqsnak => Self::CeqsnAK(ir.immediate()),
+            IrOpcode::Andak => Self::AndAK(ir.immediate()),
+            IrOpcode::Orak => Self::OrAK(ir.immediate()),
+            IrOpcode::Xorak => Self::XorAK(ir.immediate()),
+            IrOpcode::Movak => Self::MovAK(ir.immediate()),
+            IrOpcode::Goto => Self::Goto(ir.rom_address()),
+            IrOpcode::Call => Self::Call(ir.rom_address()),
+            IrOpcode::Invalid => return None,
+        };
+        Some(instruction)
+    }
+
+    /// Encodes instruction to 13 bit word. Operands are truncated to the width of their
+    /// instruction fields
+    pub fn encode(&self) -> Word {
+        let (opcode, operand, bit) = self.parts();
+        let stamp = OPCODE_STAMPS
+            .iter()
+            .find(|stamp| stamp.opcode == opcode)
+            .expect("Each instruction should have opcode stamp");
+        let bit = (bit as Word & 0b111) << 5;
+        let operands = match stamp.operands {
+            Operands::None => 0,
+            Operands::Io => operand & 0b11111,
+            Operands::WordMem => operand & 0b11110,
+            Operands::Mem => operand & 0b111111,
+            Operands::MemBit => (operand & 0b1111) | bit,
+            Operands::IoBit => (operand & 0b11111) | bit,
+            Operands::Immediate => operand & 0b11111111,
+            Operands::RomAddress => operand & 0b1111111111,
+        };
+        stamp.stamp | operands
+    }
+
+    /// Returns IR opcode, address or immediate operand and bit index operand
+    fn parts(&self) -> (IrOpcode, Word, u8) {
+        match *self {
+            Self::Nop => (IrOpcode::Nop, 0, 0),
+            Self::Ldsptl => (IrOpcode::Ldsptl, 0, 0),
+            Self::Ldspth => (IrOpcode::Ldspth, 0, 0),
+            Self::AddcA => (IrOpcode::Addca, 0, 0),
+            Self::SubcA => (IrOpcode::Subca, 0, 0),
+            Self::IzsnA => (IrOpcode::Izsna, 0, 0),
+            Self::DzsnA => (IrOpcode::Dzsna, 0, 0),
+            Self::PcaddA => (IrOpcode::Pcadda, 0, 0),
+            Self::NotA => (IrOpcode::Nota, 0, 0),
+            Self::NegA => (IrOpcode::Nega, 0, 0),
+            Self::SrA => (IrOpcode::Sra, 0, 0),
+            Self::SlA => (IrOpcode::Sla, 0, 0),
+            Self::SrcA => (IrOpcode::Srca, 0, 0),
+            Self::SlcA => (IrOpcode::Slca, 0, 0),
+            Self::SwapA => (IrOpcode::Swapa, 0, 0),
+            Self::Wdreset => (IrOpcode::Wdreset, 0, 0),
+            Self::PushAf => (IrOpcode::Pushaf, 0, 0),
+            Self::PopAf => (IrOpcode::Popaf, 0, 0),
+            Self::Reset => (IrOpcode::Reset, 0, 0),
+            Self::Stopsys => (IrOpcode::Stopsys, 0, 0),
+            Self::Stopexe => (IrOpcode::Stopexe, 0, 0),
+            Self::Engint => (IrOpcode::Engint, 0, 0),
+            Self::Disgint => (IrOpcode::Disgint, 0, 0),
+            Self::Ret => (IrOpcode::Ret, 0, 0),
+            Self::Reti => (IrOpcode::Reti, 0, 0),
+            Self::Mul => (IrOpcode::Mul, 0, 0),
+            Self::XorIoA(addr) => (IrOpcode::Xorioa, addr as Word, 0),
+            Self::MovIoA(addr) => (IrOpcode::Movioa, addr as Word, 0),
+            Self::MovAIo(addr) => (IrOpcode::Movaio, addr as Word, 0),
+            Self::Stt16(addr) => (IrOpcode::Stt16, addr as Word, 0),
+            Self::Ldt16(addr) => (IrOpcode::Ldt16, addr as Word, 0),
+            Self::IdxmMA(addr) => (IrOpcode::Idxmma, addr as Word, 0),
+            Self::IdxmAM(addr) => (IrOpcode::Idxmam, addr as Word, 0),
+            Self::RetK(value) => (IrOpcode::Retk, value as Word, 0),
+            Self::T0snM { addr, bit } => (IrOpcode::T0snm, addr as Word, bit),
+            Self::T1snM { addr, bit } => (IrOpcode::T1snm, addr as Word, bit),
+            Self::Set0M { addr, bit } => (IrOpcode::Set0m, addr as Word, bit),
+            Self::Set1M { addr, bit } => (IrOpcode::Set1m, addr as Word, bit),
+            Self::AddMA(addr) => (IrOpcode::Addma, addr as Word, 0),
+            Self::SubMA(addr) => (IrOpcode::Subma, addr as Word, 0),
+            Self::AddcMA(addr) => (IrOpcode::Addcma, addr as Word, 0),
+            Self::SubcMA(addr) => (IrOpcode::Subcma, addr as Word, 0),
+            Self::AndMA(addr) => (IrOpcode::Andma, addr as Word, 0),
+            Self::OrMA(addr) => (IrOpcode::Orma, addr as Word, 0),
+            Self::XorMA(addr) => (IrOpcode::Xorma, addr as Word, 0),
+            Self::MovMA(addr) => (IrOpcode::Movma, addr as Word, 0),
+            Self::AddAM(addr) => (IrOpcode::Addam, addr as Word, 0),
+            Self::SubAM(addr) => (IrOpcode::Subam, addr as Word, 0),
+            Self::AddcAM(addr) => (IrOpcode::Addcam, addr as Word, 0),
+            Self::SubcAM(addr) => (IrOpcode::Subcam, addr as Word, 0),
+            Self::AndAM(addr) => (IrOpcode::Andam, addr as Word, 0),
+            Self::OrAM(addr) => (IrOpcode::Oram, addr as Word, 0),
+            Self::XorAM(addr) => (IrOpcode::Xoram, addr as Word, 0),
+            Self::MovAM(addr) => (IrOpcode::Movam, addr as Word, 0),
+            Self::AddcM(addr) => (IrOpcode::Addcm, addr as Word, 0),
+            Self::SubcM(addr) => (IrOpcode::Subcm, addr as Word, 0),
+            Self::IzsnM(addr) => (IrOpcode::Izsnm, addr as Word, 0),
+            Self::DzsnM(addr) => (IrOpcode::Dzsnm, addr as Word, 0),
+            Self::IncM(addr) => (IrOpcode::Incm, addr as Word, 0),
+            Self::DecM(addr) => (IrOpcode::Decm, addr as Word, 0),
+            Self::ClearM(addr) => (IrOpcode::Clearm, addr as Word, 0),
+            Self::XchM(addr) => (IrOpcode::Xchm, addr as Word, 0),
+            Self::NotM(addr) => (IrOpcode::Notm, addr as Word, 0),
+            Self::NegM(addr) => (IrOpcode::Negm, addr as Word, 0),
+            Self::SrM(addr) => (IrOpcode::Srm, addr as Word, 0),
+            Self::SlM(addr) => (IrOpcode::Slm, addr as Word, 0),
+            Self::SrcM(addr) => (IrOpcode::Srcm, addr as Word, 0),
+            Self::SlcM(addr) => (IrOpcode::Slcm, addr as Word, 0),
+            Self::CeqsnAM(addr) => (IrOpcode::Ceqsnam, addr as Word, 0),
+            Self::T0snIo { addr, bit } => (IrOpcode::T0snio, addr as Word, bit),
+            Self::T1snIo { addr, bit } => (IrOpcode::T1snio, addr as Word, bit),
+            Self::Set0Io { addr, bit } => (IrOpcode::Set0io, addr as Word, bit),
+            Self::Set1Io { addr, bit } => (IrOpcode::Set1io, addr as Word, bit),
+            Self::AddAK(value) => (IrOpcode::Addak, value as Word, 0),
+            Self::SubAK(value) => (IrOpcode::Subak, value as Word, 0),
+            Self::CeqsnAK(value) => (IrOpcode::Ceqsnak, value as Word, 0),
+            Self::AndAK(value) => (IrOpcode::Andak, value as Word, 0),
+            Self::OrAK(value) => (IrOpcode::Orak, value as Word, 0),
+            Self::XorAK(value) => (IrOpcode::Xorak, value as Word, 0),
+            Self::MovAK(value) => (IrOpcode::Movak, value as Word, 0),
+            Self::Goto(addr) => (IrOpcode::Goto, addr, 0),
+            Self::Call(addr) => (IrOpcode::Call, addr, 0),
+        }
+    }
+}
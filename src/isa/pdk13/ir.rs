@@ -14,7 +14,10 @@
 //! x => lo operand part (used alone as mem/io address)
 //! x | y => used together as 10 bit address
 
-use super::{opcode_stamp::OpcodeStamp, Pdk13Error, Pdk13Result, Word};
+use super::{
+    opcode_stamp::{OpcodeStamp, Operands},
+    Word,
+};
 
 const PDK13_WORD_MASK: Word = 0b0001111111111111;
 
@@ -142,7 +145,7 @@ impl IrSlotBuilder {
 
 #[non_exhaustive]
 #[repr(u8)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum IrOpcode {
     Nop,
     Ldsptl,
@@ -226,116 +229,143 @@ pub enum IrOpcode {
     Movak,
     Goto,
     Call,
+    /// Word which does not encode any instruction
+    Invalid,
 }
 
+/// IR opcodes indexed by their discriminant
+const IR_OPCODES: [IrOpcode; 83] = [
+    IrOpcode::Nop,
+    IrOpcode::Ldsptl,
+    IrOpcode::Ldspth,
+    IrOpcode::Addca,
+    IrOpcode::Subca,
+    IrOpcode::Izsna,
+    IrOpcode::Dzsna,
+    IrOpcode::Pcadda,
+    IrOpcode::Nota,
+    IrOpcode::Nega,
+    IrOpcode::Sra,
+    IrOpcode::Sla,
+    IrOpcode::Srca,
+    IrOpcode::Slca,
+    IrOpcode::Swapa,
+    IrOpcode::Wdreset,
+    IrOpcode::Pushaf,
+    IrOpcode::Popaf,
+    IrOpcode::Reset,
+    IrOpcode::Stopsys,
+    IrOpcode::Stopexe,
+    IrOpcode::Engint,
+    IrOpcode::Disgint,
+    IrOpcode::Ret,
+    IrOpcode::Reti,
+    IrOpcode::Mul,
+    IrOpcode::Xorioa,
+    IrOpcode::Movioa,
+    IrOpcode::Movaio,
+    IrOpcode::Stt16,
+    IrOpcode::Ldt16,
+    IrOpcode::Idxmma,
+    IrOpcode::Idxmam,
+    IrOpcode::Retk,
+    IrOpcode::T0snm,
+    IrOpcode::T1snm,
+    IrOpcode::Set0m,
+    IrOpcode::Set1m,
+    IrOpcode::Addma,
+    IrOpcode::Subma,
+    IrOpcode::Addcma,
+    IrOpcode::Subcma,
+    IrOpcode::Andma,
+    IrOpcode::Orma,
+    IrOpcode::Xorma,
+    IrOpcode::Movma,
+    IrOpcode::Addam,
+    IrOpcode::Subam,
+    IrOpcode::Addcam,
+    IrOpcode::Subcam,
+    IrOpcode::Andam,
+    IrOpcode::Oram,
+    IrOpcode::Xoram,
+    IrOpcode::Movam,
+    IrOpcode::Addcm,
+    IrOpcode::Subcm,
+    IrOpcode::Izsnm,
+    IrOpcode::Dzsnm,
+    IrOpcode::Incm,
+    IrOpcode::Decm,
+    IrOpcode::Clearm,
+    IrOpcode::Xchm,
+    IrOpcode::Notm,
+    IrOpcode::Negm,
+    IrOpcode::Srm,
+    IrOpcode::Slm,
+    IrOpcode::Srcm,
+    IrOpcode::Slcm,
+    IrOpcode::Ceqsnam,
+    IrOpcode::T0snio,
+    IrOpcode::T1snio,
+    IrOpcode::Set0io,
+    IrOpcode::Set1io,
+    IrOpcode::Addak,
+    IrOpcode::Subak,
+    IrOpcode::Ceqsnak,
+    IrOpcode::Andak,
+    IrOpcode::Orak,
+    IrOpcode::Xorak,
+    IrOpcode::Movak,
+    IrOpcode::Goto,
+    IrOpcode::Call,
+    IrOpcode::Invalid,
+];
+
 impl IrOpcode {
-    // Function is perfectly safe as long as IrOpcode is non-exhaustive
+    /// Returns opcode with the given discriminant, `Invalid` for out of range values
     fn from_primitive(value: u8) -> IrOpcode {
-        unsafe { core::mem::transmute(value) }
+        IR_OPCODES
+            .get(value as usize)
+            .copied()
+            .unwrap_or(IrOpcode::Invalid)
     }
 }
 
 pub(crate) fn generate_ir(instruction: Word) -> IrSlot {
-    const MISC_GROUP_MASK: u16 = 0b111_1111111000000;
-    const MISC_GROUP_STAMP: u16 = 0b000_0000000000000;
-
-    const XOR_IO_GROUP_MASK: u16 = 0b111_1111111000000;
-    const XOR_IO_GROUP_STAMP: u16 = 0b000_0000001000000;
-    const XOR_IO_OPCODE_MASK: u16 = 0b111_1111111100000;
-
-    const MOV_IO_GROUP_MASK: u16 = 0b111_1111111000000;
-    const MOV_IO_GROUP_STAMP: u16 = 0b000_0000010000000;
-    const MOV_IO_OPCODE_MASK: u16 = 0b111_1111111100000;
-
-    const MEM16_GROUP_MASK: u16 = 0b111_1111111000000;
-    const MEM16_GROUP_STAMP: u16 = 0b000_0000011000000;
-    const MEM16_OPCODE_MASK: u16 = 0b111_1111111100001;
-
-    const RET_CONST_GROUP_MASK: u16 = 0b111_1111100000000;
-    const RET_CONST_GROUP_STAMP: u16 = 0b000_0000100000000;
-    const RET_CONST_OPCODE_MASK: u16 = 0b111_1111100000000;
-
-    const MEM_BIT_OPS_GROUP_MASK: u16 = 0b111_1111000000000;
-    const MEM_BIT_OPS_GROUP_STAMP: u16 = 0b000_0001000000000;
-    const MEM_BIT_OPS_OPCODE_MASK: u16 = 0b111_1111100010000;
-
-    const MEM_AND_ACC_GROUP_MASK: u16 = 0b111_1110000000000;
-    const MEM_AND_ACC_GROUP_STAMP: u16 = 0b000_0010000000000;
-    const MEM_AND_ACC_OPCODE_MASK: u16 = 0b111_1111111000000;
-
-    const MEM_GROUP_MASK: u16 = 0b111_1110000000000;
-    const MEM_GROUP_STAMP: u16 = 0b000_0100000000000;
-    const MEM_OPCODE_MASK: u16 = 0b111_1111111000000;
-
-    const IO_BIT_OPS_GROUP_MASK: u16 = 0b111_1110000000000;
-    const IO_BIT_OPS_GROUP_STAMP: u16 = 0b000_0110000000000;
-    const IO_BIT_OPS_OPCODE_MASK: u16 = 0b111_1111100000000;
-
-    const ACC_CONST_GROUP_MASK: u16 = 0b111_1100000000000;
-    const ACC_CONST_GROUP_STAMP: u16 = 0b000_1000000000000;
-    const ACC_CONST_OPCODE_MASK: u16 = 0b111_1111100000000;
-
-    const JUMP_GROUP_MASK: u16 = 0b111_1100000000000;
-    const JUMP_GROUP_STAMP: u16 = 0b000_1100000000000;
-    const JUMP_OPCODE_MASK: u16 = 0b111_1110000000000;
-
     let mut ir_builder = IrSlotBuilder::new();
     ir_builder.original_word(instruction);
-    let opcode_stamp;
-
-    if instruction & MISC_GROUP_MASK == MISC_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction);
-    // No operands
-    } else if instruction & XOR_IO_GROUP_MASK == XOR_IO_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & XOR_IO_OPCODE_MASK);
-        // Operand 1: 5 bit io address at offset 0
-        ir_builder.io_address((instruction & 0b11111) as u8);
-    } else if instruction & MOV_IO_GROUP_MASK == MOV_IO_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & MOV_IO_OPCODE_MASK);
-        // Operand 1: 5 bit io address at offset 0
-        ir_builder.io_address((instruction & 0b11111) as u8);
-    } else if instruction & MEM16_GROUP_MASK == MEM16_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & MEM16_OPCODE_MASK);
-        // Operand 1: 5 bit memory address at offset 0; lsb should be ignored (word aligned address)
-        ir_builder.mem_address((instruction & 0b11110) as u8);
-    } else if instruction & RET_CONST_GROUP_MASK == RET_CONST_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & RET_CONST_OPCODE_MASK);
-        // Operand 1: 8 bit immediate at offset 0
-        ir_builder.immediate((instruction & 0b11111111) as u8);
-    } else if instruction & MEM_BIT_OPS_GROUP_MASK == MEM_BIT_OPS_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & MEM_BIT_OPS_OPCODE_MASK);
-        // Operand 1: 4 bit memory address at offset 0
-        ir_builder.mem_address((instruction & 0b1111) as u8);
-        // Operand 2: 3 bit offset of bit at offset 5
-        // Convention: always place bit pos to u16 ir operand
-        ir_builder.bit_index(((instruction >> 5) & 0b111) as u8);
-    } else if instruction & MEM_AND_ACC_GROUP_MASK == MEM_AND_ACC_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & MEM_AND_ACC_OPCODE_MASK);
-        // Operand 1: 6 bit memory address at offset 0
-        ir_builder.mem_address((instruction & 0b111111) as u8);
-    } else if instruction & MEM_GROUP_MASK == MEM_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & MEM_OPCODE_MASK);
-        // Operand 1: 6 bit memory address at offset 0
-        ir_builder.mem_address((instruction & 0b111111) as u8);
-    } else if instruction & IO_BIT_OPS_GROUP_MASK == IO_BIT_OPS_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & IO_BIT_OPS_OPCODE_MASK);
-        // Operand 1: 5 bit memory address at offset 0
-        ir_builder.mem_address((instruction & 0b11111) as u8);
-        // Operand 2: 3 bit pos of bit at offset 5
-        // Convention: always place bit pos to u16 ir operand
-        ir_builder.bit_index(((instruction >> 5) & 0b111) as u8);
-    } else if instruction & ACC_CONST_GROUP_MASK == ACC_CONST_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & ACC_CONST_OPCODE_MASK);
-        // Operand 1: 8 bit immediate at offset 0
-        // Convention: always place immediate to u16 ir operand
-        ir_builder.immediate((instruction & 0b11111111) as u8);
-    } else if instruction & JUMP_GROUP_MASK == JUMP_GROUP_STAMP {
-        opcode_stamp = OpcodeStamp::from_primitive(instruction & JUMP_OPCODE_MASK);
-        // Operand 1: 10 bit immediate at offset 0
-        ir_builder.rom_address(instruction & 0b1111111111);
-    } else {
-        opcode_stamp = OpcodeStamp::Nop;
+
+    let stamp = match OpcodeStamp::find(instruction) {
+        Some(stamp) => stamp,
+        None => return ir_builder.ir_opcode(IrOpcode::Invalid).build(),
+    };
+
+    match stamp.operands {
+        Operands::None => {}
+        Operands::Io => {
+            ir_builder.io_address((instruction & 0b11111) as u8);
+        }
+        Operands::WordMem => {
+            // Lsb should be ignored (word aligned address)
+            ir_builder.mem_address((instruction & 0b11110) as u8);
+        }
+        Operands::Mem => {
+            ir_builder.mem_address((instruction & 0b111111) as u8);
+        }
+        Operands::MemBit => {
+            ir_builder.mem_address((instruction & 0b1111) as u8);
+            ir_builder.bit_index(((instruction >> 5) & 0b111) as u8);
+        }
+        Operands::IoBit => {
+            ir_builder.io_address((instruction & 0b11111) as u8);
+            ir_builder.bit_index(((instruction >> 5) & 0b111) as u8);
+        }
+        Operands::Immediate => {
+            ir_builder.immediate((instruction & 0b11111111) as u8);
+        }
+        Operands::RomAddress => {
+            ir_builder.rom_address(instruction & 0b1111111111);
+        }
     }
 
-    ir_builder.ir_opcode(opcode_stamp.to_ir_opcode()).build()
+    ir_builder.ir_opcode(stamp.opcode).build()
 }
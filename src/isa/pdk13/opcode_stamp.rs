@@ -1,193 +1,147 @@
-use super::ir::IrOpcode;
+//! Checked pdk13 decoding table. Each instruction is described by the stamp (fixed opcode bits)
+//! and mask which selects opcode bits in the 13 bit word; remaining bits are operands
 
-#[repr(u16)]
-#[non_exhaustive]
-pub enum OpcodeStamp {
+use super::{ir::IrOpcode, Word};
+
+/// Layout of the instruction operand bits
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Operands {
+    None,
+    /// 5 bit io address at offset 0
+    Io,
+    /// 4 bit word aligned memory address at offset 1
+    WordMem,
+    /// 6 bit memory address at offset 0
+    Mem,
+    /// 4 bit memory address at offset 0, 3 bit index of bit at offset 5
+    MemBit,
+    /// 5 bit io address at offset 0, 3 bit index of bit at offset 5
+    IoBit,
+    /// 8 bit immediate at offset 0
+    Immediate,
+    /// 10 bit rom address at offset 0
+    RomAddress,
+}
+
+#[derive(Copy, Clone)]
+pub struct OpcodeStamp {
+    pub stamp: Word,
+    pub mask: Word,
+    pub opcode: IrOpcode,
+    pub operands: Operands,
+}
+
+const fn stamp(stamp: Word, mask: Word, opcode: IrOpcode, operands: Operands) -> OpcodeStamp {
+    OpcodeStamp {
+        stamp,
+        mask,
+        opcode,
+        operands,
+    }
+}
+
+pub const OPCODE_STAMPS: [OpcodeStamp; 82] = [
     // Misc operations
-    Nop = 0x0000,
-    Ldsptl = 0x0006,
-    Ldspth = 0x0007,
-    Addca = 0x0010,
-    Subca = 0x0011,
-    Izsna = 0x0012,
-    Dzsna = 0x0013,
-    Pcadda = 0x0017,
-    Nota = 0x0018,
-    Nega = 0x0019,
-    Sra = 0x001A,
-    Sla = 0x001B,
-    Srca = 0x001C,
-    Slca = 0x001D,
-    Swapa = 0x001E,
-    Wdreset = 0x0030,
-    Pushaf = 0x0032,
-    Popaf = 0x0033,
-    Reset = 0x0035,
-    Stopsys = 0x0036,
-    Stopexe = 0x0037,
-    Engint = 0x0038,
-    Disgint = 0x0039,
-    Ret = 0x003A,
-    Reti = 0x003B,
-    Mul = 0x003C,
-    // IO operations,
-    Xorioa = 0x0060,
-    Movioa = 0x0080,
-    Movaio = 0x00A0,
+    stamp(0x0000, 0x1FFF, IrOpcode::Nop, Operands::None),
+    stamp(0x0006, 0x1FFF, IrOpcode::Ldsptl, Operands::None),
+    stamp(0x0007, 0x1FFF, IrOpcode::Ldspth, Operands::None),
+    stamp(0x0010, 0x1FFF, IrOpcode::Addca, Operands::None),
+    stamp(0x0011, 0x1FFF, IrOpcode::Subca, Operands::None),
+    stamp(0x0012, 0x1FFF, IrOpcode::Izsna, Operands::None),
+    stamp(0x0013, 0x1FFF, IrOpcode::Dzsna, Operands::None),
+    stamp(0x0017, 0x1FFF, IrOpcode::Pcadda, Operands::None),
+    stamp(0x0018, 0x1FFF, IrOpcode::Nota, Operands::None),
+    stamp(0x0019, 0x1FFF, IrOpcode::Nega, Operands::None),
+    stamp(0x001A, 0x1FFF, IrOpcode::Sra, Operands::None),
+    stamp(0x001B, 0x1FFF, IrOpcode::Sla, Operands::None),
+    stamp(0x001C, 0x1FFF, IrOpcode::Srca, Operands::None),
+    stamp(0x001D, 0x1FFF, IrOpcode::Slca, Operands::None),
+    stamp(0x001E, 0x1FFF, IrOpcode::Swapa, Operands::None),
+    stamp(0x0030, 0x1FFF, IrOpcode::Wdreset, Operands::None),
+    stamp(0x0032, 0x1FFF, IrOpcode::Pushaf, Operands::None),
+    stamp(0x0033, 0x1FFF, IrOpcode::Popaf, Operands::None),
+    stamp(0x0035, 0x1FFF, IrOpcode::Reset, Operands::None),
+    stamp(0x0036, 0x1FFF, IrOpcode::Stopsys, Operands::None),
+    stamp(0x0037, 0x1FFF, IrOpcode::Stopexe, Operands::None),
+    stamp(0x0038, 0x1FFF, IrOpcode::Engint, Operands::None),
+    stamp(0x0039, 0x1FFF, IrOpcode::Disgint, Operands::None),
+    stamp(0x003A, 0x1FFF, IrOpcode::Ret, Operands::None),
+    stamp(0x003B, 0x1FFF, IrOpcode::Reti, Operands::None),
+    stamp(0x003C, 0x1FFF, IrOpcode::Mul, Operands::None),
+    // IO operations
+    stamp(0x0060, 0x1FE0, IrOpcode::Xorioa, Operands::Io),
+    stamp(0x0080, 0x1FE0, IrOpcode::Movioa, Operands::Io),
+    stamp(0x00A0, 0x1FE0, IrOpcode::Movaio, Operands::Io),
     // Memory 16 bit operations
-    Stt16 = 0x00C0,
-    Ldt16 = 0x00C1,
-    Idxmma = 0x00E0,
-    Idxmam = 0x00E1,
+    stamp(0x00C0, 0x1FE1, IrOpcode::Stt16, Operands::WordMem),
+    stamp(0x00C1, 0x1FE1, IrOpcode::Ldt16, Operands::WordMem),
+    stamp(0x00E0, 0x1FE1, IrOpcode::Idxmma, Operands::WordMem),
+    stamp(0x00E1, 0x1FE1, IrOpcode::Idxmam, Operands::WordMem),
     // Return with result operation
-    Retk = 0x0100,
+    stamp(0x0100, 0x1F00, IrOpcode::Retk, Operands::Immediate),
     // Bit operations
-    T0snm = 0x0200,
-    T1snm = 0x0210,
-    Set0m = 0x0300,
-    Set1m = 0x0310,
+    stamp(0x0200, 0x1F10, IrOpcode::T0snm, Operands::MemBit),
+    stamp(0x0210, 0x1F10, IrOpcode::T1snm, Operands::MemBit),
+    stamp(0x0300, 0x1F10, IrOpcode::Set0m, Operands::MemBit),
+    stamp(0x0310, 0x1F10, IrOpcode::Set1m, Operands::MemBit),
     // Operations with acc and memory
-    Addma = 0x0400,
-    Subma = 0x0440,
-    Addcma = 0x0480,
-    Subcma = 0x04C0,
-    Andma = 0x0500,
-    Orma = 0x0540,
-    Xorma = 0x0580,
-    Movma = 0x05C0,
-    Addam = 0x0600,
-    Subam = 0x0640,
-    Addcam = 0x0680,
-    Subcam = 0x06C0,
-    Andam = 0x0700,
-    Oram = 0x0740,
-    Xoram = 0x0780,
-    Movam = 0x07C0,
+    stamp(0x0400, 0x1FC0, IrOpcode::Addma, Operands::Mem),
+    stamp(0x0440, 0x1FC0, IrOpcode::Subma, Operands::Mem),
+    stamp(0x0480, 0x1FC0, IrOpcode::Addcma, Operands::Mem),
+    stamp(0x04C0, 0x1FC0, IrOpcode::Subcma, Operands::Mem),
+    stamp(0x0500, 0x1FC0, IrOpcode::Andma, Operands::Mem),
+    stamp(0x0540, 0x1FC0, IrOpcode::Orma, Operands::Mem),
+    stamp(0x0580, 0x1FC0, IrOpcode::Xorma, Operands::Mem),
+    stamp(0x05C0, 0x1FC0, IrOpcode::Movma, Operands::Mem),
+    stamp(0x0600, 0x1FC0, IrOpcode::Addam, Operands::Mem),
+    stamp(0x0640, 0x1FC0, IrOpcode::Subam, Operands::Mem),
+    stamp(0x0680, 0x1FC0, IrOpcode::Addcam, Operands::Mem),
+    stamp(0x06C0, 0x1FC0, IrOpcode::Subcam, Operands::Mem),
+    stamp(0x0700, 0x1FC0, IrOpcode::Andam, Operands::Mem),
+    stamp(0x0740, 0x1FC0, IrOpcode::Oram, Operands::Mem),
+    stamp(0x0780, 0x1FC0, IrOpcode::Xoram, Operands::Mem),
+    stamp(0x07C0, 0x1FC0, IrOpcode::Movam, Operands::Mem),
     // Operations with memory
-    Addcm = 0x0800,
-    Subcm = 0x0840,
-    Izsnm = 0x0880,
-    Dzsnm = 0x08C0,
-    Incm = 0x0900,
-    Decm = 0x0940,
-    Clearm = 0x0980,
-    Xchm = 0x09C0,
-    Notm = 0x0A00,
-    Negm = 0x0A40,
-    Srm = 0x0A80,
-    Slm = 0x0AC0,
-    Srcm = 0x0B00,
-    Slcm = 0x0B40,
-    Ceqsnam = 0x0B80,
+    stamp(0x0800, 0x1FC0, IrOpcode::Addcm, Operands::Mem),
+    stamp(0x0840, 0x1FC0, IrOpcode::Subcm, Operands::Mem),
+    stamp(0x0880, 0x1FC0, IrOpcode::Izsnm, Operands::Mem),
+    stamp(0x08C0, 0x1FC0, IrOpcode::Dzsnm, Operands::Mem),
+    stamp(0x0900, 0x1FC0, IrOpcode::Incm, Operands::Mem),
+    stamp(0x0940, 0x1FC0, IrOpcode::Decm, Operands::Mem),
+    stamp(0x0980, 0x1FC0, IrOpcode::Clearm, Operands::Mem),
+    stamp(0x09C0, 0x1FC0, IrOpcode::Xchm, Operands::Mem),
+    stamp(0x0A00, 0x1FC0, IrOpcode::Notm, Operands::Mem),
+    stamp(0x0A40, 0x1FC0, IrOpcode::Negm, Operands::Mem),
+    stamp(0x0A80, 0x1FC0, IrOpcode::Srm, Operands::Mem),
+    stamp(0x0AC0, 0x1FC0, IrOpcode::Slm, Operands::Mem),
+    stamp(0x0B00, 0x1FC0, IrOpcode::Srcm, Operands::Mem),
+    stamp(0x0B40, 0x1FC0, IrOpcode::Slcm, Operands::Mem),
+    stamp(0x0B80, 0x1FC0, IrOpcode::Ceqsnam, Operands::Mem),
     // IO bit operations
-    T0snio = 0x0C00,
-    T1snio = 0x0D00,
-    Set0io = 0x0E00,
-    Set1io = 0x0F00,
+    stamp(0x0C00, 0x1F00, IrOpcode::T0snio, Operands::IoBit),
+    stamp(0x0D00, 0x1F00, IrOpcode::T1snio, Operands::IoBit),
+    stamp(0x0E00, 0x1F00, IrOpcode::Set0io, Operands::IoBit),
+    stamp(0x0F00, 0x1F00, IrOpcode::Set1io, Operands::IoBit),
     // Operations with a and literal
-    Addak = 0x1000,
-    Subak = 0x1100,
-    Ceqsnak = 0x1200,
-    Andak = 0x1400,
-    Orak = 0x1500,
-    Xorak = 0x1600,
-    Movak = 0x1700,
+    stamp(0x1000, 0x1F00, IrOpcode::Addak, Operands::Immediate),
+    stamp(0x1100, 0x1F00, IrOpcode::Subak, Operands::Immediate),
+    stamp(0x1200, 0x1F00, IrOpcode::Ceqsnak, Operands::Immediate),
+    stamp(0x1400, 0x1F00, IrOpcode::Andak, Operands::Immediate),
+    stamp(0x1500, 0x1F00, IrOpcode::Orak, Operands::Immediate),
+    stamp(0x1600, 0x1F00, IrOpcode::Xorak, Operands::Immediate),
+    stamp(0x1700, 0x1F00, IrOpcode::Movak, Operands::Immediate),
     // Control transfers
-    Goto = 0x1800,
-    Call = 0x1C00,
-}
+    stamp(0x1800, 0x1C00, IrOpcode::Goto, Operands::RomAddress),
+    stamp(0x1C00, 0x1C00, IrOpcode::Call, Operands::RomAddress),
+];
 
 impl OpcodeStamp {
-    // Safe as long as OpcodeStamp is non-exhaustive
-    pub fn from_primitive(value: u16) -> OpcodeStamp {
-        unsafe { core::mem::transmute(value) }
-    }
-
-    pub fn to_ir_opcode(&self) -> IrOpcode {
-        match self {
-            Self::Nop => IrOpcode::Nop,
-            Self::Ldsptl => IrOpcode::Ldsptl,
-            Self::Ldspth => IrOpcode::Ldspth,
-            Self::Addca => IrOpcode::Addca,
-            Self::Subca => IrOpcode::Subca,
-            Self::Izsna => IrOpcode::Izsna,
-            Self::Dzsna => IrOpcode::Dzsna,
-            Self::Pcadda => IrOpcode::Pcadda,
-            Self::Nota => IrOpcode::Nota,
-            Self::Nega => IrOpcode::Nega,
-            Self::Sra => IrOpcode::Sra,
-            Self::Sla => IrOpcode::Sla,
-            Self::Srca => IrOpcode::Srca,
-            Self::Slca => IrOpcode::Slca,
-            Self::Swapa => IrOpcode::Swapa,
-            Self::Wdreset => IrOpcode::Wdreset,
-            Self::Pushaf => IrOpcode::Pushaf,
-            Self::Popaf => IrOpcode::Popaf,
-            Self::Reset => IrOpcode::Reset,
-            Self::Stopsys => IrOpcode::Stopsys,
-            Self::Stopexe => IrOpcode::Stopexe,
-            Self::Engint => IrOpcode::Engint,
-            Self::Disgint => IrOpcode::Disgint,
-            Self::Ret => IrOpcode::Ret,
-            Self::Reti => IrOpcode::Reti,
-            Self::Mul => IrOpcode::Mul,
-            Self::Xorioa => IrOpcode::Xorioa,
-            Self::Movioa => IrOpcode::Movioa,
-            Self::Movaio => IrOpcode::Movaio,
-            Self::Stt16 => IrOpcode::Stt16,
-            Self::Ldt16 => IrOpcode::Ldt16,
-            Self::Idxmma => IrOpcode::Idxmma,
-            Self::Idxmam => IrOpcode::Idxmam,
-            Self::Retk => IrOpcode::Retk,
-            Self::T0snm => IrOpcode::T0snm,
-            Self::T1snm => IrOpcode::T1snm,
-            Self::Set0m => IrOpcode::Set0m,
-            Self::Set1m => IrOpcode::Set1m,
-            Self::Addma => IrOpcode::Addma,
-            Self::Subma => IrOpcode::Subma,
-            Self::Addcma => IrOpcode::Addcma,
-            Self::Subcma => IrOpcode::Subcma,
-            Self::Andma => IrOpcode::Andma,
-            Self::Orma => IrOpcode::Orma,
-            Self::Xorma => IrOpcode::Xorma,
-            Self::Movma => IrOpcode::Movma,
-            Self::Addam => IrOpcode::Addam,
-            Self::Subam => IrOpcode::Subam,
-            Self::Addcam => IrOpcode::Addcam,
-            Self::Subcam => IrOpcode::Subcam,
-            Self::Andam => IrOpcode::Andam,
-            Self::Oram => IrOpcode::Oram,
-            Self::Xoram => IrOpcode::Xoram,
-            Self::Movam => IrOpcode::Movam,
-            Self::Addcm => IrOpcode::Addcm,
-            Self::Subcm => IrOpcode::Subcm,
-            Self::Izsnm => IrOpcode::Izsnm,
-            Self::Dzsnm => IrOpcode::Dzsnm,
-            Self::Incm => IrOpcode::Incm,
-            Self::Decm => IrOpcode::Decm,
-            Self::Clearm => IrOpcode::Clearm,
-            Self::Xchm => IrOpcode::Xchm,
-            Self::Notm => IrOpcode::Notm,
-            Self::Negm => IrOpcode::Negm,
-            Self::Srm => IrOpcode::Srm,
-            Self::Slm => IrOpcode::Slm,
-            Self::Srcm => IrOpcode::Srcm,
-            Self::Slcm => IrOpcode::Slcm,
-            Self::Ceqsnam => IrOpcode::Ceqsnam,
-            Self::T0snio => IrOpcode::T0snio,
-            Self::T1snio => IrOpcode::T1snio,
-            Self::Set0io => IrOpcode::Set0io,
-            Self::Set1io => IrOpcode::Set1io,
-            Self::Addak => IrOpcode::Addak,
-            Self::Subak => IrOpcode::Subak,
-            Self::Ceqsnak => IrOpcode::Ceqsnak,
-            Self::Andak => IrOpcode::Andak,
-            Self::Orak => IrOpcode::Orak,
-            Self::Xorak => IrOpcode::Xorak,
-            Self::Movak => IrOpcode::Movak,
-            Self::Goto => IrOpcode::Goto,
-            Self::Call => IrOpcode::Call,
-            _ => IrOpcode::Nop,
-        }
+    /// Returns stamp of the instruction encoded by 13 bit word, `None` if word does not
+    /// encode any instruction
+    pub fn find(word: Word) -> Option<OpcodeStamp> {
+        OPCODE_STAMPS
+            .iter()
+            .copied()
+            .find(|stamp| word & stamp.mask == stamp.stamp)
     }
 }
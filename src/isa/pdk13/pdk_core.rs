@@ -3,24 +3,35 @@ use super::{
     ir::{IrOpcode, IrSlot},
     ops,
     regs::*,
-    Byte, IoAddr, RamAddr, RomAddr, Word,
+    Byte, IoAddr, Pdk13Error, Pdk13Result, RamAddr, RomAddr, Word,
 };
 
-#[derive(Copy, Clone)]
-enum PdkCoreState {
-    Execute,
-    Skip,
+/// Instruction takes single system clock cycle
+const ONE_CYCLE: u32 = 1;
+/// Instruction takes two cycles: branches, taken skips, `idxm`, `call`, `ret` and `ldspt`
+const TWO_CYCLES: u32 = 2;
+
+/// Core behaviour on words which do not encode any pdk13 instruction
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum DecodeMode {
+    /// Illegal instructions are executed as `nop`
+    #[default]
+    Lenient,
+    /// Execution stops with `Pdk13Error::IllegalInstruction`
+    Strict,
 }
 
 pub struct PdkCore {
     acc: Byte,
     pc: RomAddr,
-    state: PdkCoreState,
     global_interrupts: bool,
+    decode_mode: DecodeMode,
+    // Total count of elapsed cycles, not affected by reset
+    cycles: u64,
     // Execute cycle varibles
     prev_flags: Byte,
     pc_increment: Word,
-    next_state: PdkCoreState,
+    instruction_cycles: u32,
 }
 
 impl PdkCore {
@@ -28,19 +39,34 @@ impl PdkCore {
         Self {
             acc: 0,
             pc: 0,
-            state: PdkCoreState::Execute,
             global_interrupts: false,
+            decode_mode: DecodeMode::default(),
+            cycles: 0,
             prev_flags: 0,
             pc_increment: 0,
-            next_state: PdkCoreState::Execute,
+            instruction_cycles: ONE_CYCLE,
         }
     }
 
-    pub fn step(&mut self, bus: &mut impl Bus) {
-        self.state = match self.state {
-            PdkCoreState::Execute => self.execute(bus),
-            PdkCoreState::Skip => PdkCoreState::Execute,
-        }
+    /// Executes single instruction (or performs interrupt dispatch) and returns count of
+    /// consumed cycles. In strict decode mode illegal instruction is not executed and
+    /// program counter stays at its address
+    pub fn step(&mut self, bus: &mut impl Bus) -> Pdk13Result<u32> {
+        let cycles = if self.interrupt_pending(bus) {
+            self.enter_interrupt(bus)
+        } else {
+            self.execute(bus)?
+        };
+        self.cycles += cycles as u64;
+        Ok(cycles)
+    }
+
+    pub fn set_decode_mode(&mut self, mode: DecodeMode) {
+        self.decode_mode = mode;
+    }
+
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.decode_mode
     }
 
     pub fn reset(&mut self) {
@@ -53,19 +79,53 @@ impl PdkCore {
         self.acc
     }
 
+    pub fn set_acc(&mut self, value: Byte) {
+        self.acc = value;
+    }
+
     pub fn pc(&self) -> RomAddr {
         self.pc
     }
 
+    pub fn set_pc(&mut self, value: RomAddr) {
+        self.pc = value;
+    }
+
     pub fn global_interrupts_enabled(&self) -> bool {
         self.global_interrupts
     }
 
+    /// Returns total count of cycles elapsed since core creation
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    fn interrupt_pending(&self, bus: &impl Bus) -> bool {
+        self.global_interrupts && bus.pending_interrupts() != 0
+    }
+
+    /// Performs hardware call to the interrupt vector. Global interrupts stay disabled until
+    /// `reti` or `engint` is executed
+    fn enter_interrupt(&mut self, bus: &mut impl Bus) -> u32 {
+        let sp = bus.read_io(IO_ADDR_SP);
+        bus.write_ram_word(sp, self.pc);
+        bus.write_io(IO_ADDR_SP, sp.wrapping_add(2));
+        self.global_interrupts = false;
+        self.pc = ROM_ADDR_INTERRUPT_VECTOR;
+        TWO_CYCLES
+    }
+
     #[rustfmt::skip]
-    fn execute(&mut self, bus: &mut impl Bus) -> PdkCoreState {
-        self.prev_flags = bus.read_io(IO_ADDR_FLAGS);
+    fn execute(&mut self, bus: &mut impl Bus) -> Pdk13Result<u32> {
         let ir = bus.read_rom(self.pc);
-        self.next_state = PdkCoreState::Execute;
+        if ir.ir_opcode() == IrOpcode::Invalid && self.decode_mode == DecodeMode::Strict {
+            return Err(Pdk13Error::IllegalInstruction {
+                pc: self.pc,
+                word: ir.original_word(),
+            });
+        }
+        self.prev_flags = bus.read_flags();
+        self.instruction_cycles = ONE_CYCLE;
         self.pc_increment = 1;
 
         match ir.ir_opcode() {
@@ -149,10 +209,11 @@ impl PdkCore {
             IrOpcode::Movak => self.acc = ir.immediate() as u8,
             IrOpcode::Goto => self.goto(ir.rom_address()),
             IrOpcode::Call => self.call(ir.rom_address(), bus),
+            // Nop and illegal instructions in lenient decode mode
             _ => {}
         }
         self.pc = self.pc.wrapping_add(self.pc_increment);
-        self.next_state
+        Ok(self.instruction_cycles)
     }
 
     fn alu_acc_binary(
@@ -197,7 +258,7 @@ impl PdkCore {
         bus.write_io(IO_ADDR_SP, sp.wrapping_sub(2));
         self.pc = pc;
         self.pc_increment = 0;
-        self.next_state = PdkCoreState::Skip;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn reti(&mut self, bus: &mut impl Bus) {
@@ -213,7 +274,7 @@ impl PdkCore {
     fn goto(&mut self, addr: Word) {
         self.pc = addr;
         self.pc_increment = 0;
-        self.next_state = PdkCoreState::Skip;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn call(&mut self, addr: Word, bus: &mut impl Bus) {
@@ -222,7 +283,7 @@ impl PdkCore {
         self.pc = addr;
         bus.write_io(IO_ADDR_SP, sp.wrapping_add(2));
         self.pc_increment = 0;
-        self.next_state = PdkCoreState::Skip;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn set_bit_ram(&mut self, addr: u8, bit: u8, bus: &mut impl Bus) {
@@ -244,28 +305,28 @@ impl PdkCore {
     fn skip_if_bit_set_io(&mut self, addr: u8, bit: u8, bus: &mut impl Bus) {
         if bus.read_io(addr) & (1 << bit as u8) != 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
     }
 
     fn skip_if_bit_clear_io(&mut self, addr: u8, bit: u8, bus: &mut impl Bus) {
         if bus.read_io(addr) & (1 << bit) == 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
     }
 
     fn skip_if_bit_set_ram(&mut self, addr: u8, bit: u8, bus: &mut impl Bus) {
         if bus.read_ram(addr) & (1 << bit) != 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
     }
 
     fn skip_if_bit_clear_ram(&mut self, addr: u8, bit: u8, bus: &mut impl Bus) {
         if bus.read_ram(addr) & (1 << bit) == 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
     }
 
@@ -282,7 +343,7 @@ impl PdkCore {
     fn add_acc_to_pc(&mut self) {
         self.pc = self.pc.wrapping_add(self.acc as u16);
         self.pc_increment = 0;
-        self.next_state = PdkCoreState::Skip;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn push_af(&mut self, bus: &mut impl Bus) {
@@ -312,20 +373,22 @@ impl PdkCore {
     fn load_rom_word_indirect_sp_hi(&mut self, bus: &mut impl Bus) {
         let sp = bus.read_io(IO_ADDR_SP);
         let addr = bus.read_ram(sp) as u16 | ((bus.read_ram(sp.wrapping_add(1)) as u16) << 8);
-        self.acc = (bus.read_rom(addr).original_word() >> 8) as u8
+        self.acc = (bus.read_rom(addr).original_word() >> 8) as u8;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn load_rom_word_indirect_sp_lo(&mut self, bus: &mut impl Bus) {
         let sp = bus.read_io(IO_ADDR_SP);
         let addr = bus.read_ram(sp) as u16 | ((bus.read_ram(sp.wrapping_add(1)) as u16) << 8);
-        self.acc = bus.read_rom(addr).original_word() as u8
+        self.acc = bus.read_rom(addr).original_word() as u8;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn inc_and_skip_next_if_zero_acc(&mut self, bus: &mut impl Bus) {
         let (acc, flags) = ops::add(self.acc, 1, self.prev_flags);
         if flags & FLAG_ZERO_MASK != 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
         self.acc = acc;
         bus.write_io(IO_ADDR_FLAGS, flags);
@@ -335,7 +398,7 @@ impl PdkCore {
         let (acc, flags) = ops::sub(self.acc, 1, self.prev_flags);
         if flags & FLAG_ZERO_MASK != 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
         self.acc = acc;
         bus.write_io(IO_ADDR_FLAGS, flags);
@@ -345,7 +408,7 @@ impl PdkCore {
         let (acc, flags) = ops::add(bus.read_ram(addr), 1, self.prev_flags);
         if flags & FLAG_ZERO_MASK != 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
         bus.write_ram(addr, acc);
         bus.write_io(IO_ADDR_FLAGS, flags);
@@ -355,7 +418,7 @@ impl PdkCore {
         let (acc, flags) = ops::sub(bus.read_ram(addr), 1, self.prev_flags);
         if flags & FLAG_ZERO_MASK != 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
         bus.write_ram(addr, acc);
         bus.write_io(IO_ADDR_FLAGS, flags);
@@ -367,12 +430,12 @@ impl PdkCore {
 
     fn indirect_store_acc(&mut self, addr: Byte, bus: &mut impl Bus) {
         bus.write_ram(bus.read_ram_word(addr) as Byte, self.acc);
-        self.next_state = PdkCoreState::Skip;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn indirect_load_acc(&mut self, addr: Byte, bus: &mut impl Bus) {
         self.acc = bus.read_ram(bus.read_ram_word(addr) as Byte);
-        self.next_state = PdkCoreState::Skip;
+        self.instruction_cycles = TWO_CYCLES;
     }
 
     fn skip_next_if_equal(&mut self, value: Byte, bus: &mut impl Bus) {
@@ -380,7 +443,7 @@ impl PdkCore {
         bus.write_io(IO_ADDR_FLAGS, f);
         if f & FLAG_ZERO_MASK != 0 {
             self.pc_increment = 2;
-            self.next_state = PdkCoreState::Skip;
+            self.instruction_cycles = TWO_CYCLES;
         }
     }
 }
@@ -1,4 +1,7 @@
-use super::{Byte, IoAddr};
+use super::{Byte, IoAddr, RomAddr};
+
+// === Special ROM Addresses ===
+pub const ROM_ADDR_INTERRUPT_VECTOR: RomAddr = 0x010;
 
 // === Special IO Addresses ===
 pub const IO_ADDR_FLAGS: IoAddr = 0x00;
@@ -9,6 +12,7 @@ pub const IO_ADDR_INTRQ: IoAddr = 0x05;
 pub const IO_ADDR_T16M: IoAddr = 0x06;
 pub const IO_ADDR_TM2B: IoAddr = 0x09;
 pub const IO_ADDR_EOSCR: IoAddr = 0x0A;
+pub const IO_ADDR_IHRCR: IoAddr = 0x0B;
 pub const IO_ADDR_INTEGS: IoAddr = 0x0C;
 pub const IO_ADDR_PADIER: IoAddr = 0x0D;
 pub const IO_ADDR_PA: IoAddr = 0x10;
@@ -21,7 +25,6 @@ pub const IO_ADDR_TM2C: IoAddr = 0x1C;
 pub const IO_ADDR_TM2CT: IoAddr = 0x1D;
 pub const IO_ADDR_GPCS: IoAddr = 0x1E;
 
-
 // === FLAGS ===
 pub const FLAG_ZERO_MASK: Byte = 0x01;
 pub const FLAG_ZERO_OFFSET: Byte = 0x00;
@@ -35,3 +38,10 @@ pub const FLAG_OVERFLOW_OFFSET: Byte = 0x03;
 // === FLAG GROUPS ===
 pub const FLAGS_ARITH_MASK: Byte =
     FLAG_ZERO_MASK | FLAG_CARRY_MASK | FLAG_AUX_CARRY_MASK | FLAG_OVERFLOW_MASK;
+
+// === INTEN/INTRQ ===
+pub const INT_PA0_MASK: Byte = 0x01;
+pub const INT_PA4_MASK: Byte = 0x02;
+pub const INT_T16_MASK: Byte = 0x04;
+pub const INT_COMPARATOR_MASK: Byte = 0x10;
+pub const INT_TM2_MASK: Byte = 0x40;
@@ -0,0 +1,211 @@
+use crate::{
+    firmware::RomImage,
+    isa::pdk13::{
+        asm::{assemble, AsmError, AsmErrorKind},
+        disasm::disassemble_word,
+        Instruction, Word,
+    },
+    test_utils::TextBuffer,
+};
+
+const PDK13_WORDS: Word = 0x2000;
+
+fn assemble_words(source: &str) -> RomImage {
+    assemble(source).unwrap()
+}
+
+fn assemble_error(source: &str) -> AsmError {
+    match assemble(source) {
+        Ok(_) => panic!("Source should not be assembled"),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn disassembled_words_are_assembled_back() {
+    for word in 0..PDK13_WORDS {
+        if Instruction::decode(word).is_none() {
+            continue;
+        }
+        let mut text = TextBuffer::new();
+        disassemble_word(&mut text, word).unwrap();
+        let image = assemble_words(text.as_str());
+        assert_eq!(Some(word), image.get(0), "{}", text.as_str());
+    }
+}
+
+#[test]
+fn labels_could_be_referenced_before_definition() {
+    let image = assemble_words(
+        "
+        start:  goto main       ; jump over data
+                .dw 0x1234
+        main:   call start
+                goto .
+        ",
+    );
+    assert_eq!(Some(0x1802), image.get(0));
+    assert_eq!(Some(0x1234), image.get(1));
+    assert_eq!(Some(0x1C00), image.get(2));
+    assert_eq!(Some(0x1803), image.get(3));
+    assert_eq!(4, image.size());
+}
+
+#[test]
+fn org_moves_current_address() {
+    let image = assemble_words(
+        "
+            goto main
+        .org 0x10
+            reti
+        main:
+            nop
+        ",
+    );
+    assert_eq!(Some(0x1811), image.get(0));
+    assert_eq!(None, image.get(1));
+    assert_eq!(Some(0x003B), image.get(0x10));
+    assert_eq!(Some(0x0000), image.get(0x11));
+}
+
+#[test]
+fn constants_and_expressions_are_evaluated() {
+    let image = assemble_words(
+        "
+        LED = 4
+        .equ COUNTER, 0x10 + 1
+        MASK = (1 << LED) | ~0xFE & 0xFF
+            mov a, #MASK
+            inc COUNTER
+            set1.io pa, #LED
+            mov a, #-1
+            mov a, #END - 1
+        END = 20 / 3 % 4 * 2
+        ",
+    );
+    assert_eq!(Some(Instruction::MovAK(0x11).encode()), image.get(0));
+    assert_eq!(Some(Instruction::IncM(0x11).encode()), image.get(1));
+    assert_eq!(Some(0b1111_1001_0000), image.get(2));
+    assert_eq!(Some(Instruction::MovAK(0xFF).encode()), image.get(3));
+    assert_eq!(Some(Instruction::MovAK(3).encode()), image.get(4));
+}
+
+#[test]
+fn io_operands_are_selected_by_register_name_or_suffix() {
+    let image = assemble_words(
+        "
+            mov a, pa
+            mov pac, a
+            mov a, 0x10
+            mov.io 0x07, a
+            xor pa, a
+            t0sn pa, #3
+            t0sn 0x03, #3
+        ",
+    );
+    assert_eq!(Some(Instruction::MovAIo(0x10).encode()), image.get(0));
+    assert_eq!(Some(Instruction::MovIoA(0x11).encode()), image.get(1));
+    assert_eq!(Some(Instruction::MovAM(0x10).encode()), image.get(2));
+    assert_eq!(Some(Instruction::MovIoA(0x07).encode()), image.get(3));
+    assert_eq!(Some(Instruction::XorIoA(0x10).encode()), image.get(4));
+    assert_eq!(
+        Some(Instruction::T0snIo { addr: 0x10, bit: 3 }.encode()),
+        image.get(5)
+    );
+    assert_eq!(
+        Some(Instruction::T0snM { addr: 0x03, bit: 3 }.encode()),
+        image.get(6)
+    );
+}
+
+#[test]
+fn constants_aliasing_io_registers_are_io_operands() {
+    let image = assemble_words(
+        "
+            LED = pa
+            PORT = LED
+            COUNTER = 0x10
+            mov LED, a
+            set1 PORT, #4
+            mov COUNTER, a
+        ",
+    );
+    assert_eq!(Some(Instruction::MovIoA(0x10).encode()), image.get(0));
+    assert_eq!(
+        Some(Instruction::Set1Io { addr: 0x10, bit: 4 }.encode()),
+        image.get(1)
+    );
+    assert_eq!(Some(Instruction::MovMA(0x10).encode()), image.get(2));
+}
+
+#[test]
+fn mnemonics_are_case_insensitive() {
+    let image = assemble_words("MOV A, #1\nPush AF\nSet1.IO PA, #0");
+    assert_eq!(Some(Instruction::MovAK(1).encode()), image.get(0));
+    assert_eq!(Some(Instruction::PushAf.encode()), image.get(1));
+    assert_eq!(
+        Some(Instruction::Set1Io { addr: 0x10, bit: 0 }.encode()),
+        image.get(2)
+    );
+}
+
+#[test]
+fn errors_are_reported_with_line_number() {
+    let err = assemble_error("nop\n  jump 0x10\n");
+    assert_eq!(2, err.line);
+    assert_eq!(AsmErrorKind::UnknownMnemonic, err.kind);
+    assert_eq!(
+        AsmErrorKind::InvalidOperands,
+        assemble_error("inc a, a").kind
+    );
+    assert_eq!(
+        AsmErrorKind::UndefinedSymbol,
+        assemble_error("goto nowhere").kind
+    );
+    assert_eq!(AsmErrorKind::DuplicateSymbol, assemble_error("x:\nx:").kind);
+    assert_eq!(AsmErrorKind::DuplicateSymbol, assemble_error("pa = 1").kind);
+    assert_eq!(
+        AsmErrorKind::OperandOutOfRange,
+        assemble_error("inc 0x40").kind
+    );
+    assert_eq!(
+        AsmErrorKind::OperandOutOfRange,
+        assemble_error("set1 0x10, #1").kind
+    );
+    assert_eq!(
+        AsmErrorKind::OperandOutOfRange,
+        assemble_error("set1.io pa, #8").kind
+    );
+    assert_eq!(
+        AsmErrorKind::OperandOutOfRange,
+        assemble_error("stt16 0x11").kind
+    );
+    assert_eq!(
+        AsmErrorKind::InvalidOperands,
+        assemble_error("mov.io a, #5").kind
+    );
+    assert_eq!(
+        AsmErrorKind::InvalidOperands,
+        assemble_error("xor.io a, #1").kind
+    );
+    assert_eq!(
+        AsmErrorKind::InvalidExpression,
+        assemble_error("mov a, #(1").kind
+    );
+    assert_eq!(
+        AsmErrorKind::InvalidExpression,
+        assemble_error("mov a, #1/0").kind
+    );
+    assert_eq!(
+        AsmErrorKind::InvalidExpression,
+        assemble_error("x = y\ny = x\nmov a, #x").kind
+    );
+    assert_eq!(
+        AsmErrorKind::UnknownDirective,
+        assemble_error(".area CODE").kind
+    );
+    assert_eq!(
+        AsmErrorKind::AddressOutOfRange,
+        assemble_error(".org 0x400\nnop").kind
+    );
+}
@@ -0,0 +1,176 @@
+//! Exhaustive check of pdk13 decoding against documented instruction encodings. Pattern
+//! letters: `a` => memory/io/rom address bits, `k` => immediate bits, `b` => bit index bits
+
+use crate::isa::pdk13::{
+    ir::{generate_ir, IrOpcode},
+    Word,
+};
+
+const PDK13_WORDS: Word = 0x2000;
+
+const ENCODINGS: [(&str, IrOpcode); 82] = [
+    // Misc operations
+    ("0000000000000", IrOpcode::Nop),
+    ("0000000000110", IrOpcode::Ldsptl),
+    ("0000000000111", IrOpcode::Ldspth),
+    ("0000000010000", IrOpcode::Addca),
+    ("0000000010001", IrOpcode::Subca),
+    ("0000000010010", IrOpcode::Izsna),
+    ("0000000010011", IrOpcode::Dzsna),
+    ("0000000010111", IrOpcode::Pcadda),
+    ("0000000011000", IrOpcode::Nota),
+    ("0000000011001", IrOpcode::Nega),
+    ("0000000011010", IrOpcode::Sra),
+    ("0000000011011", IrOpcode::Sla),
+    ("0000000011100", IrOpcode::Srca),
+    ("0000000011101", IrOpcode::Slca),
+    ("0000000011110", IrOpcode::Swapa),
+    ("0000000110000", IrOpcode::Wdreset),
+    ("0000000110010", IrOpcode::Pushaf),
+    ("0000000110011", IrOpcode::Popaf),
+    ("0000000110101", IrOpcode::Reset),
+    ("0000000110110", IrOpcode::Stopsys),
+    ("0000000110111", IrOpcode::Stopexe),
+    ("0000000111000", IrOpcode::Engint),
+    ("0000000111001", IrOpcode::Disgint),
+    ("0000000111010", IrOpcode::Ret),
+    ("0000000111011", IrOpcode::Reti),
+    ("0000000111100", IrOpcode::Mul),
+    // IO operations
+    ("00000011aaaaa", IrOpcode::Xorioa),
+    ("00000100aaaaa", IrOpcode::Movioa),
+    ("00000101aaaaa", IrOpcode::Movaio),
+    // Memory 16 bit operations
+    ("00000110aaaa0", IrOpcode::Stt16),
+    ("00000110aaaa1", IrOpcode::Ldt16),
+    ("00000111aaaa0", IrOpcode::Idxmma),
+    ("00000111aaaa1", IrOpcode::Idxmam),
+    // Return with result operation
+    ("00001kkkkkkkk", IrOpcode::Retk),
+    // Bit operations
+    ("00010bbb0aaaa", IrOpcode::T0snm),
+    ("00010bbb1aaaa", IrOpcode::T1snm),
+    ("00011bbb0aaaa", IrOpcode::Set0m),
+    ("00011bbb1aaaa", IrOpcode::Set1m),
+    // Operations with acc and memory
+    ("0010000aaaaaa", IrOpcode::Addma),
+    ("0010001aaaaaa", IrOpcode::Subma),
+    ("0010010aaaaaa", IrOpcode::Addcma),
+    ("0010011aaaaaa", IrOpcode::Subcma),
+    ("0010100aaaaaa", IrOpcode::Andma),
+    ("0010101aaaaaa", IrOpcode::Orma),
+    ("0010110aaaaaa", IrOpcode::Xorma),
+    ("0010111aaaaaa", IrOpcode::Movma),
+    ("0011000aaaaaa", IrOpcode::Addam),
+    ("0011001aaaaaa", IrOpcode::Subam),
+    ("0011010aaaaaa", IrOpcode::Addcam),
+    ("0011011aaaaaa", IrOpcode::Subcam),
+    ("0011100aaaaaa", IrOpcode::Andam),
+    ("0011101aaaaaa", IrOpcode::Oram),
+    ("0011110aaaaaa", IrOpcode::Xoram),
+    ("0011111aaaaaa", IrOpcode::Movam),
+    // Operations with memory
+    ("0100000aaaaaa", IrOpcode::Addcm),
+    ("0100001aaaaaa", IrOpcode::Subcm),
+    ("0100010aaaaaa", IrOpcode::Izsnm),
+    ("0100011aaaaaa", IrOpcode::Dzsnm),
+    ("0100100aaaaaa", IrOpcode::Incm),
+    ("0100101aaaaaa", IrOpcode::Decm),
+    ("0100110aaaaaa", IrOpcode::Clearm),
+    ("0100111aaaaaa", IrOpcode::Xchm),
+    ("0101000aaaaaa", IrOpcode::Notm),
+    ("0101001aaaaaa", IrOpcode::Negm),
+    ("0101010aaaaaa", IrOpcode::Srm),
+    ("0101011aaaaaa", IrOpcode::Slm),
+    ("0101100aaaaaa", IrOpcode::Srcm),
+    ("0101101aaaaaa", IrOpcode::Slcm),
+    ("0101110aaaaaa", IrOpcode::Ceqsnam),
+    // IO bit operations
+    ("01100bbbaaaaa", IrOpcode::T0snio),
+    ("01101bbbaaaaa", IrOpcode::T1snio),
+    ("01110bbbaaaaa", IrOpcode::Set0io),
+    ("01111bbbaaaaa", IrOpcode::Set1io),
+    // Operations with a and literal
+    ("10000kkkkkkkk", IrOpcode::Addak),
+    ("10001kkkkkkkk", IrOpcode::Subak),
+    ("10010kkkkkkkk", IrOpcode::Ceqsnak),
+    ("10100kkkkkkkk", IrOpcode::Andak),
+    ("10101kkkkkkkk", IrOpcode::Orak),
+    ("10110kkkkkkkk", IrOpcode::Xorak),
+    ("10111kkkkkkkk", IrOpcode::Movak),
+    // Control transfers
+    ("110aaaaaaaaaa", IrOpcode::Goto),
+    ("111aaaaaaaaaa", IrOpcode::Call),
+];
+
+fn matches(pattern: &str, word: Word) -> bool {
+    pattern.bytes().rev().enumerate().all(|(bit, symbol)| {
+        let value = (word >> bit) & 1;
+        match symbol {
+            b'0' => value == 0,
+            b'1' => value == 1,
+            _ => true,
+        }
+    })
+}
+
+/// Collects bits marked with `symbol` preserving their positions
+fn operand_bits(pattern: &str, word: Word, symbol: u8) -> Word {
+    pattern
+        .bytes()
+        .rev()
+        .enumerate()
+        .filter(|(_, s)| *s == symbol)
+        .fold(0, |acc, (bit, _)| acc | (word & (1 << bit)))
+}
+
+fn find_encoding(word: Word) -> Option<(&'static str, IrOpcode)> {
+    let mut found = ENCODINGS.iter().filter(|(pattern, _)| matches(pattern, word));
+    let encoding = found.next().copied();
+    assert!(found.next().is_none(), "Ambiguous encoding of {:#06X}", word);
+    encoding
+}
+
+#[test]
+fn encoding_patterns_are_13_bit() {
+    for (pattern, opcode) in ENCODINGS.iter() {
+        assert_eq!(13, pattern.len(), "Invalid pattern for {:?}", opcode);
+    }
+}
+
+#[test]
+fn every_word_decodes_to_documented_instruction() {
+    for word in 0..PDK13_WORDS {
+        let ir = generate_ir(word);
+        assert_eq!(word, ir.original_word());
+        let (pattern, opcode) = match find_encoding(word) {
+            Some(encoding) => encoding,
+            None => {
+                assert_eq!(IrOpcode::Invalid, ir.ir_opcode(), "Word {:#06X}", word);
+                continue;
+            }
+        };
+        assert_eq!(opcode, ir.ir_opcode(), "Word {:#06X}", word);
+
+        let address = operand_bits(pattern, word, b'a');
+        let immediate = operand_bits(pattern, word, b'k');
+        let bit_index = operand_bits(pattern, word, b'b') >> 5;
+        match opcode {
+            IrOpcode::Goto | IrOpcode::Call => {
+                assert_eq!(address, ir.rom_address(), "Word {:#06X}", word);
+            }
+            _ => {
+                assert_eq!((address | immediate) as u8, ir.mem_address(), "Word {:#06X}", word);
+                assert_eq!(bit_index as u8, ir.bit_index(), "Word {:#06X}", word);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_instruction_is_decodable() {
+    for (pattern, opcode) in ENCODINGS.iter() {
+        let word = (0..PDK13_WORDS).find(|word| matches(pattern, *word)).unwrap();
+        assert_eq!(*opcode, generate_ir(word).ir_opcode());
+    }
+}
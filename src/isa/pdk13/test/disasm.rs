@@ -0,0 +1,95 @@
+use core::fmt::Write;
+
+use crate::{
+    isa::pdk13::{
+        disasm::{disassemble_rom, disassemble_word, io_register_name},
+        regs::*,
+        Instruction, Word,
+    },
+    test_utils::TextBuffer,
+};
+
+fn disassemble(word: Word) -> TextBuffer {
+    let mut buffer = TextBuffer::new();
+    disassemble_word(&mut buffer, word).unwrap();
+    buffer
+}
+
+#[test]
+fn immediate_operands_are_prefixed_with_hash() {
+    assert_eq!("mov a, #0x55", disassemble(0x1755).as_str());
+    assert_eq!("ceqsn a, #0x0a", disassemble(0x120A).as_str());
+    assert_eq!("ret #0xff", disassemble(0x01FF).as_str());
+}
+
+#[test]
+fn io_addresses_are_named() {
+    assert_eq!("t1sn.io pa, #4", disassemble(0b1101_1001_0000).as_str());
+    assert_eq!("mov clkmd, a", disassemble(0x0083).as_str());
+    assert_eq!("mov a, tm2ct", disassemble(0x00BD).as_str());
+    assert_eq!("set0.io 0x07, #1", disassemble(0b1110_0010_0111).as_str());
+    assert_eq!("mov.io 0x07, a", disassemble(0x0087).as_str());
+}
+
+#[test]
+fn memory_operands_are_written_as_addresses() {
+    assert_eq!("inc 0x10", disassemble(0x0910).as_str());
+    assert_eq!("add a, 0x3a", disassemble(0x063A).as_str());
+    assert_eq!("xor 0x3a, a", disassemble(0x05BA).as_str());
+    assert_eq!("set1 0x0a, #5", disassemble(0b11_1011_1010).as_str());
+    assert_eq!("idxm a, 0x1a", disassemble(0x00FB).as_str());
+}
+
+#[test]
+fn misc_instructions_have_no_operands() {
+    assert_eq!("nop", disassemble(0x0000).as_str());
+    assert_eq!("pcadd a", disassemble(0x0017).as_str());
+    assert_eq!("reti", disassemble(0x003B).as_str());
+}
+
+#[test]
+fn branch_targets_are_rom_addresses() {
+    assert_eq!("goto 0x012", disassemble(0x1812).as_str());
+    assert_eq!("call 0x3ff", disassemble(0x1FFF).as_str());
+}
+
+#[test]
+fn invalid_words_are_written_as_data() {
+    assert_eq!(".dw 0x003e", disassemble(0x003E).as_str());
+}
+
+#[test]
+fn display_uses_disassembler_syntax() {
+    let mut buffer = TextBuffer::new();
+    write!(buffer, "{}", Instruction::Set1Io { addr: IO_ADDR_PAC, bit: 3 }).unwrap();
+    assert_eq!("set1.io pac, #3", buffer.as_str());
+}
+
+#[test]
+fn known_registers_have_names() {
+    assert_eq!(Some("padier"), io_register_name(IO_ADDR_PADIER));
+    assert_eq!(None, io_register_name(0x1F));
+}
+
+#[test]
+fn rom_listing_labels_branch_targets() {
+    let rom = [
+        0x1755, // mov a, #0x55
+        0x0090, // mov pa, a
+        0x1C04, // call 0x004
+        0x1802, // goto 0x002
+        0x003A, // ret
+        0x1BFF, // goto 0x3ff, outside of the image
+    ];
+    let mut buffer = TextBuffer::new();
+    disassemble_rom(&mut buffer, &rom).unwrap();
+    let expected = "    mov a, #0x55                ; 000: 1755\n\
+                    \x20   mov pa, a                   ; 001: 0090\n\
+                    l_002:\n\
+                    \x20   call l_004                  ; 002: 1c04\n\
+                    \x20   goto l_002                  ; 003: 1802\n\
+                    l_004:\n\
+                    \x20   ret                         ; 004: 003a\n\
+                    \x20   goto 0x3ff                  ; 005: 1bff\n";
+    assert_eq!(expected, buffer.as_str());
+}
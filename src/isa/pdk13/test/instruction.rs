@@ -0,0 +1,66 @@
+use crate::isa::pdk13::{regs::*, Instruction, Word};
+
+const PDK13_WORDS: Word = 0x2000;
+
+#[test]
+fn decode_produces_typed_operands() {
+    assert_eq!(Some(Instruction::MovAK(0x55)), Instruction::decode(0x1755));
+    assert_eq!(Some(Instruction::Goto(0x012)), Instruction::decode(0x1812));
+    assert_eq!(Some(Instruction::Call(0x3FF)), Instruction::decode(0x1FFF));
+    assert_eq!(
+        Some(Instruction::Set1Io {
+            addr: IO_ADDR_PA,
+            bit: 4,
+        }),
+        Instruction::decode(0b1111_1001_0000)
+    );
+    assert_eq!(
+        Some(Instruction::T0snM { addr: 0x0A, bit: 5 }),
+        Instruction::decode(0b10_1010_1010)
+    );
+    assert_eq!(Some(Instruction::Ldt16(0x1A)), Instruction::decode(0x00DB));
+    assert_eq!(Some(Instruction::IncM(0x3A)), Instruction::decode(0x093A));
+    assert_eq!(Some(Instruction::Reti), Instruction::decode(0x003B));
+}
+
+#[test]
+fn decode_ignores_bits_above_13_bit_word() {
+    assert_eq!(Some(Instruction::MovAK(0x55)), Instruction::decode(0xF755));
+}
+
+#[test]
+fn invalid_words_are_not_decoded() {
+    assert_eq!(None, Instruction::decode(0x003E));
+    assert_eq!(None, Instruction::decode(0x1300));
+}
+
+#[test]
+fn encode_produces_instruction_word() {
+    assert_eq!(0x1755, Instruction::MovAK(0x55).encode());
+    assert_eq!(0x0080 | IO_ADDR_CLKMD as Word, Instruction::MovIoA(IO_ADDR_CLKMD).encode());
+    assert_eq!(
+        0b1101_0111_0000,
+        Instruction::T1snIo {
+            addr: IO_ADDR_PA,
+            bit: 3,
+        }
+        .encode()
+    );
+    assert_eq!(0x0000, Instruction::Nop.encode());
+}
+
+#[test]
+fn encode_truncates_operands() {
+    assert_eq!(0x0900 | 0x3F, Instruction::IncM(0xFF).encode());
+    assert_eq!(0x00C0 | 0x1E, Instruction::Stt16(0x1F).encode());
+    assert_eq!(0x1800 | 0x3FF, Instruction::Goto(0xFFFF).encode());
+}
+
+#[test]
+fn every_valid_word_survives_round_trip() {
+    for word in 0..PDK13_WORDS {
+        if let Some(instruction) = Instruction::decode(word) {
+            assert_eq!(word, instruction.encode(), "{:?}", instruction);
+        }
+    }
+}
@@ -6,9 +6,15 @@ fn too_big_opcode_produces_reduced_word() {
 }
 
 #[test]
-fn invalid_opcode_produces_nop() {
+fn invalid_opcode_produces_invalid() {
     let ir = generate_ir(0x003E);
-    assert_eq!(ir.ir_opcode(), IrOpcode::Nop)
+    assert_eq!(ir.ir_opcode(), IrOpcode::Invalid)
+}
+
+#[test]
+fn invalid_opcode_in_operand_group_produces_invalid() {
+    let ir = generate_ir(0x1300);
+    assert_eq!(ir.ir_opcode(), IrOpcode::Invalid)
 }
 
 #[test]
@@ -1,5 +1,9 @@
 mod mock_bus;
 
+mod asm;
+mod decode_table;
+mod disasm;
+mod instruction;
 mod ir_generation;
 mod ir_slot;
 mod pdk_core;
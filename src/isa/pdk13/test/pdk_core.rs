@@ -1,6 +1,7 @@
 use crate::isa::pdk13::{
     bus::{Bus, BusExt},
-    pdk_core::PdkCore,
+    pdk_core::{DecodeMode, PdkCore},
+    Pdk13Error,
 };
 
 use super::mock_bus::MockBus;
@@ -19,7 +20,7 @@ fn nop_is_valid() {
     let mut bus = MockBus::new();
     let mut core = PdkCore::new();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Nop).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(0x001, core.pc());
 }
@@ -36,7 +37,7 @@ fn ldsptl_is_valid() {
     // Adjust rom (Actual rom data)
     bus.rom[0x02BA] = IrSlotBuilder::new().original_word(0x34).build();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Ldsptl).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x34, core.acc())
 }
 
@@ -52,7 +53,7 @@ fn ldspth_is_valid() {
     // Adjust rom (Actual rom data)
     bus.rom[0x02BA] = IrSlotBuilder::new().original_word(0x1234).build();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Ldspth).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x12, core.acc())
 }
 
@@ -64,7 +65,7 @@ fn movak_sets_acc_value() {
         .ir_opcode(IrOpcode::Movak)
         .immediate(0x42)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, core.acc());
 }
 
@@ -77,8 +78,8 @@ fn addca_without_carry_is_nop_with_flags() {
         .immediate(0x42)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Addca).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -97,8 +98,8 @@ fn addca_with_carry_is_valid_1() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Addca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x50, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -117,8 +118,8 @@ fn addca_with_carry_is_valid_2() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Addca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x80, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -137,8 +138,8 @@ fn addca_with_carry_is_valid_3() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Addca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_zero_flag());
@@ -156,8 +157,8 @@ fn subca_without_carry_is_nop_with_flags() {
         .immediate(0x42)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Subca).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -176,8 +177,8 @@ fn subca_with_carry_is_valid_1() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Subca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x1F, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -196,8 +197,8 @@ fn subca_with_carry_is_valid_2() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Subca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x7F, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -216,8 +217,8 @@ fn subca_with_carry_is_valid_3() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Subca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0xFF, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -236,8 +237,8 @@ fn subca_with_carry_is_valid_4() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Subca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_zero_flag());
@@ -256,10 +257,9 @@ fn izsna_skips_instruction_on_if_acc_zero() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Izsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x03, core.pc());
-    core.step(&mut bus); // 2T instruction check
     assert_eq!(0x00, core.acc());
     assert_eq!(0x03, core.pc());
     assert_eq!(true, bus.is_zero_flag());
@@ -278,8 +278,8 @@ fn izsna_not_skips_instruction_on_if_acc_non_zero() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Izsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0xFF, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -298,8 +298,8 @@ fn izsna_has_valid_flags_1() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Izsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x20, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -318,8 +318,8 @@ fn izsna_has_valid_flags_2() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Izsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x80, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -338,10 +338,9 @@ fn dzsna_skips_instruction_on_if_acc_zero() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Dzsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x03, core.pc());
-    core.step(&mut bus); // 2T instruction check
     assert_eq!(0x00, core.acc());
     assert_eq!(0x03, core.pc());
     assert_eq!(true, bus.is_zero_flag());
@@ -360,8 +359,8 @@ fn dzsna_not_skips_instruction_on_if_acc_non_zero() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Dzsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -380,8 +379,8 @@ fn dzsna_has_valid_flags_1() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Dzsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x1F, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -400,8 +399,8 @@ fn dzsna_has_valid_flags_2() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Dzsna).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x7F, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -419,10 +418,8 @@ fn pcadda_adds_pc() {
         .immediate(0x20)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Pcadda).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
-    assert_eq!(0x21, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x20, core.acc());
     assert_eq!(0x21, core.pc());
 }
@@ -436,8 +433,8 @@ fn nota_inverts_acc() {
         .immediate(0x3C)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Nota).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0xC3, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -452,8 +449,8 @@ fn nota_changes_zero_flag() {
         .immediate(0xFF)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Nota).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_zero_flag());
@@ -468,8 +465,8 @@ fn nega_negates_acc() {
         .immediate(0x0F)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Nega).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0xF1, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_zero_flag());
@@ -484,8 +481,8 @@ fn nega_changes_zero_flag() {
         .immediate(0x00)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Nega).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_zero_flag());
@@ -500,8 +497,8 @@ fn sra_shifts_right() {
         .immediate(0x12)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Sra).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x09, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_carry_flag());
@@ -516,8 +513,8 @@ fn sra_sets_carry() {
         .immediate(0x13)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Sra).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x09, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_carry_flag());
@@ -532,8 +529,8 @@ fn sla_shifts_left() {
         .immediate(0x12)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Sla).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x24, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_carry_flag());
@@ -548,8 +545,8 @@ fn sla_sets_carry() {
         .immediate(0x81)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Sla).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_carry_flag());
@@ -564,8 +561,8 @@ fn srca_shifts_right() {
         .immediate(0x12)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Srca).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x09, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_carry_flag());
@@ -580,8 +577,8 @@ fn srca_sets_carry() {
         .immediate(0x13)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Srca).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x09, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_carry_flag());
@@ -597,8 +594,8 @@ fn srca_uses_carry_1() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Srca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x89, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_carry_flag());
@@ -614,8 +611,8 @@ fn srca_uses_carry_2() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Srca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x89, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_carry_flag());
@@ -630,8 +627,8 @@ fn slca_shifts_left() {
         .immediate(0x12)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Slca).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x24, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_carry_flag());
@@ -646,8 +643,8 @@ fn slca_sets_carry() {
         .immediate(0x81)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Slca).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_carry_flag());
@@ -663,8 +660,8 @@ fn slca_uses_carry_1() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Slca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x43, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(false, bus.is_carry_flag());
@@ -680,8 +677,8 @@ fn slca_uses_carry_2() {
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Slca).build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x83, core.acc());
     assert_eq!(0x02, core.pc());
     assert_eq!(true, bus.is_carry_flag());
@@ -696,8 +693,8 @@ fn swapa_swaps_acc_nibbles() {
         .immediate(0xC4)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Swapa).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x4C, core.acc());
     assert_eq!(0x02, core.pc());
 }
@@ -707,7 +704,7 @@ fn wdtreset_triggers_wdt_reset() {
     let mut bus = MockBus::new();
     let mut core = PdkCore::new();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Wdreset).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.wdt_reset_active);
 }
 
@@ -722,8 +719,8 @@ fn pushaf_pushes_acc_and_flags() {
         .immediate(0x12)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Pushaf).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x12, bus.read_ram(0x10));
     assert_eq!(0x34, bus.read_ram(0x11));
     assert_eq!(0x12, bus.read_sp());
@@ -737,7 +734,7 @@ fn popaf_pops_acc_and_flags() {
     bus.write_ram(0x10, 0x12);
     bus.write_ram(0x11, 0x34);
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Popaf).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x12, core.acc());
     assert_eq!(0x34, bus.read_flags());
     assert_eq!(0x10, bus.read_sp());
@@ -752,8 +749,8 @@ fn reset_resets_device() {
         .immediate(0x42)
         .build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Reset).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.reset_active);
     assert_eq!(0, core.acc());
     assert_eq!(0, core.pc());
@@ -765,7 +762,7 @@ fn stopsys_triggers_stop_sys_signal() {
     let mut bus = MockBus::new();
     let mut core = PdkCore::new();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Stopsys).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.stop_sys_active);
 }
 
@@ -774,7 +771,7 @@ fn stopexe_triggers_stop_exe_signal() {
     let mut bus = MockBus::new();
     let mut core = PdkCore::new();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Stopexe).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.stop_exe_active);
 }
 
@@ -783,7 +780,7 @@ fn engint_enables_global_interrupts() {
     let mut bus = MockBus::new();
     let mut core = PdkCore::new();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Engint).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(true, core.global_interrupts_enabled());
 }
 
@@ -793,8 +790,8 @@ fn disgint_disables_global_interrupts() {
     let mut core = PdkCore::new();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Engint).build();
     bus.rom[1] = IrSlotBuilder::new().ir_opcode(IrOpcode::Disgint).build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(false, core.global_interrupts_enabled());
 }
 
@@ -805,9 +802,7 @@ fn ret_performs_return() {
     bus.write_sp(0x04);
     bus.write_ram_word(0x02, 0x123);
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Ret).build();
-    core.step(&mut bus);
-    assert_eq!(0x123, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x123, core.pc());
     assert_eq!(0x02, bus.read_sp());
 }
@@ -819,9 +814,7 @@ fn reti_performs_return_from_interrupt() {
     bus.write_sp(0x04);
     bus.write_ram_word(0x02, 0x123);
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Reti).build();
-    core.step(&mut bus);
-    assert_eq!(0x123, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x123, core.pc());
     assert_eq!(0x02, bus.read_sp());
     assert_eq!(true, core.global_interrupts_enabled());
@@ -832,7 +825,7 @@ fn mul_is_nop() {
     let mut bus = MockBus::new();
     let mut core = PdkCore::new();
     bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Mul).build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
     assert_eq!(0x00, core.acc());
 }
@@ -850,8 +843,8 @@ fn xorioa_xores_io_with_acc() {
         .ir_opcode(IrOpcode::Xorioa)
         .io_address(0x10)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b01011010, bus.read_io(0x10));
 }
 
@@ -867,8 +860,8 @@ fn movioa_moves_acc_to_io() {
         .ir_opcode(IrOpcode::Movioa)
         .mem_address(0x10)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x42, bus.read_io(0x10));
 }
@@ -882,7 +875,7 @@ fn movaio_moves_io_to_acc() {
         .ir_opcode(IrOpcode::Movaio)
         .io_address(0x10)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, core.acc());
 }
 
@@ -895,7 +888,7 @@ fn movaio_sets_zero_flag() {
         .ir_opcode(IrOpcode::Movaio)
         .io_address(0x10)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -910,7 +903,7 @@ fn stt16_loads_tim16_from_ram() {
         .ir_opcode(IrOpcode::Stt16)
         .mem_address(0x10)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x1234, bus.tim16);
 }
 
@@ -923,7 +916,7 @@ fn ldt16_saves_tim16_to_ram() {
         .ir_opcode(IrOpcode::Ldt16)
         .mem_address(0x10)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x34, bus.read_ram(0x10));
     assert_eq!(0x12, bus.read_ram(0x11));
 }
@@ -942,8 +935,8 @@ fn idxmma_performs_indirect_store() {
         .ir_opcode(IrOpcode::Idxmma)
         .mem_address(0x02)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    assert_eq!(2, core.step(&mut bus).unwrap());
 
     assert_eq!(0x42, bus.ram[0x24]);
 }
@@ -959,7 +952,7 @@ fn idxmam_performs_indirect_load() {
         .ir_opcode(IrOpcode::Idxmam)
         .mem_address(0x02)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x42, core.acc());
 }
@@ -974,9 +967,7 @@ fn retk_performs_ret_with_immediate() {
         .ir_opcode(IrOpcode::Retk)
         .immediate(0x42)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x123, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x123, core.pc());
     assert_eq!(0x02, bus.read_sp());
     assert_eq!(0x42, core.acc());
@@ -992,9 +983,7 @@ fn t0snm_skips_instruction_if_bit_clear_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1008,9 +997,7 @@ fn t0snm_skips_instruction_if_bit_clear_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1024,9 +1011,7 @@ fn t0snm_skips_instruction_if_bit_clear_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1040,9 +1025,9 @@ fn t0snm_not_skips_instruction_if_bit_set_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1056,9 +1041,9 @@ fn t0snm_not_skips_instruction_if_bit_set_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1072,9 +1057,9 @@ fn t0snm_not_skips_instruction_if_bit_set_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1088,9 +1073,7 @@ fn t1snm_skips_instruction_if_bit_set_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1104,9 +1087,7 @@ fn t1snm_skips_instruction_if_bit_set_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1120,9 +1101,7 @@ fn t1snm_skips_instruction_if_bit_set_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1136,9 +1115,9 @@ fn t1snm_not_skips_instruction_if_bit_clear_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1152,9 +1131,9 @@ fn t1snm_not_skips_instruction_if_bit_clear_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1168,9 +1147,9 @@ fn t1snm_not_skips_instruction_if_bit_clear_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1184,9 +1163,7 @@ fn t0snio_skips_instruction_if_bit_clear_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1200,9 +1177,7 @@ fn t0snio_skips_instruction_if_bit_clear_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1216,9 +1191,7 @@ fn t0snio_skips_instruction_if_bit_clear_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1232,9 +1205,9 @@ fn t0snio_not_skips_instruction_if_bit_set_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1248,9 +1221,9 @@ fn t0snio_not_skips_instruction_if_bit_set_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1264,9 +1237,9 @@ fn t0snio_not_skips_instruction_if_bit_set_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1280,9 +1253,7 @@ fn t1snio_skips_instruction_if_bit_set_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1296,9 +1267,7 @@ fn t1snio_skips_instruction_if_bit_set_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1312,9 +1281,7 @@ fn t1snio_skips_instruction_if_bit_set_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 }
 
@@ -1328,9 +1295,9 @@ fn t1snio_not_skips_instruction_if_bit_clear_1() {
         .mem_address(0x0E)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1344,9 +1311,9 @@ fn t1snio_not_skips_instruction_if_bit_clear_2() {
         .mem_address(0x0E)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1360,9 +1327,9 @@ fn t1snio_not_skips_instruction_if_bit_clear_3() {
         .mem_address(0x0E)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
 }
 
@@ -1376,7 +1343,7 @@ fn set0m_clears_bit_1() {
         .mem_address(0x10)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0xEF, bus.ram[0x10]);
 }
 
@@ -1390,7 +1357,7 @@ fn set0m_clears_bit_2() {
         .mem_address(0x10)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0xFE, bus.ram[0x10]);
 }
 
@@ -1404,7 +1371,7 @@ fn set0m_clears_bit_3() {
         .mem_address(0x10)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x7F, bus.ram[0x10]);
 }
 
@@ -1418,7 +1385,7 @@ fn set1m_sets_bit_1() {
         .mem_address(0x10)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x10, bus.ram[0x10]);
 }
 
@@ -1432,7 +1399,7 @@ fn set1m_sets_bit_2() {
         .mem_address(0x10)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, bus.ram[0x10]);
 }
 
@@ -1446,7 +1413,7 @@ fn set1m_sets_bit_3() {
         .mem_address(0x10)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x80, bus.ram[0x10]);
 }
 
@@ -1460,7 +1427,7 @@ fn set0io_clears_bit_1() {
         .mem_address(0x10)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0xEF, bus.io[0x10]);
 }
 
@@ -1474,7 +1441,7 @@ fn set0io_clears_bit_2() {
         .mem_address(0x10)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0xFE, bus.io[0x10]);
 }
 
@@ -1488,7 +1455,7 @@ fn set0io_clears_bit_3() {
         .mem_address(0x10)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x7F, bus.io[0x10]);
 }
 
@@ -1502,7 +1469,7 @@ fn set1io_sets_bit_1() {
         .mem_address(0x10)
         .bit_index(4)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x10, bus.io[0x10]);
 }
 
@@ -1516,7 +1483,7 @@ fn set1io_sets_bit_2() {
         .mem_address(0x10)
         .bit_index(0)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, bus.io[0x10]);
 }
 
@@ -1530,7 +1497,7 @@ fn set1io_sets_bit_3() {
         .mem_address(0x10)
         .bit_index(7)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x80, bus.io[0x10]);
 }
 
@@ -1542,9 +1509,7 @@ fn goto_sets_pc_1() {
         .ir_opcode(IrOpcode::Goto)
         .rom_address(0x1FF)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x1FF, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x1FF, core.pc());
 }
 
@@ -1556,9 +1521,7 @@ fn goto_sets_pc_2() {
         .ir_opcode(IrOpcode::Goto)
         .rom_address(0x000)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x000, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x000, core.pc());
 }
 
@@ -1570,9 +1533,7 @@ fn goto_sets_pc_3() {
         .ir_opcode(IrOpcode::Goto)
         .rom_address(0x7FF)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x7FF, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x7FF, core.pc());
 }
 
@@ -1585,16 +1546,13 @@ fn call_calls_function() {
         .ir_opcode(IrOpcode::Goto)
         .rom_address(0x1FF)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x1FF, core.pc());
     bus.rom[0x1FF] = IrSlotBuilder::new()
         .ir_opcode(IrOpcode::Call)
         .rom_address(0x6FF)
         .build();
-    core.step(&mut bus);
-    assert_eq!(0x6FF, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x6FF, core.pc());
     assert_eq!(0x12, bus.read_sp());
     assert_eq!(0x200, bus.read_ram_word(0x10));
@@ -1609,7 +1567,7 @@ fn clearm_clears_memory_cell() {
         .ir_opcode(IrOpcode::Clearm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
 }
 
@@ -1627,8 +1585,8 @@ fn xchm_exchanges_memory_with_acc() {
         .ir_opcode(IrOpcode::Xchm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x21, bus.ram[0x21]);
     assert_eq!(0x42, core.acc());
 }
@@ -1642,7 +1600,7 @@ fn notm_inverts_mem() {
         .ir_opcode(IrOpcode::Notm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x0F, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -1656,7 +1614,7 @@ fn notm_changes_zero_flag() {
         .ir_opcode(IrOpcode::Notm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -1670,7 +1628,7 @@ fn negm_negates_mem() {
         .ir_opcode(IrOpcode::Negm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0xF1, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -1684,7 +1642,7 @@ fn negm_changes_zero_flag() {
         .ir_opcode(IrOpcode::Negm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -1698,7 +1656,7 @@ fn srm_shifts_right() {
         .ir_opcode(IrOpcode::Srm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x08, bus.ram[0x21]);
     assert_eq!(false, bus.is_carry_flag());
 }
@@ -1712,7 +1670,7 @@ fn srm_changes_carry_flag() {
         .ir_opcode(IrOpcode::Srm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x08, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
 }
@@ -1726,7 +1684,7 @@ fn slm_shifts_left() {
         .ir_opcode(IrOpcode::Slm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x22, bus.ram[0x21]);
     assert_eq!(false, bus.is_carry_flag());
 }
@@ -1740,7 +1698,7 @@ fn slm_changes_carry_flag() {
         .ir_opcode(IrOpcode::Slm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
 }
@@ -1754,7 +1712,7 @@ fn srcm_shifts_right() {
         .ir_opcode(IrOpcode::Srcm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x08, bus.ram[0x21]);
     assert_eq!(false, bus.is_carry_flag());
 }
@@ -1768,7 +1726,7 @@ fn srcm_changes_carry_flag() {
         .ir_opcode(IrOpcode::Srcm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x08, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
 }
@@ -1782,7 +1740,7 @@ fn srcm_uses_carry_1() {
         .ir_opcode(IrOpcode::Srcm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x08, bus.ram[0x21]);
 }
 
@@ -1796,7 +1754,7 @@ fn srcm_uses_carry_2() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x88, bus.ram[0x21]);
 }
 
@@ -1809,7 +1767,7 @@ fn slcm_shifts_left() {
         .ir_opcode(IrOpcode::Slcm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x22, bus.ram[0x21]);
     assert_eq!(false, bus.is_carry_flag());
 }
@@ -1823,7 +1781,7 @@ fn slcm_changes_carry_flag() {
         .ir_opcode(IrOpcode::Slcm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
 }
@@ -1837,7 +1795,7 @@ fn slcm_uses_carry_1() {
         .ir_opcode(IrOpcode::Slcm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x22, bus.ram[0x21]);
 }
 
@@ -1851,7 +1809,7 @@ fn slcm_uses_carry_2() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x23, bus.ram[0x21]);
 }
 
@@ -1867,8 +1825,8 @@ fn movma_moves_acc_to_mem() {
         .ir_opcode(IrOpcode::Movma)
         .immediate(0x22)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, bus.ram[0x22]);
 }
 
@@ -1884,8 +1842,8 @@ fn andak_ands_acc_with_immediate() {
         .ir_opcode(IrOpcode::Andak)
         .immediate(0b10011001)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b10001000, core.acc());
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -1902,8 +1860,8 @@ fn andak_changes_zero_flag() {
         .ir_opcode(IrOpcode::Andak)
         .immediate(0x00)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -1920,8 +1878,8 @@ fn orak_ors_acc_with_immediate() {
         .ir_opcode(IrOpcode::Orak)
         .immediate(0b10011001)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b10111011, core.acc());
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -1938,8 +1896,8 @@ fn orak_changes_zero_flag() {
         .ir_opcode(IrOpcode::Orak)
         .immediate(0x00)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -1956,8 +1914,8 @@ fn xorak_xors_acc_with_immediate() {
         .ir_opcode(IrOpcode::Xorak)
         .immediate(0b10011001)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b00110011, core.acc());
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -1974,8 +1932,8 @@ fn xorak_changes_zero_flag() {
         .ir_opcode(IrOpcode::Xorak)
         .immediate(0x00)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -1989,7 +1947,7 @@ fn movam_moves_mem_to_acc() {
         .ir_opcode(IrOpcode::Movam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, core.acc());
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -2003,7 +1961,7 @@ fn movam_changes_zero_flag() {
         .ir_opcode(IrOpcode::Movam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2021,8 +1979,8 @@ fn andam_ands_acc_with_mem() {
         .ir_opcode(IrOpcode::Andam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b10001000, core.acc());
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -2040,8 +1998,8 @@ fn andam_changes_zero_flag() {
         .ir_opcode(IrOpcode::Andam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2059,8 +2017,8 @@ fn oram_ors_acc_with_mem() {
         .ir_opcode(IrOpcode::Oram)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b10111011, core.acc());
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -2078,8 +2036,8 @@ fn oram_changes_zero_flag() {
         .ir_opcode(IrOpcode::Oram)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2097,8 +2055,8 @@ fn xoram_xors_acc_with_mem() {
         .ir_opcode(IrOpcode::Xoram)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b00110011, core.acc());
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -2116,8 +2074,8 @@ fn xoram_changes_zero_flag() {
         .ir_opcode(IrOpcode::Xoram)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2135,8 +2093,8 @@ fn andma_ands_acc_with_mem() {
         .ir_opcode(IrOpcode::Andma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b10001000, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -2154,8 +2112,8 @@ fn andma_changes_zero_flag() {
         .ir_opcode(IrOpcode::Andma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2173,8 +2131,8 @@ fn orma_ors_acc_with_mem() {
         .ir_opcode(IrOpcode::Orma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b10111011, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -2192,8 +2150,8 @@ fn orma_changes_zero_flag() {
         .ir_opcode(IrOpcode::Orma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2211,8 +2169,8 @@ fn xorma_xors_acc_with_mem() {
         .ir_opcode(IrOpcode::Xorma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0b00110011, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
 }
@@ -2230,8 +2188,8 @@ fn xorma_changes_zero_flag() {
         .ir_opcode(IrOpcode::Xorma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2248,8 +2206,8 @@ fn addak_adds_immediate_to_acc() {
         .ir_opcode(IrOpcode::Addak)
         .immediate(4)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(7, core.acc());
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2269,8 +2227,8 @@ fn addak_sets_zero_flag() {
         .ir_opcode(IrOpcode::Addak)
         .immediate(1)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2287,8 +2245,8 @@ fn addak_sets_carry_flag() {
         .ir_opcode(IrOpcode::Addak)
         .immediate(16)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(15, core.acc());
     assert_eq!(true, bus.is_carry_flag());
 }
@@ -2305,8 +2263,8 @@ fn addak_sets_aux_carry_flag() {
         .ir_opcode(IrOpcode::Addak)
         .immediate(5)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(20, core.acc());
     assert_eq!(true, bus.is_aux_carry_flag());
 }
@@ -2323,8 +2281,8 @@ fn addak_sets_overflow_flag() {
         .ir_opcode(IrOpcode::Addak)
         .immediate(5)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(132, core.acc());
     assert_eq!(true, bus.is_overflow_flag());
 }
@@ -2341,8 +2299,8 @@ fn subak_subs_immediate_from_acc() {
         .ir_opcode(IrOpcode::Subak)
         .immediate(7)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(1, core.acc());
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2362,8 +2320,8 @@ fn subak_sets_zero_flag() {
         .ir_opcode(IrOpcode::Subak)
         .immediate(15)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0, core.acc());
     assert_eq!(true, bus.is_zero_flag());
 }
@@ -2380,8 +2338,8 @@ fn subak_sets_carry_flag() {
         .ir_opcode(IrOpcode::Subak)
         .immediate(3)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(254, core.acc());
     assert_eq!(true, bus.is_carry_flag());
 }
@@ -2398,8 +2356,8 @@ fn subak_sets_aux_carry_flag() {
         .ir_opcode(IrOpcode::Subak)
         .immediate(3)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(14, core.acc());
     assert_eq!(true, bus.is_aux_carry_flag());
 }
@@ -2416,8 +2374,8 @@ fn subak_sets_overflow_flag() {
         .ir_opcode(IrOpcode::Subak)
         .immediate(5)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(123, core.acc());
     assert_eq!(true, bus.is_overflow_flag());
 }
@@ -2434,11 +2392,9 @@ fn ceqsnak_skips_instruction_on_if_acc_zero() {
         .ir_opcode(IrOpcode::Ceqsnak)
         .immediate(0x42)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
-    core.step(&mut bus);
-    assert_eq!(0x03, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x03, core.pc());
 
     assert_eq!(0x42, core.acc());
@@ -2460,8 +2416,8 @@ fn ceqsnak_not_skips_instruction_on_if_acc_non_zero() {
         .ir_opcode(IrOpcode::Ceqsnak)
         .immediate(0x41)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
     assert_eq!(0x42, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -2482,8 +2438,8 @@ fn ceqsnak_sets_zero_flag() {
         .ir_opcode(IrOpcode::Ceqsnak)
         .immediate(15)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_zero_flag());
 }
 
@@ -2499,8 +2455,8 @@ fn ceqsnak_sets_carry_flag() {
         .ir_opcode(IrOpcode::Ceqsnak)
         .immediate(3)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_carry_flag());
 }
 
@@ -2516,8 +2472,8 @@ fn ceqsnak_sets_aux_carry_flag() {
         .ir_opcode(IrOpcode::Ceqsnak)
         .immediate(3)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_aux_carry_flag());
 }
 
@@ -2533,8 +2489,8 @@ fn ceqsnak_sets_overflow_flag() {
         .ir_opcode(IrOpcode::Ceqsnak)
         .immediate(5)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_overflow_flag());
 }
 
@@ -2551,11 +2507,9 @@ fn ceqsnam_skips_instruction_on_if_mem_zero() {
         .ir_opcode(IrOpcode::Ceqsnam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
-    core.step(&mut bus);
-    assert_eq!(0x03, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x03, core.pc());
 
     assert_eq!(0x42, core.acc());
@@ -2578,8 +2532,8 @@ fn ceqsnam_not_skips_instruction_on_if_mem_non_zero() {
         .ir_opcode(IrOpcode::Ceqsnam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(0x02, core.pc());
     assert_eq!(0x42, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -2601,8 +2555,8 @@ fn ceqsnam_sets_zero_flag() {
         .ir_opcode(IrOpcode::Ceqsnam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_zero_flag());
 }
 
@@ -2619,8 +2573,8 @@ fn ceqsnam_sets_carry_flag() {
         .ir_opcode(IrOpcode::Ceqsnam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_carry_flag());
 }
 
@@ -2637,8 +2591,8 @@ fn ceqsnam_sets_aux_carry_flag() {
         .ir_opcode(IrOpcode::Ceqsnam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_aux_carry_flag());
 }
 
@@ -2655,8 +2609,8 @@ fn ceqsnam_sets_overflow_flag() {
         .ir_opcode(IrOpcode::Ceqsnam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
     assert_eq!(true, bus.is_overflow_flag());
 }
 
@@ -2669,7 +2623,7 @@ fn addcm_without_carry_is_nop_with_flags() {
         .mem_address(0x21)
         .ir_opcode(IrOpcode::Addcm)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2687,7 +2641,7 @@ fn addcm_with_carry_is_valid_1() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x50, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2705,7 +2659,7 @@ fn addcm_with_carry_is_valid_2() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x80, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2723,7 +2677,7 @@ fn addcm_with_carry_is_valid_3() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
     assert_eq!(true, bus.is_carry_flag());
@@ -2740,7 +2694,7 @@ fn subcm_without_carry_is_nop_with_flags() {
         .ir_opcode(IrOpcode::Subcm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x42, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2758,7 +2712,7 @@ fn subcm_with_carry_is_valid_1() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x1F, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2776,7 +2730,7 @@ fn subcm_with_carry_is_valid_2() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x7F, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2794,7 +2748,7 @@ fn subcm_with_carry_is_valid_3() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0xFF, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(true, bus.is_carry_flag());
@@ -2812,7 +2766,7 @@ fn subcm_with_carry_is_valid_4() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2830,9 +2784,7 @@ fn izsnm_skips_instruction_on_if_acc_zero() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
@@ -2851,7 +2803,7 @@ fn izsnm_not_skips_instruction_on_if_acc_non_zero() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x01, core.pc());
     assert_eq!(0xFF, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -2870,7 +2822,7 @@ fn izsnm_has_valid_flags_1() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x20, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2888,7 +2840,7 @@ fn izsnm_has_valid_flags_2() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
     assert_eq!(0x80, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
     assert_eq!(false, bus.is_carry_flag());
@@ -2906,9 +2858,7 @@ fn dzsnm_skips_instruction_on_if_acc_zero() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    assert_eq!(0x02, core.pc());
-    core.step(&mut bus);
+    assert_eq!(2, core.step(&mut bus).unwrap());
     assert_eq!(0x02, core.pc());
 
     assert_eq!(0x00, bus.ram[0x21]);
@@ -2928,7 +2878,7 @@ fn dzsnm_not_skips_instruction_on_if_acc_non_zero() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x01, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -2947,7 +2897,7 @@ fn dzsnm_has_valid_flags_1() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x1F, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -2966,7 +2916,7 @@ fn dzsnm_has_valid_flags_2() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x7F, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -2984,7 +2934,7 @@ fn incm_increments() {
         .ir_opcode(IrOpcode::Incm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x21, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3002,7 +2952,7 @@ fn incm_has_valid_flags_1() {
         .ir_opcode(IrOpcode::Incm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
@@ -3020,7 +2970,7 @@ fn incm_has_valid_flags_2() {
         .ir_opcode(IrOpcode::Incm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x80, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3038,7 +2988,7 @@ fn decm_decrements() {
         .ir_opcode(IrOpcode::Decm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x18, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3056,7 +3006,7 @@ fn decm_has_valid_flags_1() {
         .ir_opcode(IrOpcode::Decm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
@@ -3074,7 +3024,7 @@ fn decm_has_valid_flags_2() {
         .ir_opcode(IrOpcode::Decm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x7F, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3092,7 +3042,7 @@ fn decm_has_valid_flags_3() {
         .ir_opcode(IrOpcode::Decm)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0xFF, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3114,8 +3064,8 @@ fn addma_adds_acc_to_mem() {
         .ir_opcode(IrOpcode::Addma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x07, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3137,8 +3087,8 @@ fn addma_changes_zero_flag() {
         .ir_opcode(IrOpcode::Addma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
@@ -3157,8 +3107,8 @@ fn addma_changes_carry_flag() {
         .ir_opcode(IrOpcode::Addma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x01, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
@@ -3177,8 +3127,8 @@ fn addma_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Addma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(25, bus.ram[0x21]);
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -3197,8 +3147,8 @@ fn addma_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Addma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(128, bus.ram[0x21]);
     assert_eq!(true, bus.is_overflow_flag());
@@ -3217,8 +3167,8 @@ fn subma_subs_acc_from_mem() {
         .ir_opcode(IrOpcode::Subma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(8, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3240,8 +3190,8 @@ fn subma_changes_zero_flag() {
         .ir_opcode(IrOpcode::Subma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
@@ -3260,8 +3210,8 @@ fn subma_changes_carry_flag() {
         .ir_opcode(IrOpcode::Subma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(255, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
@@ -3280,8 +3230,8 @@ fn subma_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Subma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(14, bus.ram[0x21]);
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -3300,8 +3250,8 @@ fn subma_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Subma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(127, bus.ram[0x21]);
     assert_eq!(true, bus.is_overflow_flag());
@@ -3320,8 +3270,8 @@ fn addcma_adds_acc_to_mem() {
         .ir_opcode(IrOpcode::Addcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x07, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3344,8 +3294,8 @@ fn addcma_uses_carry() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x08, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3367,8 +3317,8 @@ fn addcma_changes_zero_flag() {
         .ir_opcode(IrOpcode::Addcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
@@ -3387,8 +3337,8 @@ fn addcma_changes_carry_flag() {
         .ir_opcode(IrOpcode::Addcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x01, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
@@ -3407,8 +3357,8 @@ fn addcma_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Addcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(25, bus.ram[0x21]);
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -3427,8 +3377,8 @@ fn addcma_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Addcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(128, bus.ram[0x21]);
     assert_eq!(true, bus.is_overflow_flag());
@@ -3447,8 +3397,8 @@ fn subcma_subs_acc_from_mem() {
         .ir_opcode(IrOpcode::Subcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(8, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3471,8 +3421,8 @@ fn subcma_uses_carry() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(7, bus.ram[0x21]);
     assert_eq!(false, bus.is_zero_flag());
@@ -3494,8 +3444,8 @@ fn subcma_changes_zero_flag() {
         .ir_opcode(IrOpcode::Subcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, bus.ram[0x21]);
     assert_eq!(true, bus.is_zero_flag());
@@ -3514,8 +3464,8 @@ fn subcma_changes_carry_flag() {
         .ir_opcode(IrOpcode::Subcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(255, bus.ram[0x21]);
     assert_eq!(true, bus.is_carry_flag());
@@ -3534,8 +3484,8 @@ fn subcma_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Subcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(14, bus.ram[0x21]);
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -3554,8 +3504,8 @@ fn subcma_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Subcma)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(127, bus.ram[0x21]);
     assert_eq!(true, bus.is_overflow_flag());
@@ -3574,8 +3524,8 @@ fn addam_adds_acc_to_mem() {
         .ir_opcode(IrOpcode::Addam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x07, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -3597,8 +3547,8 @@ fn addam_changes_zero_flag() {
         .ir_opcode(IrOpcode::Addam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
@@ -3617,8 +3567,8 @@ fn addam_changes_carry_flag() {
         .ir_opcode(IrOpcode::Addam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x01, core.acc());
     assert_eq!(true, bus.is_carry_flag());
@@ -3637,8 +3587,8 @@ fn addam_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Addam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(25, core.acc());
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -3657,8 +3607,8 @@ fn addam_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Addam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(128, core.acc());
     assert_eq!(true, bus.is_overflow_flag());
@@ -3677,8 +3627,8 @@ fn subam_subs_acc_from_mem() {
         .ir_opcode(IrOpcode::Subam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(8, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -3700,8 +3650,8 @@ fn subam_changes_zero_flag() {
         .ir_opcode(IrOpcode::Subam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
@@ -3720,8 +3670,8 @@ fn subam_changes_carry_flag() {
         .ir_opcode(IrOpcode::Subam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(255, core.acc());
     assert_eq!(true, bus.is_carry_flag());
@@ -3740,8 +3690,8 @@ fn subam_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Subam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(14, core.acc());
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -3760,8 +3710,8 @@ fn subam_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Subam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(127, core.acc());
     assert_eq!(true, bus.is_overflow_flag());
@@ -3780,8 +3730,8 @@ fn addcam_adds_acc_to_mem() {
         .ir_opcode(IrOpcode::Addcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(7, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -3804,8 +3754,8 @@ fn addcam_uses_carry() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(8, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -3827,8 +3777,8 @@ fn addcam_changes_zero_flag() {
         .ir_opcode(IrOpcode::Addcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0, core.acc());
     assert_eq!(true, bus.is_zero_flag());
@@ -3847,8 +3797,8 @@ fn addcam_changes_carry_flag() {
         .ir_opcode(IrOpcode::Addcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x01, core.acc());
     assert_eq!(true, bus.is_carry_flag());
@@ -3867,8 +3817,8 @@ fn addcam_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Addcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(25, core.acc());
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -3887,8 +3837,8 @@ fn addcam_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Addcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(128, core.acc());
     assert_eq!(true, bus.is_overflow_flag());
@@ -3907,8 +3857,8 @@ fn subcam_subs_acc_from_mem() {
         .ir_opcode(IrOpcode::Subcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(8, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -3931,8 +3881,8 @@ fn subcam_uses_carry() {
         .mem_address(0x21)
         .build();
     bus.set_carry_flag(true);
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(7, core.acc());
     assert_eq!(false, bus.is_zero_flag());
@@ -3954,8 +3904,8 @@ fn subcam_changes_zero_flag() {
         .ir_opcode(IrOpcode::Subcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(0x00, core.acc());
     assert_eq!(true, bus.is_zero_flag());
@@ -3974,8 +3924,8 @@ fn subcam_changes_carry_flag() {
         .ir_opcode(IrOpcode::Subcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(255, core.acc());
     assert_eq!(true, bus.is_carry_flag());
@@ -3994,8 +3944,8 @@ fn subcam_changes_aux_carry_flag() {
         .ir_opcode(IrOpcode::Subcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(14, core.acc());
     assert_eq!(true, bus.is_aux_carry_flag());
@@ -4014,9 +3964,163 @@ fn subcam_changes_overflow_flag() {
         .ir_opcode(IrOpcode::Subcam)
         .mem_address(0x21)
         .build();
-    core.step(&mut bus);
-    core.step(&mut bus);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
 
     assert_eq!(127, core.acc());
     assert_eq!(true, bus.is_overflow_flag());
 }
+
+#[test]
+fn interrupt_is_dispatched_when_requested_and_enabled() {
+    use crate::isa::pdk13::regs::*;
+
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.write_sp(0x10);
+    bus.io[IO_ADDR_INTEN as usize] = INT_T16_MASK;
+    bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Engint).build();
+    core.step(&mut bus).unwrap();
+    bus.request_interrupt(INT_T16_MASK);
+    assert_eq!(2, core.step(&mut bus).unwrap());
+    assert_eq!(ROM_ADDR_INTERRUPT_VECTOR, core.pc());
+    assert_eq!(0x01, bus.read_ram_word(0x10));
+    assert_eq!(0x12, bus.read_sp());
+    assert!(!core.global_interrupts_enabled());
+}
+
+#[test]
+fn interrupt_is_not_dispatched_when_globally_disabled() {
+    use crate::isa::pdk13::regs::*;
+
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.io[IO_ADDR_INTEN as usize] = INT_PA0_MASK;
+    bus.request_interrupt(INT_PA0_MASK);
+    core.step(&mut bus).unwrap();
+    assert_eq!(0x01, core.pc());
+}
+
+#[test]
+fn interrupt_is_not_dispatched_when_not_enabled() {
+    use crate::isa::pdk13::regs::*;
+
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.io[IO_ADDR_INTEN as usize] = INT_PA0_MASK;
+    bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Engint).build();
+    core.step(&mut bus).unwrap();
+    bus.request_interrupt(INT_PA4_MASK);
+    core.step(&mut bus).unwrap();
+    assert_eq!(0x02, core.pc());
+    assert_eq!(INT_PA4_MASK, bus.io[IO_ADDR_INTRQ as usize]);
+}
+
+#[test]
+fn interrupt_is_dispatched_after_two_cycle_instruction() {
+    use crate::isa::pdk13::regs::*;
+
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.write_sp(0x10);
+    bus.io[IO_ADDR_INTEN as usize] = INT_TM2_MASK;
+    bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Engint).build();
+    bus.rom[1] = IrSlotBuilder::new()
+        .ir_opcode(IrOpcode::Goto)
+        .rom_address(0x100)
+        .build();
+    core.step(&mut bus).unwrap();
+    assert_eq!(2, core.step(&mut bus).unwrap());
+    assert_eq!(0x100, core.pc());
+    bus.request_interrupt(INT_TM2_MASK);
+    assert_eq!(2, core.step(&mut bus).unwrap());
+    assert_eq!(ROM_ADDR_INTERRUPT_VECTOR, core.pc());
+    assert_eq!(0x100, bus.read_ram_word(0x10));
+}
+
+#[test]
+fn reti_returns_from_dispatched_interrupt() {
+    use crate::isa::pdk13::regs::*;
+
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.write_sp(0x10);
+    bus.io[IO_ADDR_INTEN as usize] = INT_COMPARATOR_MASK;
+    bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Engint).build();
+    bus.rom[ROM_ADDR_INTERRUPT_VECTOR as usize] = IrSlotBuilder::new()
+        .ir_opcode(IrOpcode::Set0io)
+        .io_address(IO_ADDR_INTRQ)
+        .bit_index(4)
+        .build();
+    bus.rom[ROM_ADDR_INTERRUPT_VECTOR as usize + 1] =
+        IrSlotBuilder::new().ir_opcode(IrOpcode::Reti).build();
+    core.step(&mut bus).unwrap();
+    bus.request_interrupt(INT_COMPARATOR_MASK);
+    core.step(&mut bus).unwrap();
+    core.step(&mut bus).unwrap();
+    assert_eq!(0x00, bus.io[IO_ADDR_INTRQ as usize]);
+    core.step(&mut bus).unwrap();
+    assert_eq!(0x01, core.pc());
+    assert_eq!(0x10, bus.read_sp());
+    assert!(core.global_interrupts_enabled());
+}
+
+#[test]
+fn ldsptl_takes_two_cycles() {
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Ldsptl).build();
+    assert_eq!(2, core.step(&mut bus).unwrap());
+    assert_eq!(0x001, core.pc());
+}
+
+#[test]
+fn cycle_counter_accumulates_instruction_cycles() {
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Nop).build();
+    bus.rom[1] = IrSlotBuilder::new()
+        .ir_opcode(IrOpcode::Goto)
+        .rom_address(0x000)
+        .build();
+    assert_eq!(1, core.step(&mut bus).unwrap());
+    assert_eq!(2, core.step(&mut bus).unwrap());
+    assert_eq!(1, core.step(&mut bus).unwrap());
+    assert_eq!(4, core.cycles());
+    core.reset();
+    assert_eq!(4, core.cycles());
+}
+
+#[test]
+fn illegal_instruction_is_nop_in_lenient_mode() {
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    bus.rom[0] = IrSlotBuilder::new()
+        .ir_opcode(IrOpcode::Invalid)
+        .original_word(0x003E)
+        .build();
+    assert_eq!(1, core.step(&mut bus).unwrap());
+    assert_eq!(0x001, core.pc());
+}
+
+#[test]
+fn illegal_instruction_stops_execution_in_strict_mode() {
+    let mut bus = MockBus::new();
+    let mut core = PdkCore::new();
+    core.set_decode_mode(DecodeMode::Strict);
+    bus.rom[0] = IrSlotBuilder::new().ir_opcode(IrOpcode::Nop).build();
+    bus.rom[1] = IrSlotBuilder::new()
+        .ir_opcode(IrOpcode::Invalid)
+        .original_word(0x003E)
+        .build();
+    core.step(&mut bus).unwrap();
+    match core.step(&mut bus) {
+        Err(Pdk13Error::IllegalInstruction { pc, word }) => {
+            assert_eq!(0x001, pc);
+            assert_eq!(0x003E, word);
+        }
+        _ => panic!("Illegal instruction should be reported"),
+    }
+    assert_eq!(0x001, core.pc());
+    assert_eq!(1, core.cycles());
+}
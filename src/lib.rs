@@ -1,4 +1,8 @@
 #![no_std]
 
+pub mod firmware;
 pub mod isa;
 pub mod mcu;
+
+#[cfg(test)]
+mod test_utils;
@@ -1,3 +1,5 @@
+use core::time::Duration;
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Pin(pub(crate) usize);
 
@@ -10,6 +12,7 @@ impl Pin {
     }
 }
 
+/// Analog pin level relative to MCU supply voltage, where `u16::MAX` corresponds to VDD
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct AnalogSignal(u16);
 
@@ -39,13 +42,23 @@ impl AnalogSignal {
     }
 }
 
+/// Simulated time of the event reported to host
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Timestamp {
+    /// System clock cycles elapsed since emulator creation
+    pub cycle: u64,
+    /// Simulated time elapsed since emulator creation
+    pub time: Duration,
+}
+
+/// Host callbacks, each of them receives simulated time of the event. Digital pin inputs
+/// are pushed by host to the emulator instead of being polled
 pub trait HostAdapter {
-    fn read_pin_digital(&self, pin: Pin) -> bool;
-    fn write_pin_digital(&mut self, pin: Pin, value: bool);
+    fn write_pin_digital(&mut self, pin: Pin, value: bool, timestamp: Timestamp);
 
-    fn read_pin_analog(&self, pin: Pin) -> AnalogSignal;
-    fn write_pin_analog(&mut self, pin: Pin, value: AnalogSignal);
+    fn read_pin_analog(&self, pin: Pin, timestamp: Timestamp) -> AnalogSignal;
+    fn write_pin_analog(&mut self, pin: Pin, value: AnalogSignal, timestamp: Timestamp);
 
-    fn set_pin_output_enabled(&mut self, pin: Pin, enabled: bool);
-    fn set_pin_pull_up_enabled(&mut self, pin: Pin, enabled: bool);
+    fn set_pin_output_enabled(&mut self, pin: Pin, enabled: bool, timestamp: Timestamp);
+    fn set_pin_pull_up_enabled(&mut self, pin: Pin, enabled: bool, timestamp: Timestamp);
 }
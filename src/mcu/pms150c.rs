@@ -1,8 +1,40 @@
-use core::cell::Cell;
+mod clock;
+mod comparator;
+pub mod debugger;
+pub mod fuse;
+mod ihrc;
+mod input_queue;
+mod time_base;
+mod timer16;
+mod timer2;
+mod watchdog;
+
+#[cfg(test)]
+mod test;
+
+use core::{cell::Cell, time::Duration};
 
 use crate::{
+    firmware::{
+        easypdk::{
+            Calibration, EasyPdkImage, CALIBRATION_WORDS, FUSE_ADDRESS, MAX_CALIBRATIONS,
+        },
+        FirmwareFile, RomImage,
+    },
     isa::pdk13::*,
-    mcu::host_adapter::{ HostAdapter, Pin },
+    mcu::host_adapter::{ HostAdapter, Pin, Timestamp },
+};
+use self::{
+    clock::ClockAccumulator,
+    comparator::{Comparator, ComparatorInput},
+    debugger::{Access, Space, WatchHit, WatchMask},
+    fuse::FuseOptions,
+    ihrc::{IHRCR_CENTER, IHRC_NOMINAL_FREQUENCY},
+    input_queue::{InputQueue, PinEvent},
+    time_base::TimeBase,
+    timer16::{Timer16, Timer16Clock},
+    timer2::{Timer2, Timer2Clock},
+    watchdog::Watchdog,
 };
 
 const IO_SPACE_SIZE: usize = 0x20;   // 32 bytes;
@@ -13,9 +45,17 @@ const IO_ADDRESS_MASK: IoAddr = IO_SPACE_SIZE as IoAddr - 1;
 const RAM_ADDRESS_MASK: RamAddr = RAM_SPACE_SIZE as RamAddr - 1;
 const ROM_ADDRESS_MASK: RomAddr = ROM_SPACE_SIZE as RomAddr - 1;
 
-const IHRC_FREQUENCY: u32 = 16_000_000; // 16 MHz
 const ILRC_FREQUENCY: u32 = 62_000;     // 62 KHz
 
+const DEFAULT_SUPPLY_MILLIVOLTS: u32 = 5_000; // 5V
+const DEFAULT_LVR_MILLIVOLTS: u32 = 1_800;    // 1.8V
+
+// Unprogrammed ROM word, decoded as `call 0x3FF`
+const OTP_BLANK_WORD: Word = 0x1FFF;
+
+// Pin input queue is checked with this period while system clock is stopped
+const STOPPED_CLOCK_POLL_NANOS: u64 = 1_000; // 1 us
+
 pub mod pins {
     use crate::mcu::host_adapter::Pin;
 
@@ -26,7 +66,48 @@ pub mod pins {
     pub const PA6: Pin = Pin(0b01000000);
     pub const PA7: Pin = Pin(0b10000000);
 
-    pub(crate) const ALL_PINS: [Pin; 6] = [PA0, PA3, PA4, PA5, PA6, PA7];
+    /// Pins of the package with their names
+    pub const ALL_PINS: [(&str, Pin); 6] = [
+        ("PA0", PA0),
+        ("PA3", PA3),
+        ("PA4", PA4),
+        ("PA5", PA5),
+        ("PA6", PA6),
+        ("PA7", PA7),
+    ];
+}
+
+/// Cause of the MCU reset
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ResetCause {
+    PowerOn,
+    Software,
+    Watchdog,
+    /// Supply voltage dropped below low voltage reset level
+    LowVoltage,
+    /// Low level on PA5 with enabled external reset function
+    ExternalPin,
+}
+
+/// ROM programming semantics. Unprogrammed words read as all-ones in both modes
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum RomMode {
+    /// Any word could be overwritten freely
+    #[default]
+    Rewritable,
+    /// One-time programmable ROM: programming could only clear bits, so new value is ANDed
+    /// into the existing word
+    Otp,
+}
+
+/// MCU power mode, changed by STOPSYS/STOPEXE instructions
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum PowerMode {
+    Active,
+    /// Core is halted, oscillators and peripherals keep running
+    StopExe,
+    /// Core is halted, all oscillators are stopped
+    StopSys,
 }
 
 pub struct Pms150c {
@@ -35,25 +116,131 @@ pub struct Pms150c {
 }
 
 pub trait Emulator {
-    /// Returns current frequency to emulate. Please check before each stepping process
-    /// to perform correct step count
+    /// Returns current system clock frequency, 0 if system clock is stopped
     fn get_frequency(&self) -> u32;
-    /// Performs emulation step
-    fn step(&mut self, host: &mut dyn HostAdapter);
+    /// Executes single instruction and returns count of consumed system clock cycles. In
+    /// power-down mode or while MCU is held in reset a single cycle is emulated. If system
+    /// clock is stopped, pins are polled for wake-up and 0 is returned. In strict decode mode
+    /// emulation stops on illegal instruction without advancing simulated time
+    fn step(&mut self, host: &mut dyn HostAdapter) -> Pdk13Result<u32>;
+    /// Returns total count of system clock cycles emulated since emulator creation
+    fn cycles(&self) -> u64;
+    /// Returns simulated time elapsed since emulator creation
+    fn elapsed(&self) -> Duration;
+
+    /// Runs emulation for the given amount of simulated time. Last instruction could end
+    /// slightly after the deadline
+    fn run_for(&mut self, host: &mut dyn HostAdapter, duration: Duration) -> Pdk13Result<()> {
+        let deadline = self.elapsed() + duration;
+        while self.elapsed() < deadline {
+            self.step(host)?;
+        }
+        Ok(())
+    }
+
+    /// Runs emulation for at least `cycles` system clock cycles and returns count of
+    /// emulated cycles. Returns early if system clock has been stopped (e.g. by STOPSYS),
+    /// `run_for` should be used to wait for the wake-up
+    fn run_cycles(&mut self, host: &mut dyn HostAdapter, cycles: u64) -> Pdk13Result<u64> {
+        let start = self.cycles();
+        while self.cycles() - start < cycles {
+            if self.step(host)? == 0 {
+                break;
+            }
+        }
+        Ok(self.cycles() - start)
+    }
+
+    /// Runs emulation until `predicate` returns true or `timeout` of simulated time is
+    /// elapsed. Returns true if predicate has been satisfied
+    fn run_until<P>(
+        &mut self,
+        host: &mut dyn HostAdapter,
+        timeout: Duration,
+        mut predicate: P,
+    ) -> Pdk13Result<bool>
+    where
+        Self: Sized,
+        P: FnMut(&Self) -> bool,
+    {
+        let deadline = self.elapsed() + timeout;
+        loop {
+            if predicate(self) {
+                return Ok(true);
+            }
+            if self.elapsed() >= deadline {
+                return Ok(false);
+            }
+            self.step(host)?;
+        }
+    }
+
     /// Resets internal emulator state and adjusts host state
     fn init(&mut self, host: &mut dyn HostAdapter);
-    /// Write rom memory to specified address
+    /// Write rom memory to specified address. In OTP mode `Pdk13Error::RomVerifyFailed` is
+    /// returned if the programmed word differs from `value`
     fn write_rom(&mut self, address: usize, value: Word) -> Pdk13Result<()>;
+    /// Writes all words of the firmware image to rom, unwritten words are left intact
+    fn load_firmware(&mut self, image: &RomImage) -> Pdk13Result<()> {
+        for (address, word) in image.words() {
+            self.write_rom(address as usize, word)?;
+        }
+        Ok(())
+    }
+    /// Queues pin input level change at the given simulated time. Change is applied on the
+    /// first system clock cycle which starts at or after `time`, changes in the past are
+    /// applied on the next cycle. Pin levels are kept across resets. Queue holds a limited
+    /// number of pending changes, the change is dropped and error is returned if it is full
+    fn set_pin_input(&mut self, pin: Pin, value: bool, time: Duration) -> Pdk13Result<()>;
+    /// Selects core behaviour on illegal instructions, lenient by default
+    fn set_decode_mode(&mut self, mode: DecodeMode);
+    /// Selects ROM programming semantics and erases ROM to the unprogrammed all-ones state
+    fn set_rom_mode(&mut self, mode: RomMode);
+    /// Returns cause of the last MCU reset
+    fn last_reset_cause(&self) -> ResetCause;
+    /// Returns true if MCU is in power-down mode (STOPSYS or STOPEXE)
+    fn is_sleeping(&self) -> bool;
 }
 
 struct State {
     io: [Byte; IO_SPACE_SIZE],
     ram: [Byte; RAM_SPACE_SIZE],
     rom: [IrSlot; ROM_SPACE_SIZE],
+    rom_mode: RomMode,
     clock_frequency: u32,
-    pa: Cell<Byte>,
+    ihrc_frequency: u32,
+    ihrc_error_ppm: i32,
+    // Calibration placeholders of the loaded easy-pdk image which have not been reached yet
+    calibrations: [Option<Calibration>; MAX_CALIBRATIONS],
+    calibration_target: Option<u32>,
     pac: Byte,
     paph: Byte,
+    // Pin levels latched from queued host input changes, used for edge detection
+    input_pins: Byte,
+    input_queue: InputQueue,
+    // Pin levels driven to host by PA data register or peripherals
+    output_pins: Byte,
+    ihrc_clock: ClockAccumulator,
+    ilrc_clock: ClockAccumulator,
+    timer16: Timer16,
+    timer2: Timer2,
+    watchdog: Watchdog,
+    comparator: Comparator,
+    supply_millivolts: u32,
+    lvr_millivolts: u32,
+    fuse: Option<FuseOptions>,
+    power_mode: PowerMode,
+    pa5_reset_enabled: bool,
+    last_reset_cause: ResetCause,
+    // Reset requested by peripherals, performed after the current step
+    pending_reset: Option<ResetCause>,
+    // Reset by PA5 or LVR is applied once and MCU stays halted while it is asserted
+    reset_held: bool,
+    cycles: u64,
+    time: TimeBase,
+    // Addresses watched by debugger and the first watched access of the current step
+    watch_mask: WatchMask,
+    watch_hit: Cell<Option<WatchHit>>,
 }
 
 impl State {
@@ -61,21 +248,94 @@ impl State {
         Self {
             io: [0; IO_SPACE_SIZE],
             ram: [0; RAM_SPACE_SIZE],
-            rom: [IrSlot::default(); ROM_SPACE_SIZE],
+            rom: [IrSlot::from_instruction(OTP_BLANK_WORD); ROM_SPACE_SIZE],
+            rom_mode: RomMode::Rewritable,
             clock_frequency: ILRC_FREQUENCY,
-            pa: Cell::new(0),
+            ihrc_frequency: IHRC_NOMINAL_FREQUENCY,
+            ihrc_error_ppm: 0,
+            calibrations: [None; MAX_CALIBRATIONS],
+            calibration_target: None,
             pac: 0,
             paph: 0,
+            input_pins: 0,
+            input_queue: InputQueue::new(),
+            output_pins: 0,
+            ihrc_clock: ClockAccumulator::new(),
+            ilrc_clock: ClockAccumulator::new(),
+            timer16: Timer16::new(),
+            timer2: Timer2::new(),
+            watchdog: Watchdog::new(),
+            comparator: Comparator::new(),
+            supply_millivolts: DEFAULT_SUPPLY_MILLIVOLTS,
+            lvr_millivolts: DEFAULT_LVR_MILLIVOLTS,
+            fuse: None,
+            power_mode: PowerMode::Active,
+            pa5_reset_enabled: false,
+            last_reset_cause: ResetCause::PowerOn,
+            pending_reset: None,
+            reset_held: false,
+            cycles: 0,
+            time: TimeBase::new(),
+            watch_mask: WatchMask::default(),
+            watch_hit: Cell::new(None),
         }
     }
 
-    fn reset(&mut self) {
-        self.io[regs::IO_ADDR_CLKMD as usize] = 0b11110110;
+    /// Returns system clock frequency, 0 if system clock is stopped
+    fn system_frequency(&self) -> u32 {
+        // All oscillators are stopped in STOPSYS mode
+        if self.power_mode == PowerMode::StopSys {
+            return 0;
+        }
+        self.clock_frequency
+    }
+
+    /// Latches pin input level change and returns produced edges
+    fn latch_input_event(&mut self, event: PinEvent) -> PinEdges {
+        let previous = self.input_pins;
+        self.input_pins = event.apply(previous);
+        PinEdges {
+            rising: self.input_pins & !previous,
+            falling: previous & !self.input_pins,
+        }
+    }
+
+    /// Applies register reset values for the given reset cause. RAM content is undefined
+    /// after power-on (emulator clears it) and is kept for all other reset causes
+    fn reset(&mut self, cause: ResetCause) {
+        // External reset function stays enabled while MCU is held in reset by PA5
+        let pa5_reset_enabled = cause == ResetCause::ExternalPin && self.pa5_reset_enabled;
+        // IHRC trimming is kept, power-on value trims error-free oscillator to 16 MHz
+        let ihrcr = if cause == ResetCause::PowerOn {
+            IHRCR_CENTER
+        } else {
+            self.io[regs::IO_ADDR_IHRCR as usize]
+        };
+        // All IO registers except CLKMD, PADIER and IHRCR are cleared (FLAGS, SP, INTEN,
+        // INTRQ, PA, PAC, PAPH, timer and comparator registers)
+        self.io = [0; IO_SPACE_SIZE];
+        self.io[regs::IO_ADDR_CLKMD as usize] = 0b11110110 | pa5_reset_enabled as Byte;
         self.io[regs::IO_ADDR_PADIER as usize] = 0b11111001;
+        self.io[regs::IO_ADDR_IHRCR as usize] = ihrcr;
+        if cause == ResetCause::PowerOn {
+            self.ram = [0; RAM_SPACE_SIZE];
+        }
+        self.ihrc_frequency = ihrc::ihrc_frequency(ihrcr, self.ihrc_error_ppm);
+        self.pa5_reset_enabled = pa5_reset_enabled;
         self.clock_frequency = ILRC_FREQUENCY;
-        self.pa.set(0);
         self.pac = 0;
         self.paph = 0;
+        self.output_pins = 0;
+        self.ihrc_clock.reset();
+        self.ilrc_clock.reset();
+        self.timer16.reset();
+        self.timer2.reset();
+        self.watchdog.reset();
+        self.watchdog.set_enabled(true);
+        self.comparator.reset();
+        self.power_mode = PowerMode::Active;
+        self.last_reset_cause = cause;
+        self.pending_reset = None;
     }
 }
 
@@ -86,108 +346,629 @@ impl Pms150c {
             state: State::new(),
         }
     }
+
+    /// Sets MCU supply voltage, which is used to scale internal voltage references relative
+    /// to analog pin levels. Analog levels reported by host are relative to VDD
+    pub fn set_supply_voltage(&mut self, millivolts: u32) {
+        self.state.supply_millivolts = millivolts;
+    }
+
+    /// Sets supply voltage level below which MCU is held in reset
+    pub fn set_low_voltage_reset_level(&mut self, millivolts: u32) {
+        self.state.lvr_millivolts = millivolts;
+    }
+
+    /// Applies fuse options. Only low voltage reset level is taken from the fuse, security,
+    /// IO drive and boot-up time are not modelled
+    pub fn set_fuse_options(&mut self, options: FuseOptions) {
+        self.state.fuse = Some(options);
+        self.state.lvr_millivolts = options.lvr.millivolts();
+    }
+
+    /// Returns applied fuse options, `None` if fuse has not been programmed
+    pub fn fuse_options(&self) -> Option<FuseOptions> {
+        self.state.fuse
+    }
+
+    /// Sets deviation of untrimmed IHRC oscillator from its nominal frequency. Takes effect
+    /// on the next IHRCR write or reset
+    pub fn set_ihrc_error_ppm(&mut self, ppm: i32) {
+        self.state.ihrc_error_ppm = ppm;
+    }
+
+    /// Overrides target frequency of easy-pdk IHRC calibrations, `None` selects frequency
+    /// requested by the image
+    pub fn set_calibration_target(&mut self, frequency: Option<u32>) {
+        self.state.calibration_target = frequency;
+    }
+
+    /// Loads easy-pdk image. Calibration placeholders are written with programmer stubs and
+    /// patched when execution reaches them, like programmer does after tuning IHRCR. Fuse word
+    /// is written to ROM and applied if it is present in the image
+    pub fn load_easypdk_image(&mut self, image: &EasyPdkImage) -> Pdk13Result<()> {
+        self.load_firmware(&image.programmed_code())?;
+        for (slot, calibration) in self.state.calibrations.iter_mut().zip(image.calibrations()) {
+            *slot = Some(*calibration);
+        }
+        if let Some(fuse) = image.fuse() {
+            self.write_rom(FUSE_ADDRESS as usize, fuse)?;
+            self.set_fuse_options(FuseOptions::from_word(fuse));
+        }
+        Ok(())
+    }
+
+    /// Loads firmware file of either format
+    pub fn load_firmware_file(&mut self, file: &FirmwareFile) -> Pdk13Result<()> {
+        match file {
+            FirmwareFile::EasyPdk(image) => self.load_easypdk_image(image),
+            FirmwareFile::Binary(image) => self.load_firmware(image),
+        }
+    }
+
+    /// Tunes IHRCR for the calibration placeholder at PC and patches it. Target frequency
+    /// is the system clock frequency, so IHRC is tuned according to the current divider.
+    /// Patch is programmed by the ROM mode rules, it only clears bits of the stub, so OTP ROM
+    /// accepts it
+    fn calibrate_ihrc(&mut self) -> Pdk13Result<()> {
+        let pc = self.core.pc();
+        let slot = self.state.calibrations.iter_mut()
+            .find(|slot| matches!(slot, Some(calibration) if calibration.address == pc));
+        let calibration = match slot.and_then(Option::take) {
+            Some(calibration) => calibration,
+            None => return Ok(()),
+        };
+        let target = self.state.calibration_target.unwrap_or(calibration.frequency);
+        let clkmd = self.state.io[regs::IO_ADDR_CLKMD as usize];
+        let divider = ihrc_divider(clkmd).unwrap_or(1);
+        let ihrcr = ihrc::calibrate(target.saturating_mul(divider), self.state.ihrc_error_ppm);
+        for (offset, word) in calibration.patch(ihrcr).iter().copied().enumerate() {
+            let address = (calibration.address as usize + offset) & ROM_ADDRESS_MASK as usize;
+            self.write_rom(address, word)?;
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self, cause: ResetCause, host: &mut dyn HostAdapter) {
+        self.core.reset();
+        HostBridge::new(&mut self.state, host).apply_reset(cause);
+    }
 }
 
 impl Emulator for Pms150c {
     fn get_frequency(&self) -> u32 {
-        self.state.clock_frequency
+        self.state.system_frequency()
     }
 
-    fn step(&mut self, host: &mut dyn HostAdapter) {
-        self.core.step(&mut HostBridge::new(&mut self.state, host));
-    }
+    fn step(&mut self, host: &mut dyn HostAdapter) -> Pdk13Result<u32> {
+        if self.state.calibrations.iter().any(Option::is_some) {
+            self.calibrate_ihrc()?;
+        }
+        let frequency = self.get_frequency();
+        let mut bridge = HostBridge::new(&mut self.state, host);
+        let edges = bridge.sample_inputs();
+        let reset = bridge.reset_asserted();
+        let reset_held = core::mem::replace(&mut bridge.state.reset_held, reset.is_some());
+        let cycles = if let Some(cause) = reset {
+            if !reset_held {
+                self.reset(cause, host);
+            }
+            1
+        } else if frequency == 0 {
+            // System clock is stopped, only pin change could wake up MCU
+            bridge.wake_up_on_pin_change(edges);
+            0
+        } else if bridge.state.power_mode == PowerMode::Active {
+            let cycles = self.core.step(&mut bridge)?;
+            bridge.tick(edges);
+            // Peripherals are clocked on each cycle of multi-cycle instructions
+            for _ in 1..cycles {
+                bridge.next_cycle();
+                let edges = bridge.sample_inputs();
+                bridge.tick(edges);
+            }
+            cycles
+        } else {
+            bridge.tick_stop_exe(edges);
+            1
+        };
 
-    fn init(&mut self, host: &mut dyn HostAdapter) {
-        for pin in pins::ALL_PINS.iter().copied() {
-            host.set_pin_output_enabled(pin, false);
-            host.set_pin_pull_up_enabled(pin, false);
-            host.write_pin_digital(pin, false);
+        self.state.cycles += cycles as u64;
+        if frequency == 0 {
+            self.state.time.advance_nanos(STOPPED_CLOCK_POLL_NANOS);
+        } else {
+            self.state.time.advance_cycles(cycles, frequency);
+        }
+        if let Some(cause) = self.state.pending_reset {
+            self.reset(cause, host);
         }
+        Ok(cycles)
+    }
 
-        self.state.reset();
+    fn cycles(&self) -> u64 {
+        self.state.cycles
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.state.time.elapsed()
+    }
+
+    fn init(&mut self, host: &mut dyn HostAdapter) {
+        self.reset(ResetCause::PowerOn, host);
     }
 
     fn write_rom(&mut self, address: usize, value: Word) -> Pdk13Result<()> {
         if address >= ROM_SPACE_SIZE {
             return Err(Pdk13Error::TooBigAddress(address, ROM_SPACE_SIZE));
         }
-        self.state.rom[address] = IrSlot::from_instruction(value);
+        let word = match self.state.rom_mode {
+            RomMode::Rewritable => value,
+            RomMode::Otp => self.state.rom[address].original_word() & value,
+        };
+        // Placeholders overwritten by the new firmware are not patched anymore
+        for slot in self.state.calibrations.iter_mut() {
+            let overwritten = matches!(slot, Some(calibration)
+                if address.wrapping_sub(calibration.address as usize) < CALIBRATION_WORDS);
+            if overwritten {
+                *slot = None;
+            }
+        }
+        self.state.rom[address] = IrSlot::from_instruction(word);
+        if word != value {
+            return Err(Pdk13Error::RomVerifyFailed {
+                address: address as RomAddr,
+                expected: value,
+                actual: word,
+            });
+        }
+        Ok(())
+    }
+
+    fn load_firmware(&mut self, image: &RomImage) -> Pdk13Result<()> {
+        // Placeholders of the previously loaded easy-pdk image belong to the old firmware
+        self.state.calibrations = [None; MAX_CALIBRATIONS];
+        for (address, word) in image.words() {
+            self.write_rom(address as usize, word)?;
+        }
         Ok(())
     }
+
+    fn set_pin_input(&mut self, pin: Pin, value: bool, time: Duration) -> Pdk13Result<()> {
+        let event = PinEvent {
+            time,
+            pin_mask: pin.port_bit_mask(),
+            level: value,
+        };
+        if self.state.input_queue.push(event) {
+            Ok(())
+        } else {
+            Err(Pdk13Error::InputQueueFull(time))
+        }
+    }
+
+    fn set_decode_mode(&mut self, mode: DecodeMode) {
+        self.core.set_decode_mode(mode);
+    }
+
+    fn set_rom_mode(&mut self, mode: RomMode) {
+        self.state.rom = [IrSlot::from_instruction(OTP_BLANK_WORD); ROM_SPACE_SIZE];
+        self.state.calibrations = [None; MAX_CALIBRATIONS];
+        self.state.rom_mode = mode;
+    }
+
+    fn last_reset_cause(&self) -> ResetCause {
+        self.state.last_reset_cause
+    }
+
+    fn is_sleeping(&self) -> bool {
+        self.state.power_mode != PowerMode::Active
+    }
 }
 
 struct HostBridge<'a> {
     state: &'a mut State,
     host: &'a mut dyn HostAdapter,
+    // Current cycle offset from the beginning of the step
+    cycle_offset: u32,
+    // System clock frequency at the beginning of the step, simulated time is advanced with it
+    frequency: u32,
+}
+
+#[derive(Copy, Clone, Default)]
+struct PinEdges {
+    rising: Byte,
+    falling: Byte,
+}
+
+impl PinEdges {
+    fn rising(self, pin: Pin) -> bool {
+        self.rising & pin.port_bit_mask() != 0
+    }
+
+    fn falling(self, pin: Pin) -> bool {
+        self.falling & pin.port_bit_mask() != 0
+    }
+
+    /// Returns pin edges selected by INTEGS 2-bit field (both, rising or falling edge)
+    fn selected_by_integs(self, pin: Pin, integs: Byte) -> Byte {
+        let edges = match integs & 0b11 {
+            0b00 => self.rising | self.falling,
+            0b01 => self.rising,
+            0b10 => self.falling,
+            _ => 0,
+        };
+        edges & pin.port_bit_mask()
+    }
+}
+
+fn ihrc_enabled(clkmd: Byte) -> bool {
+    clkmd & 0b00010000 != 0
+}
+
+fn ilrc_enabled(clkmd: Byte) -> bool {
+    clkmd & 0b00000100 != 0
+}
+
+fn sys_freq_flags(clkmd: Byte) -> Byte {
+    let freq_flags_lo = (clkmd >> 5) & 0x07;
+    let freq_flags_hi = (clkmd >> 3) & 0x01;
+    freq_flags_lo | (freq_flags_hi << 3)
 }
 
-fn decode_sys_freq(clkmd: Byte) -> u32 {
-    let freq_flags = {
-        let freq_flags_lo = (clkmd >> 5) & 0x07;
-        let freq_flags_hi = (clkmd >> 3) & 0x01;
-        freq_flags_lo | (freq_flags_hi << 3)
-    };
-    let ihrc_enabled = (clkmd & 0b00010000) != 0;
-    let ilrc_enabled = (clkmd & 0b00000100) != 0;
+/// Returns IHRC divider if system clock is derived from IHRC
+fn ihrc_divider(clkmd: Byte) -> Option<u32> {
+    match sys_freq_flags(clkmd) {
+        0b0000 => Some(4),
+        0b0001 => Some(2),
+        0b1000 => Some(16),
+        0b1001 => Some(8),
+        0b1011 => Some(32),
+        0b1100 => Some(64),
+        _ => None,
+    }
+}
 
-    if (matches!(freq_flags, 0b0000 | 0b0001 | 0b1000 | 0b1001 | 0b1011 | 0b1100) & !ihrc_enabled)
+fn decode_sys_freq(clkmd: Byte, ihrc_frequency: u32) -> u32 {
+    let freq_flags = sys_freq_flags(clkmd);
+    let ihrc_enabled = ihrc_enabled(clkmd);
+    let ilrc_enabled = ilrc_enabled(clkmd);
+
+    if (ihrc_divider(clkmd).is_some() & !ihrc_enabled)
         | (matches!(freq_flags, 0b0110 | 0b0111 | 0b1010) & !ilrc_enabled)
     {
         // User code stopped clocking
         return 0;
     }
 
+    if let Some(divider) = ihrc_divider(clkmd) {
+        return ihrc_frequency / divider;
+    }
+
     match freq_flags {
-        0b0000 => IHRC_FREQUENCY  / 4,
-        0b0001 => IHRC_FREQUENCY / 2,
-        0b0010 ..= 0b0101 => 0,
         0b0110 => ILRC_FREQUENCY / 4,
         0b0111 => ILRC_FREQUENCY,
-        0b1000 => IHRC_FREQUENCY / 16,
-        0b1001 => IHRC_FREQUENCY / 8,
         0b1010 => ILRC_FREQUENCY / 16,
-        0b1011 => IHRC_FREQUENCY / 32,
-        0b1100 => IHRC_FREQUENCY / 64,
-        0b1101 ..= 0b1111 => 0,
-        0b10000 ..= 0xFF => unreachable!(),
+        _ => 0,
     }
 }
 
 impl<'a> HostBridge<'a> {
     pub fn new(state: &'a mut State, host: &'a mut dyn HostAdapter) -> Self {
-        Self { state, host }
+        let frequency = state.system_frequency();
+        Self {
+            state,
+            host,
+            cycle_offset: 0,
+            frequency,
+        }
+    }
+
+    /// Advances host event timestamps to the next cycle of multi-cycle instruction
+    fn next_cycle(&mut self) {
+        self.cycle_offset += 1;
+    }
+
+    /// Returns simulated time of the current cycle
+    fn timestamp(&self) -> Timestamp {
+        let mut time = self.state.time;
+        time.advance_cycles(self.cycle_offset, self.frequency);
+        Timestamp {
+            cycle: self.state.cycles + self.cycle_offset as u64,
+            time: time.elapsed(),
+        }
+    }
+
+    /// Clocks peripherals for a single system clock cycle. Returns true if timer event has
+    /// been occurred
+    fn tick(&mut self, edges: PinEdges) -> bool {
+        let clkmd = self.state.io[regs::IO_ADDR_CLKMD as usize];
+        let sys_frequency = self.state.clock_frequency;
+        let ihrc_frequency = if ihrc_enabled(clkmd) { self.state.ihrc_frequency } else { 0 };
+        let ilrc_frequency = if ilrc_enabled(clkmd) { ILRC_FREQUENCY } else { 0 };
+        let ihrc_pulses = self.state.ihrc_clock.ticks(ihrc_frequency, sys_frequency);
+        let ilrc_pulses = self.state.ilrc_clock.ticks(ilrc_frequency, sys_frequency);
+
+        let timer16_overflow = self.tick_timer16(ihrc_pulses, ilrc_pulses, edges);
+        let timer2_matched = self.tick_timer2_and_comparator(ihrc_pulses, ilrc_pulses, edges);
+        self.tick_watchdog(ilrc_pulses);
+        timer16_overflow | timer2_matched
+    }
+
+    /// Wakes up MCU from power-down mode on pin change for pins enabled in PADIER
+    fn wake_up_on_pin_change(&mut self, edges: PinEdges) {
+        let padier = self.state.io[regs::IO_ADDR_PADIER as usize];
+        if (edges.rising | edges.falling) & padier != 0 {
+            self.state.power_mode = PowerMode::Active;
+        }
+    }
+
+    /// Emulates single system clock cycle in STOPEXE mode. Peripherals keep running and
+    /// timer events wake up MCU
+    fn tick_stop_exe(&mut self, edges: PinEdges) {
+        self.wake_up_on_pin_change(edges);
+        if self.tick(edges) {
+            self.state.power_mode = PowerMode::Active;
+        }
+    }
+
+    fn enter_power_down(&mut self, mode: PowerMode) {
+        self.state.power_mode = mode;
+    }
+
+    /// Clocks Timer2 and comparator, returns true on Timer2 counter match
+    fn tick_timer2_and_comparator(
+        &mut self,
+        ihrc_pulses: u32,
+        ilrc_pulses: u32,
+        edges: PinEdges,
+    ) -> bool {
+        if !self.state.comparator.enabled() {
+            return self.tick_timer2(ihrc_pulses, ilrc_pulses, edges, false);
+        }
+
+        let result = self.compare_inputs();
+        let sampled = self.state.comparator.sampled_by_timer2();
+        let comparator_rising = !sampled && self.update_comparator_output(result);
+        let timer2_matched =
+            self.tick_timer2(ihrc_pulses, ilrc_pulses, edges, comparator_rising);
+        if sampled && timer2_matched {
+            self.update_comparator_output(result);
+        }
+        timer2_matched
+    }
+
+    fn comparator_input_level(&self, input: ComparatorInput) -> u16 {
+        match input {
+            ComparatorInput::Pin(pin) => self.host.read_pin_analog(pin, self.timestamp()).as_u16(),
+            ComparatorInput::Bandgap => {
+                Comparator::bandgap_reference(self.state.supply_millivolts)
+            }
+            ComparatorInput::InternalReference => self.state.comparator.internal_reference(),
+            ComparatorInput::Reserved => 0,
+        }
+    }
+
+    fn compare_inputs(&self) -> bool {
+        let comparator = &self.state.comparator;
+        let plus = self.comparator_input_level(comparator.plus_input());
+        let minus = self.comparator_input_level(comparator.minus_input());
+        comparator.compare(plus, minus)
+    }
+
+    /// Updates comparator output, returns true on rising edge of the output
+    fn update_comparator_output(&mut self, output: bool) -> bool {
+        if !self.state.comparator.update_output(output) {
+            return false;
+        }
+        self.request_interrupt(regs::INT_COMPARATOR_MASK);
+        if self.state.comparator.pa0_output_enabled() {
+            self.update_output_pins();
+        }
+        output
+    }
+
+    fn tick_watchdog(&mut self, ilrc_pulses: u32) {
+        let misc = self.state.io[regs::IO_ADDR_MISC as usize];
+        if self.state.watchdog.clock(ilrc_pulses, misc) {
+            self.state.pending_reset = Some(ResetCause::Watchdog);
+        }
+    }
+
+    /// Clocks Timer16, returns true on selected counter bit overflow
+    fn tick_timer16(&mut self, ihrc_pulses: u32, ilrc_pulses: u32, edges: PinEdges) -> bool {
+        let pulses = match self.state.timer16.clock_source() {
+            Timer16Clock::Disabled => 0,
+            Timer16Clock::SysClk => 1,
+            Timer16Clock::Ihrc => ihrc_pulses,
+            Timer16Clock::Ilrc => ilrc_pulses,
+            Timer16Clock::Pa0Falling => edges.falling(pins::PA0) as u32,
+            Timer16Clock::Pa4Falling => edges.falling(pins::PA4) as u32,
+        };
+        let falling_edge = self.state.io[regs::IO_ADDR_INTEGS as usize] & 0b00010000 != 0;
+        let overflow = self.state.timer16.clock(pulses, falling_edge);
+        if overflow {
+            self.request_interrupt(regs::INT_T16_MASK);
+        }
+        overflow
+    }
+
+    /// Clocks Timer2, returns true on counter match
+    fn tick_timer2(
+        &mut self,
+        ihrc_pulses: u32,
+        ilrc_pulses: u32,
+        edges: PinEdges,
+        comparator_rising: bool,
+    ) -> bool {
+        let pulses = match self.state.timer2.clock_source() {
+            Timer2Clock::Disabled => 0,
+            Timer2Clock::SysClk => 1,
+            Timer2Clock::Ihrc => ihrc_pulses,
+            Timer2Clock::Ilrc => ilrc_pulses,
+            Timer2Clock::Comparator => comparator_rising as u32,
+            Timer2Clock::Pa0Rising => edges.rising(pins::PA0) as u32,
+            Timer2Clock::Pa0Falling => edges.falling(pins::PA0) as u32,
+            Timer2Clock::Pa4Rising => edges.rising(pins::PA4) as u32,
+            Timer2Clock::Pa4Falling => edges.falling(pins::PA4) as u32,
+        };
+        let matched = self.state.timer2.clock(pulses);
+        if matched {
+            self.request_interrupt(regs::INT_TM2_MASK);
+        }
+        if self.state.timer2.output_pin().is_some() {
+            self.update_output_pins();
+        }
+        matched
+    }
+
+    /// Applies queued pin input changes which are due at the current cycle and returns
+    /// edges since the previous sample. Pulses shorter than a cycle produce both edges
+    fn sample_pins(&mut self) -> PinEdges {
+        let time = self.timestamp().time;
+        let mut edges = PinEdges::default();
+        while let Some(event) = self.state.input_queue.pop_due(time) {
+            let event_edges = self.state.latch_input_event(event);
+            edges.rising |= event_edges.rising;
+            edges.falling |= event_edges.falling;
+        }
+        edges
+    }
+
+    /// Samples pins at the beginning of system clock cycle and latches external pin
+    /// interrupt requests according to INTEGS edge selection
+    fn sample_inputs(&mut self) -> PinEdges {
+        let edges = self.sample_pins();
+        let integs = self.state.io[regs::IO_ADDR_INTEGS as usize];
+        let padier = self.state.io[regs::IO_ADDR_PADIER as usize];
+        if edges.selected_by_integs(pins::PA0, integs) & padier != 0 {
+            self.request_interrupt(regs::INT_PA0_MASK);
+        }
+        if edges.selected_by_integs(pins::PA4, integs >> 2) & padier != 0 {
+            self.request_interrupt(regs::INT_PA4_MASK);
+        }
+        edges
     }
 
     fn on_change_clkmd(&mut self, clkmd: Byte) {
-        self.state.clock_frequency = decode_sys_freq(clkmd);
+        self.state.clock_frequency = decode_sys_freq(clkmd, self.state.ihrc_frequency);
         self.set_watchdog_enabled(clkmd & 0b00000010 != 0);
         self.set_pa5_reset_enabled(clkmd & 0b00000001 != 0);
     }
 
-    fn set_watchdog_enabled(&mut self, _enabled: bool) {}
-    fn set_pa5_reset_enabled(&mut self, _enabled: bool) {}
+    /// Latches the first access to the address watched by debugger
+    fn watch(&self, space: Space, access: Access, address: Byte, value: Byte) {
+        if self.state.watch_mask.matches(space, access, address)
+            && self.state.watch_hit.get().is_none()
+        {
+            self.state.watch_hit.set(Some(WatchHit {
+                space,
+                address,
+                access,
+                value,
+            }));
+        }
+    }
+
+    fn on_change_ihrcr(&mut self, ihrcr: Byte) {
+        self.state.ihrc_frequency = ihrc::ihrc_frequency(ihrcr, self.state.ihrc_error_ppm);
+        let clkmd = self.state.io[regs::IO_ADDR_CLKMD as usize];
+        self.state.clock_frequency = decode_sys_freq(clkmd, self.state.ihrc_frequency);
+    }
+
+    fn set_watchdog_enabled(&mut self, enabled: bool) {
+        self.state.watchdog.set_enabled(enabled);
+    }
+
+    fn set_pa5_reset_enabled(&mut self, enabled: bool) {
+        self.state.pa5_reset_enabled = enabled;
+    }
 
-    fn on_change_pin(&mut self, pa: Byte, pin: Pin) {
-        let toggle = (self.state.pa.get() ^ pa) & pin.port_bit_mask() != 0;
+    /// Returns cause of the reset if MCU should be held in reset by low supply voltage or
+    /// low level on PA5
+    fn reset_asserted(&self) -> Option<ResetCause> {
+        if self.state.supply_millivolts < self.state.lvr_millivolts {
+            return Some(ResetCause::LowVoltage);
+        }
+        let pa5_low = self.state.input_pins & pins::PA5.port_bit_mask() == 0;
+        if self.state.pa5_reset_enabled && pa5_low {
+            return Some(ResetCause::ExternalPin);
+        }
+        None
+    }
+
+    /// Resets peripherals and returns host pins to their reset state
+    fn apply_reset(&mut self, cause: ResetCause) {
+        self.state.reset(cause);
+        let timestamp = self.timestamp();
+        for (_, pin) in pins::ALL_PINS.iter().copied() {
+            self.host.set_pin_output_enabled(pin, false, timestamp);
+            self.host.set_pin_pull_up_enabled(pin, false, timestamp);
+            self.host.write_pin_digital(pin, false, timestamp);
+        }
+    }
+
+    fn on_change_pin(&mut self, levels: Byte, pin: Pin) {
+        let toggle = (self.state.output_pins ^ levels) & pin.port_bit_mask() != 0;
         if toggle {
-            self.host.write_pin_digital(pin, pa & pin.port_bit_mask() != 0)
+            let timestamp = self.timestamp();
+            self.host.write_pin_digital(pin, levels & pin.port_bit_mask() != 0, timestamp)
+        }
+    }
+
+    fn on_change_pa(&mut self) {
+        self.update_output_pins();
+    }
+
+    /// Drives host pins with PA data register value, overridden by peripheral outputs
+    fn update_output_pins(&mut self) {
+        let mut levels = self.state.io[regs::IO_ADDR_PA as usize];
+        if let Some(pin) = self.state.timer2.output_pin() {
+            levels &= !pin.port_bit_mask();
+            if self.state.timer2.output() {
+                levels |= pin.port_bit_mask();
+            }
         }
+        if self.state.comparator.pa0_output_enabled() {
+            levels &= !pins::PA0.port_bit_mask();
+            if self.state.comparator.output() {
+                levels |= pins::PA0.port_bit_mask();
+            }
+        }
+
+        self.on_change_pin(levels, pins::PA7);
+        self.on_change_pin(levels, pins::PA6);
+        self.on_change_pin(levels, pins::PA5);
+        self.on_change_pin(levels, pins::PA4);
+        self.on_change_pin(levels, pins::PA3);
+        self.on_change_pin(levels, pins::PA0);
+        self.state.output_pins = levels;
+    }
+
+    fn on_change_tm2c(&mut self, tm2c: Byte) {
+        self.state.timer2.set_control(tm2c);
+        self.update_output_pins();
+    }
+
+    fn on_change_gpcc(&mut self, gpcc: Byte) {
+        self.state.comparator.set_control(gpcc);
+        self.update_output_pins();
     }
 
-    fn on_change_pa(&mut self, pa: Byte) {
-        self.on_change_pin(pa, pins::PA7);
-        self.on_change_pin(pa, pins::PA6);
-        self.on_change_pin(pa, pins::PA5);
-        self.on_change_pin(pa, pins::PA4);
-        self.on_change_pin(pa, pins::PA3);
-        self.on_change_pin(pa, pins::PA0);
-        self.state.pa.set(pa);
+    fn on_change_gpcs(&mut self, gpcs: Byte) {
+        self.state.comparator.set_select(gpcs);
+        self.update_output_pins();
+    }
+
+    /// Returns output pins from PA data register and latched input pins
+    fn read_pa(&self) -> Byte {
+        let pa = self.state.io[regs::IO_ADDR_PA as usize];
+        (pa & self.state.pac) | (self.state.input_pins & !self.state.pac)
     }
 
     fn on_change_pin_control(&mut self, pac: Byte, pin: Pin) {
         let toggle = (self.state.pac ^ pac) & pin.port_bit_mask() != 0;
         if toggle {
-            self.host.set_pin_output_enabled(pin, pac & pin.port_bit_mask() != 0);
+            let timestamp = self.timestamp();
+            self.host.set_pin_output_enabled(pin, pac & pin.port_bit_mask() != 0, timestamp);
         }
     }
 
@@ -204,7 +985,8 @@ impl<'a> HostBridge<'a> {
     fn on_change_pin_pull_up(&mut self, paph: Byte, pin: Pin) {
         let toggle = (self.state.paph ^ paph) & pin.port_bit_mask() != 0;
         if toggle {
-            self.host.set_pin_pull_up_enabled(pin, paph & pin.port_bit_mask() != 0);
+            let timestamp = self.timestamp();
+            self.host.set_pin_pull_up_enabled(pin, paph & pin.port_bit_mask() != 0, timestamp);
         }
     }
 
@@ -217,42 +999,13 @@ impl<'a> HostBridge<'a> {
         self.on_change_pin_pull_up(paph, pins::PA0);
         self.state.paph = paph;
     }
-
-    fn on_read_pa(&self) {
-        // clear all input pins
-        let output_pins = self.state.pa.get() & self.state.pac;
-        let mut input_pins:  u8 = 0x00;
-
-        // Update pins state from host
-        if self.host.read_pin_digital(pins::PA7) {
-            input_pins |= pins::PA7.port_bit_mask();
-        };
-        if self.host.read_pin_digital(pins::PA6) {
-            input_pins |= pins::PA6.port_bit_mask();
-        };
-        if self.host.read_pin_digital(pins::PA5) {
-            input_pins |= pins::PA5.port_bit_mask();
-        };
-        if self.host.read_pin_digital(pins::PA4) {
-            input_pins |= pins::PA4.port_bit_mask();
-        };
-        if self.host.read_pin_digital(pins::PA3) {
-            input_pins |= pins::PA3.port_bit_mask();
-        };
-        if self.host.read_pin_digital(pins::PA0) {
-            input_pins |= pins::PA0.port_bit_mask();
-        };
-        // clear non-input pins
-        input_pins &= !self.state.pac;
-
-        self.state.pa.set(output_pins |  input_pins);
-    }
 }
 
 impl<'a> Bus for HostBridge<'a> {
     fn write_io(&mut self, addr: IoAddr, value: Byte) {
         use regs::*;
 
+        self.watch(Space::Io, Access::Write, addr & IO_ADDRESS_MASK, value);
         self.state.io[(addr & IO_ADDRESS_MASK) as usize] = value;
         match addr & IO_ADDRESS_MASK {
             IO_ADDR_FLAGS => {}
@@ -261,31 +1014,31 @@ impl<'a> Bus for HostBridge<'a> {
             IO_ADDR_CLKMD => self.on_change_clkmd(value),
             IO_ADDR_INTEN => {},
             IO_ADDR_INTRQ => {},
-            IO_ADDR_T16M => {},
+            IO_ADDR_T16M => self.state.timer16.set_mode(value),
             0x07 => {},
             0x08 => {},
-            IO_ADDR_TM2B => {},
+            IO_ADDR_TM2B => self.state.timer2.set_bound(value),
             IO_ADDR_EOSCR => {},
-            0x0B => {},
+            IO_ADDR_IHRCR => self.on_change_ihrcr(value),
             IO_ADDR_INTEGS => {},
             IO_ADDR_PADIER => {},
             0x0E => {},
             0x0F => {},
-            IO_ADDR_PA => self.on_change_pa(value),
+            IO_ADDR_PA => self.on_change_pa(),
             IO_ADDR_PAC => self.on_change_pac(value),
             IO_ADDR_PAPH => self.on_change_paph(value),
             0x13 => {},
             0x14 => {},
             0x15 => {},
             0x16 => {},
-            IO_ADDR_TM2S => {},
+            IO_ADDR_TM2S => self.state.timer2.set_scaler(value),
             0x18 => {},
             0x19 => {},
-            IO_ADDR_GPCC => {},
+            IO_ADDR_GPCC => self.on_change_gpcc(value),
             IO_ADDR_MISC => {},
-            IO_ADDR_TM2C => {},
-            IO_ADDR_TM2CT => {},
-            IO_ADDR_GPCS => {},
+            IO_ADDR_TM2C => self.on_change_tm2c(value),
+            IO_ADDR_TM2CT => self.state.timer2.set_counter(value),
+            IO_ADDR_GPCS => self.on_change_gpcs(value),
             0x1F => {},
             // Unreachable io addresses
             0x20 ..= 0xFF => unreachable!(),
@@ -293,36 +1046,72 @@ impl<'a> Bus for HostBridge<'a> {
     }
 
     fn read_io(&self, addr: u8) -> u8 {
-        if addr == regs::IO_ADDR_PA {
-            self.on_read_pa();
-        }
-
-        self.state.io[(addr & IO_ADDRESS_MASK) as usize]
+        let addr = addr & IO_ADDRESS_MASK;
+        let value = match addr {
+            regs::IO_ADDR_PA => self.read_pa(),
+            regs::IO_ADDR_TM2CT => self.state.timer2.counter(),
+            regs::IO_ADDR_GPCC => {
+                // Comparator result bit is read only
+                let gpcc = self.state.io[regs::IO_ADDR_GPCC as usize] & 0b10111111;
+                gpcc | ((self.state.comparator.output() as Byte) << 6)
+            }
+            _ => self.state.io[addr as usize],
+        };
+        self.watch(Space::Io, Access::Read, addr, value);
+        value
     }
 
     fn write_ram(&mut self, addr: RamAddr, value: Byte) {
+        self.watch(Space::Ram, Access::Write, addr & RAM_ADDRESS_MASK, value);
         self.state.ram[(addr & RAM_ADDRESS_MASK) as usize] = value;
     }
 
     fn read_ram(&self, addr: RamAddr) -> Byte {
-        self.state.ram[(addr & RAM_ADDRESS_MASK) as usize]
+        let value = self.state.ram[(addr & RAM_ADDRESS_MASK) as usize];
+        self.watch(Space::Ram, Access::Read, addr & RAM_ADDRESS_MASK, value);
+        value
     }
 
     fn read_rom(&self, addr: RomAddr) -> IrSlot {
         self.state.rom[(addr & ROM_ADDRESS_MASK) as usize]
     }
 
-    fn write_tim16(&mut self, value: u16) {}
+    fn write_tim16(&mut self, value: u16) {
+        self.state.timer16.set_counter(value);
+    }
 
-    fn read_tim16(&self) -> u16 { 0 }
+    fn read_tim16(&self) -> u16 {
+        self.state.timer16.counter()
+    }
 
     fn reset(&mut self) {
-        self.state.reset();
+        self.apply_reset(ResetCause::Software);
     }
 
-    fn stop_exe(&mut self) {}
+    fn stop_exe(&mut self) {
+        self.enter_power_down(PowerMode::StopExe);
+    }
 
-    fn stop_sys(&mut self) {}
+    fn stop_sys(&mut self) {
+        self.enter_power_down(PowerMode::StopSys);
+    }
+
+    fn wdt_reset(&mut self) {
+        self.state.watchdog.reset();
+    }
 
-    fn wdt_reset(&mut self) {}
+    // Internal accesses bypass debugger watchpoints, only accesses made by the program are
+    // reported
+
+    fn read_flags(&self) -> Byte {
+        self.state.io[regs::IO_ADDR_FLAGS as usize]
+    }
+
+    fn request_interrupt(&mut self, mask: Byte) {
+        self.state.io[regs::IO_ADDR_INTRQ as usize] |= mask;
+    }
+
+    fn pending_interrupts(&self) -> Byte {
+        self.state.io[regs::IO_ADDR_INTEN as usize] & self.state.io[regs::IO_ADDR_INTRQ as usize]
+    }
 }
@@ -0,0 +1,28 @@
+/// Converts system clock cycles to the pulse count of an another oscillator. Fractional part
+/// is carried between the calls, so no pulses are lost when frequencies are not multiples of
+/// each other
+pub(super) struct ClockAccumulator {
+    phase: u32,
+}
+
+impl ClockAccumulator {
+    pub fn new() -> Self {
+        Self { phase: 0 }
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = 0;
+    }
+
+    /// Returns count of `frequency` oscillator pulses which happened during one system
+    /// clock cycle
+    pub fn ticks(&mut self, frequency: u32, sys_frequency: u32) -> u32 {
+        if sys_frequency == 0 {
+            self.phase = 0;
+            return 0;
+        }
+        let total = self.phase as u64 + frequency as u64;
+        self.phase = (total % sys_frequency as u64) as u32;
+        (total / sys_frequency as u64) as u32
+    }
+}
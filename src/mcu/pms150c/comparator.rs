@@ -0,0 +1,128 @@
+//! Comparator emulation. Comparator is configured via GPCC register:
+//!
+//! [7]   => enable
+//! [6]   => result (read only)
+//! [5]   => result is sampled by Timer2 clock
+//! [4]   => inverse result polarity
+//! [3:1] => minus input (PA3, PA4, 1.20V bandgap, internal reference, PA6, PA7)
+//! [0]   => plus input (internal reference, PA4)
+//!
+//! GPCS register selects internal reference voltage from VDD resistor ladder ([5:4] range,
+//! [3:0] tap) and enables comparator output on PA0 ([7])
+
+use crate::{
+    isa::pdk13::Byte,
+    mcu::{host_adapter::Pin, pms150c::pins},
+};
+
+const BANDGAP_MILLIVOLTS: u32 = 1200;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(super) enum ComparatorInput {
+    Pin(Pin),
+    Bandgap,
+    InternalReference,
+    Reserved,
+}
+
+pub(super) struct Comparator {
+    control: Byte,
+    select: Byte,
+    output: bool,
+}
+
+impl Comparator {
+    pub fn new() -> Self {
+        Self {
+            control: 0,
+            select: 0,
+            output: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn set_control(&mut self, gpcc: Byte) {
+        self.control = gpcc;
+        if !self.enabled() {
+            self.output = false;
+        }
+    }
+
+    pub fn set_select(&mut self, gpcs: Byte) {
+        self.select = gpcs;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.control & 0x80 != 0
+    }
+
+    pub fn sampled_by_timer2(&self) -> bool {
+        self.control & 0x20 != 0
+    }
+
+    pub fn pa0_output_enabled(&self) -> bool {
+        self.select & 0x80 != 0
+    }
+
+    pub fn output(&self) -> bool {
+        self.output
+    }
+
+    pub fn minus_input(&self) -> ComparatorInput {
+        match (self.control >> 1) & 0x07 {
+            0b000 => ComparatorInput::Pin(pins::PA3),
+            0b001 => ComparatorInput::Pin(pins::PA4),
+            0b010 => ComparatorInput::Bandgap,
+            0b011 => ComparatorInput::InternalReference,
+            0b100 => ComparatorInput::Pin(pins::PA6),
+            0b101 => ComparatorInput::Pin(pins::PA7),
+            _ => ComparatorInput::Reserved,
+        }
+    }
+
+    pub fn plus_input(&self) -> ComparatorInput {
+        if self.control & 0x01 != 0 {
+            ComparatorInput::Pin(pins::PA4)
+        } else {
+            ComparatorInput::InternalReference
+        }
+    }
+
+    /// Returns internal reference voltage relative to VDD
+    pub fn internal_reference(&self) -> u16 {
+        // VDD resistor ladder: 16 taps with optional bottom and top resistors, which are
+        // selected by the range bits
+        let tap = (self.select & 0x0F) as u32 + 1;
+        let (bottom, total) = match (self.select >> 4) & 0x03 {
+            0b00 => (8, 32),
+            0b01 => (0, 24),
+            0b10 => (6, 27),
+            _ => (0, 21),
+        };
+        ((u16::MAX as u32 * (bottom + tap)) / total) as u16
+    }
+
+    /// Returns bandgap reference voltage relative to VDD
+    pub fn bandgap_reference(supply_millivolts: u32) -> u16 {
+        if supply_millivolts <= BANDGAP_MILLIVOLTS {
+            return u16::MAX;
+        }
+        ((u16::MAX as u32 * BANDGAP_MILLIVOLTS) / supply_millivolts) as u16
+    }
+
+    /// Returns comparison result of the input levels with applied polarity
+    pub fn compare(&self, plus: u16, minus: u16) -> bool {
+        let inverse = self.control & 0x10 != 0;
+        (plus > minus) ^ inverse
+    }
+
+    /// Updates comparator output. Returns true if output has been changed
+    pub fn update_output(&mut self, output: bool) -> bool {
+        let changed = self.output != output;
+        self.output = output;
+        changed
+    }
+}
@@ -0,0 +1,380 @@
+//! Debugger layer over `Pms150c`: PC breakpoints with hit counts, RAM/IO watchpoints and
+//! execution control which understands `call`/`ret` nesting via SP
+
+use super::{Emulator, Pms150c, IO_ADDRESS_MASK, RAM_ADDRESS_MASK, ROM_ADDRESS_MASK};
+use crate::{
+    isa::pdk13::{
+        regs::IO_ADDR_SP, Byte, IoAddr, IrOpcode, IrSlot, Pdk13Result, RamAddr, RomAddr, Word,
+    },
+    mcu::host_adapter::HostAdapter,
+};
+
+pub const MAX_BREAKPOINTS: usize = 16;
+pub const MAX_WATCHPOINTS: usize = 16;
+
+/// Address space of the watched access
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Space {
+    Ram,
+    Io,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Accesses which trigger the watchpoint
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum WatchKind {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl WatchKind {
+    fn matches(self, access: Access) -> bool {
+        match self {
+            WatchKind::Read => access == Access::Read,
+            WatchKind::Write => access == Access::Write,
+            WatchKind::ReadWrite => true,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Watchpoint {
+    pub space: Space,
+    pub address: Byte,
+    pub kind: WatchKind,
+}
+
+/// Watched access performed by the program. Accesses which are part of instruction semantics
+/// (flags update, stack push on `call` or interrupt) are reported as well, internal accesses of
+/// the core and peripherals (flags snapshot, interrupt request latching and pending checks)
+/// are not
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct WatchHit {
+    pub space: Space,
+    pub address: Byte,
+    pub access: Access,
+    /// Value which has been read or written
+    pub value: Byte,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BreakpointId(usize);
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct WatchpointId(usize);
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum StopReason {
+    /// Single step, step over or step out has been completed
+    Step,
+    Breakpoint(BreakpointId),
+    /// Execution is stopped after the instruction which made the watched access
+    Watchpoint(WatchpointId, WatchHit),
+    /// Cycle budget of the run has been exhausted
+    CycleLimit,
+}
+
+/// Watched addresses bitmap checked by the MCU on each RAM/IO access
+#[derive(Copy, Clone, Default)]
+pub(super) struct WatchMask {
+    ram_read: u64,
+    ram_write: u64,
+    io_read: u64,
+    io_write: u64,
+}
+
+impl WatchMask {
+    pub fn matches(&self, space: Space, access: Access, address: Byte) -> bool {
+        let bits = match (space, access) {
+            (Space::Ram, Access::Read) => self.ram_read,
+            (Space::Ram, Access::Write) => self.ram_write,
+            (Space::Io, Access::Read) => self.io_read,
+            (Space::Io, Access::Write) => self.io_write,
+        };
+        bits & (1 << (address & 63)) != 0
+    }
+
+    fn insert(&mut self, watchpoint: &Watchpoint) {
+        let (read, write) = match watchpoint.space {
+            Space::Ram => (&mut self.ram_read, &mut self.ram_write),
+            Space::Io => (&mut self.io_read, &mut self.io_write),
+        };
+        let bit = 1 << (watchpoint.address & 63);
+        if watchpoint.kind.matches(Access::Read) {
+            *read |= bit;
+        }
+        if watchpoint.kind.matches(Access::Write) {
+            *write |= bit;
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Breakpoint {
+    address: RomAddr,
+    hits: u32,
+    ignore_count: u32,
+}
+
+pub struct Debugger {
+    mcu: Pms150c,
+    breakpoints: [Option<Breakpoint>; MAX_BREAKPOINTS],
+    watchpoints: [Option<Watchpoint>; MAX_WATCHPOINTS],
+}
+
+impl Debugger {
+    pub fn new(mcu: Pms150c) -> Self {
+        Self {
+            mcu,
+            breakpoints: [None; MAX_BREAKPOINTS],
+            watchpoints: [None; MAX_WATCHPOINTS],
+        }
+    }
+
+    pub fn mcu(&self) -> &Pms150c {
+        &self.mcu
+    }
+
+    pub fn mcu_mut(&mut self) -> &mut Pms150c {
+        &mut self.mcu
+    }
+
+    pub fn into_inner(self) -> Pms150c {
+        self.mcu
+    }
+
+    pub fn pc(&self) -> RomAddr {
+        self.mcu.core.pc()
+    }
+
+    pub fn set_pc(&mut self, value: RomAddr) {
+        self.mcu.core.set_pc(value & ROM_ADDRESS_MASK);
+    }
+
+    pub fn acc(&self) -> Byte {
+        self.mcu.core.acc()
+    }
+
+    pub fn set_acc(&mut self, value: Byte) {
+        self.mcu.core.set_acc(value);
+    }
+
+    pub fn sp(&self) -> RamAddr {
+        self.mcu.state.io[IO_ADDR_SP as usize]
+    }
+
+    /// Reads RAM without triggering watchpoints
+    pub fn read_ram(&self, address: RamAddr) -> Byte {
+        self.mcu.state.ram[(address & RAM_ADDRESS_MASK) as usize]
+    }
+
+    pub fn write_ram(&mut self, address: RamAddr, value: Byte) {
+        self.mcu.state.ram[(address & RAM_ADDRESS_MASK) as usize] = value;
+    }
+
+    /// Reads IO register without triggering watchpoints and side effects
+    pub fn read_io(&self, address: IoAddr) -> Byte {
+        self.mcu.state.io[(address & IO_ADDRESS_MASK) as usize]
+    }
+
+    /// Writes IO register value as is, peripherals are not notified about the change
+    pub fn write_io(&mut self, address: IoAddr, value: Byte) {
+        self.mcu.state.io[(address & IO_ADDRESS_MASK) as usize] = value;
+    }
+
+    pub fn read_rom(&self, address: RomAddr) -> Word {
+        self.mcu.state.rom[(address & ROM_ADDRESS_MASK) as usize].original_word()
+    }
+
+    /// Replaces ROM word regardless of ROM programming mode
+    pub fn write_rom(&mut self, address: RomAddr, value: Word) {
+        self.mcu.state.rom[(address & ROM_ADDRESS_MASK) as usize] = IrSlot::from_instruction(value);
+    }
+
+    /// Adds breakpoint at the ROM address, returns `None` if all breakpoint slots are used
+    pub fn add_breakpoint(&mut self, address: RomAddr) -> Option<BreakpointId> {
+        let index = self.breakpoints.iter().position(Option::is_none)?;
+        self.breakpoints[index] = Some(Breakpoint {
+            address: address & ROM_ADDRESS_MASK,
+            hits: 0,
+            ignore_count: 0,
+        });
+        Some(BreakpointId(index))
+    }
+
+    pub fn remove_breakpoint(&mut self, id: BreakpointId) -> bool {
+        self.breakpoints[id.0].take().is_some()
+    }
+
+    /// Returns breakpoint set at the ROM address
+    pub fn find_breakpoint(&self, address: RomAddr) -> Option<BreakpointId> {
+        self.breakpoints
+            .iter()
+            .position(|slot| matches!(slot, Some(breakpoint) if breakpoint.address == address))
+            .map(BreakpointId)
+    }
+
+    /// Breakpoint stops execution only after it has been hit `count` times
+    pub fn set_ignore_count(&mut self, id: BreakpointId, count: u32) -> bool {
+        match &mut self.breakpoints[id.0] {
+            Some(breakpoint) => {
+                breakpoint.ignore_count = count;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns how many times execution has reached the breakpoint, ignored hits included
+    pub fn breakpoint_hits(&self, id: BreakpointId) -> Option<u32> {
+        self.breakpoints[id.0].map(|breakpoint| breakpoint.hits)
+    }
+
+    /// Adds watchpoint, returns `None` if all watchpoint slots are used
+    pub fn add_watchpoint(&mut self, mut watchpoint: Watchpoint) -> Option<WatchpointId> {
+        let index = self.watchpoints.iter().position(Option::is_none)?;
+        watchpoint.address &= match watchpoint.space {
+            Space::Ram => RAM_ADDRESS_MASK,
+            Space::Io => IO_ADDRESS_MASK,
+        };
+        self.watchpoints[index] = Some(watchpoint);
+        self.update_watch_mask();
+        Some(WatchpointId(index))
+    }
+
+    pub fn remove_watchpoint(&mut self, id: WatchpointId) -> bool {
+        let removed = self.watchpoints[id.0].take().is_some();
+        self.update_watch_mask();
+        removed
+    }
+
+    /// Executes single instruction. While MCU is sleeping or held in reset a single emulator
+    /// step is performed
+    pub fn step(&mut self, host: &mut dyn HostAdapter) -> Pdk13Result<StopReason> {
+        match self.run(host, 1, |_, _| true)? {
+            StopReason::CycleLimit => Ok(StopReason::Step),
+            reason => Ok(reason),
+        }
+    }
+
+    /// Runs until breakpoint or watchpoint is hit or `max_cycles` are elapsed. Breakpoint at
+    /// the current PC is not hit before at least one instruction is executed
+    pub fn resume(
+        &mut self,
+        host: &mut dyn HostAdapter,
+        max_cycles: u64,
+    ) -> Pdk13Result<StopReason> {
+        self.run(host, max_cycles, |_, _| false)
+    }
+
+    /// Executes single instruction, subroutine called by `call` is executed as a whole
+    pub fn step_over(
+        &mut self,
+        host: &mut dyn HostAdapter,
+        max_cycles: u64,
+    ) -> Pdk13Result<StopReason> {
+        let pc = self.pc();
+        if self.opcode_at(pc) != IrOpcode::Call {
+            return self.step(host);
+        }
+        let sp = self.sp();
+        let return_address = pc.wrapping_add(1) & ROM_ADDRESS_MASK;
+        self.run(host, max_cycles, |debugger, _| {
+            debugger.pc() == return_address && debugger.sp() == sp
+        })
+    }
+
+    /// Runs until the current subroutine or interrupt handler returns
+    pub fn step_out(
+        &mut self,
+        host: &mut dyn HostAdapter,
+        max_cycles: u64,
+    ) -> Pdk13Result<StopReason> {
+        let sp = self.sp();
+        self.run(host, max_cycles, |debugger, opcode| {
+            let returned = matches!(opcode, IrOpcode::Ret | IrOpcode::Reti | IrOpcode::Retk);
+            returned && debugger.sp() < sp
+        })
+    }
+
+    /// Steps emulator until `done` returns true for the executed instruction opcode,
+    /// breakpoint or watchpoint is hit, or cycle budget is exhausted. Steps with stopped
+    /// system clock are counted as a single cycle
+    fn run(
+        &mut self,
+        host: &mut dyn HostAdapter,
+        max_cycles: u64,
+        done: impl Fn(&Self, IrOpcode) -> bool,
+    ) -> Pdk13Result<StopReason> {
+        let mut elapsed = 0u64;
+        loop {
+            let pc = self.pc();
+            let opcode = self.opcode_at(pc);
+            let core_cycles = self.mcu.core.cycles();
+            self.mcu.state.watch_hit.set(None);
+            elapsed += self.mcu.step(host)?.max(1) as u64;
+
+            if let Some(hit) = self.mcu.state.watch_hit.take() {
+                if let Some(id) = self.find_watchpoint(&hit) {
+                    return Ok(StopReason::Watchpoint(id, hit));
+                }
+            }
+            let executed = self.mcu.core.cycles() != core_cycles;
+            if executed && done(self, opcode) {
+                return Ok(StopReason::Step);
+            }
+            if executed || self.pc() != pc {
+                if let Some(id) = self.hit_breakpoint() {
+                    return Ok(StopReason::Breakpoint(id));
+                }
+            }
+            if elapsed >= max_cycles {
+                return Ok(StopReason::CycleLimit);
+            }
+        }
+    }
+
+    fn opcode_at(&self, address: RomAddr) -> IrOpcode {
+        self.mcu.state.rom[(address & ROM_ADDRESS_MASK) as usize].ir_opcode()
+    }
+
+    /// Counts hit of the breakpoint at PC, returns its id if execution should be stopped
+    fn hit_breakpoint(&mut self) -> Option<BreakpointId> {
+        let id = self.find_breakpoint(self.pc())?;
+        let breakpoint = self.breakpoints[id.0].as_mut()?;
+        breakpoint.hits += 1;
+        if breakpoint.hits > breakpoint.ignore_count {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    fn find_watchpoint(&self, hit: &WatchHit) -> Option<WatchpointId> {
+        self.watchpoints
+            .iter()
+            .position(|slot| match slot {
+                Some(watchpoint) => {
+                    watchpoint.space == hit.space
+                        && watchpoint.address == hit.address
+                        && watchpoint.kind.matches(hit.access)
+                }
+                None => false,
+            })
+            .map(WatchpointId)
+    }
+
+    fn update_watch_mask(&mut self) {
+        let mut mask = WatchMask::default();
+        for watchpoint in self.watchpoints.iter().flatten() {
+            mask.insert(watchpoint);
+        }
+        self.mcu.state.watch_mask = mask;
+    }
+}
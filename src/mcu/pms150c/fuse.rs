@@ -0,0 +1,128 @@
+//! Fuse (code option) word, programmed into the last ROM word. Unprogrammed OTP bits read
+//! as 1, so blank fuse selects disabled security, 2.0V LVR, normal IO drive and fast boot-up
+
+use crate::isa::pdk13::Word;
+
+const FUSE_SECURITY_OFF: Word = 1 << 0;
+const FUSE_LVR_SHIFT: u32 = 2;
+const FUSE_LVR_MASK: Word = 0b111 << FUSE_LVR_SHIFT;
+const FUSE_IO_DRIVE_NORMAL: Word = 1 << 7;
+const FUSE_BOOT_UP_FAST: Word = 0b11 << 10;
+// Bits without known function are left unprogrammed
+const FUSE_RESERVED_BITS: Word =
+    0x1FFF & !(FUSE_SECURITY_OFF | FUSE_LVR_MASK | FUSE_IO_DRIVE_NORMAL | FUSE_BOOT_UP_FAST);
+
+/// Low voltage reset level, encoded in fuse bits [4:2]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LvrLevel {
+    V4_0,
+    V3_5,
+    V3_0,
+    V2_75,
+    V2_5,
+    V1_8,
+    V2_2,
+    V2_0,
+}
+
+impl LvrLevel {
+    const LEVELS: [LvrLevel; 8] = [
+        LvrLevel::V4_0,
+        LvrLevel::V3_5,
+        LvrLevel::V3_0,
+        LvrLevel::V2_75,
+        LvrLevel::V2_5,
+        LvrLevel::V1_8,
+        LvrLevel::V2_2,
+        LvrLevel::V2_0,
+    ];
+
+    pub fn millivolts(self) -> u32 {
+        match self {
+            LvrLevel::V4_0 => 4_000,
+            LvrLevel::V3_5 => 3_500,
+            LvrLevel::V3_0 => 3_000,
+            LvrLevel::V2_75 => 2_750,
+            LvrLevel::V2_5 => 2_500,
+            LvrLevel::V1_8 => 1_800,
+            LvrLevel::V2_2 => 2_200,
+            LvrLevel::V2_0 => 2_000,
+        }
+    }
+
+    fn bits(self) -> Word {
+        Self::LEVELS
+            .iter()
+            .position(|level| *level == self)
+            .unwrap_or(0) as Word
+    }
+}
+
+/// PA output drive strength
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum IoDrive {
+    Low,
+    Normal,
+}
+
+/// Delay between power-on and start of the code execution
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum BootUp {
+    Slow,
+    Fast,
+}
+
+/// Decoded fuse word. Only LVR level affects emulation, other options are decoded and kept
+/// for the host but ignored by the model
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct FuseOptions {
+    /// ROM readout protection, ignored since emulated ROM is always readable
+    pub security: bool,
+    pub lvr: LvrLevel,
+    /// Ignored since pin output is modelled as digital level without drive strength
+    pub io_drive: IoDrive,
+    /// Ignored, code execution starts right after reset regardless of boot-up time
+    pub boot_up: BootUp,
+}
+
+impl FuseOptions {
+    pub fn from_word(word: Word) -> Self {
+        let lvr = (word & FUSE_LVR_MASK) >> FUSE_LVR_SHIFT;
+        Self {
+            security: word & FUSE_SECURITY_OFF == 0,
+            lvr: LvrLevel::LEVELS[lvr as usize],
+            io_drive: if word & FUSE_IO_DRIVE_NORMAL != 0 {
+                IoDrive::Normal
+            } else {
+                IoDrive::Low
+            },
+            // Any of boot-up bits cleared selects slow boot-up
+            boot_up: if word & FUSE_BOOT_UP_FAST == FUSE_BOOT_UP_FAST {
+                BootUp::Fast
+            } else {
+                BootUp::Slow
+            },
+        }
+    }
+
+    pub fn to_word(self) -> Word {
+        let mut word = FUSE_RESERVED_BITS | self.lvr.bits() << FUSE_LVR_SHIFT;
+        if !self.security {
+            word |= FUSE_SECURITY_OFF;
+        }
+        if self.io_drive == IoDrive::Normal {
+            word |= FUSE_IO_DRIVE_NORMAL;
+        }
+        if self.boot_up == BootUp::Fast {
+            word |= FUSE_BOOT_UP_FAST;
+        }
+        word
+    }
+}
+
+impl Default for FuseOptions {
+    /// Options of the unprogrammed fuse word
+    fn default() -> Self {
+        Self::from_word(0x1FFF)
+    }
+}
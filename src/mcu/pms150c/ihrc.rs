@@ -0,0 +1,25 @@
+//! IHRC oscillator trimming. IHRCR shifts oscillator frequency by 0.2% per step around the
+//! center value; untrimmed oscillator deviates from nominal 16 MHz by simulated error
+
+use crate::isa::pdk13::Byte;
+
+pub(super) const IHRC_NOMINAL_FREQUENCY: u32 = 16_000_000; // 16 MHz
+/// IHRCR value which trims error-free oscillator to the nominal frequency
+pub(super) const IHRCR_CENTER: Byte = 0x80;
+
+const IHRCR_STEP_PPM: i64 = 2_000;
+const PPM: i64 = 1_000_000;
+
+/// Returns IHRC frequency for the given IHRCR value and oscillator error
+pub(super) fn ihrc_frequency(ihrcr: Byte, error_ppm: i32) -> u32 {
+    let untrimmed = IHRC_NOMINAL_FREQUENCY as i64 * (PPM + error_ppm as i64) / PPM;
+    let trim = (ihrcr as i64 - IHRCR_CENTER as i64) * IHRCR_STEP_PPM;
+    (untrimmed * (PPM + trim) / PPM).max(0) as u32
+}
+
+/// Returns IHRCR value which brings IHRC closest to the target frequency
+pub(super) fn calibrate(target: u32, error_ppm: i32) -> Byte {
+    (0..=Byte::MAX)
+        .min_by_key(|ihrcr| (ihrc_frequency(*ihrcr, error_ppm) as i64 - target as i64).abs())
+        .unwrap_or(IHRCR_CENTER)
+}
@@ -0,0 +1,86 @@
+//! Fixed capacity queue of pin level changes pushed by host. Events are kept in time order
+//! and applied by emulator on the cycle when their time comes
+
+use core::time::Duration;
+
+use crate::isa::pdk13::Byte;
+
+const INPUT_QUEUE_CAPACITY: usize = 32;
+
+#[derive(Copy, Clone)]
+pub(super) struct PinEvent {
+    pub time: Duration,
+    pub pin_mask: Byte,
+    pub level: bool,
+}
+
+impl PinEvent {
+    /// Applies event to the pin levels
+    pub fn apply(self, levels: Byte) -> Byte {
+        if self.level {
+            levels | self.pin_mask
+        } else {
+            levels & !self.pin_mask
+        }
+    }
+}
+
+pub(super) struct InputQueue {
+    events: [PinEvent; INPUT_QUEUE_CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl InputQueue {
+    pub fn new() -> Self {
+        Self {
+            events: [PinEvent {
+                time: Duration::from_nanos(0),
+                pin_mask: 0,
+                level: false,
+            }; INPUT_QUEUE_CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Pushes event to the queue. Events pushed out of order are delayed up to the time of
+    /// the last queued event. Returns false and drops the event if queue is full
+    pub fn push(&mut self, mut event: PinEvent) -> bool {
+        if self.len == INPUT_QUEUE_CAPACITY {
+            return false;
+        }
+        if let Some(last) = self.last() {
+            event.time = event.time.max(last.time);
+        }
+        let index = (self.head + self.len) % INPUT_QUEUE_CAPACITY;
+        self.events[index] = event;
+        self.len += 1;
+        true
+    }
+
+    /// Pops the oldest event if its time is not later than `time`
+    pub fn pop_due(&mut self, time: Duration) -> Option<PinEvent> {
+        if self.len == 0 || self.events[self.head].time > time {
+            return None;
+        }
+        self.pop()
+    }
+
+    fn pop(&mut self) -> Option<PinEvent> {
+        if self.len == 0 {
+            return None;
+        }
+        let event = self.events[self.head];
+        self.head = (self.head + 1) % INPUT_QUEUE_CAPACITY;
+        self.len -= 1;
+        Some(event)
+    }
+
+    fn last(&self) -> Option<PinEvent> {
+        if self.len == 0 {
+            return None;
+        }
+        Some(self.events[(self.head + self.len - 1) % INPUT_QUEUE_CAPACITY])
+    }
+}
@@ -0,0 +1,122 @@
+use super::{goto, movak, movioa, run, setup};
+use crate::{
+    isa::pdk13::{regs::*, Word},
+    mcu::{
+        host_adapter::{AnalogSignal, Pin},
+        pms150c::{pins, test::mock_host::MockHost, Pms150c},
+    },
+};
+
+// mov a, io
+const fn movaio(addr: u8) -> Word {
+    0x00A0 | addr as Word
+}
+
+fn set_analog(host: &mut MockHost, pin: Pin, level: u16) {
+    host.analog[pin.port_bit_mask().trailing_zeros() as usize] = AnalogSignal::from_u16(level);
+}
+
+fn setup_comparator(gpcs: u8, gpcc: u8) -> (Pms150c, MockHost) {
+    let (mut mcu, mut host) = setup(&[
+        movak(gpcs),
+        movioa(IO_ADDR_GPCS),
+        movak(gpcc),
+        movioa(IO_ADDR_GPCC),
+        goto(4),
+    ]);
+    run(&mut mcu, &mut host, 4);
+    (mcu, host)
+}
+
+fn comparator_requested(mcu: &Pms150c) -> bool {
+    mcu.state.io[IO_ADDR_INTRQ as usize] & INT_COMPARATOR_MASK != 0
+}
+
+#[test]
+fn gpcc_reports_comparison_result() {
+    let (mut mcu, mut host) = setup(&[
+        movak(0b0000_0111),
+        movioa(IO_ADDR_GPCS),
+        // PA4 compared to internal reference
+        movak(0b1000_0111),
+        movioa(IO_ADDR_GPCC),
+        movaio(IO_ADDR_GPCC),
+        goto(5),
+    ]);
+    set_analog(&mut host, pins::PA4, 0x9000);
+    run(&mut mcu, &mut host, 5);
+    assert_eq!(0b1100_0111, mcu.core.acc());
+    set_analog(&mut host, pins::PA4, 0x7000);
+    run(&mut mcu, &mut host, 1);
+    assert!(!mcu.state.comparator.output());
+}
+
+#[test]
+fn output_change_requests_interrupt_and_drives_pa0() {
+    let (mut mcu, mut host) = setup_comparator(0b1000_0111, 0b1000_0111);
+    assert!(!comparator_requested(&mcu));
+    assert!(!host.output(pins::PA0));
+    set_analog(&mut host, pins::PA4, 0xF000);
+    run(&mut mcu, &mut host, 1);
+    assert!(comparator_requested(&mcu));
+    assert!(host.output(pins::PA0));
+    set_analog(&mut host, pins::PA4, 0x1000);
+    run(&mut mcu, &mut host, 1);
+    assert!(!host.output(pins::PA0));
+}
+
+#[test]
+fn inverse_polarity_inverts_result() {
+    let (mut mcu, mut host) = setup_comparator(0b0000_0111, 0b1001_0111);
+    set_analog(&mut host, pins::PA4, 0xF000);
+    run(&mut mcu, &mut host, 1);
+    assert!(!mcu.state.comparator.output());
+    set_analog(&mut host, pins::PA4, 0x1000);
+    run(&mut mcu, &mut host, 1);
+    assert!(mcu.state.comparator.output());
+}
+
+#[test]
+fn bandgap_reference_depends_on_supply_voltage() {
+    // PA4 compared to 1.20V bandgap reference
+    let (mut mcu, mut host) = setup_comparator(0, 0b1000_0101);
+    // 1.35V on 3V supply
+    set_analog(&mut host, pins::PA4, 0x7333);
+    mcu.set_supply_voltage(3000);
+    run(&mut mcu, &mut host, 1);
+    assert!(mcu.state.comparator.output());
+    mcu.set_supply_voltage(2000);
+    run(&mut mcu, &mut host, 1);
+    assert!(!mcu.state.comparator.output());
+}
+
+#[test]
+fn internal_reference_follows_resistor_ladder() {
+    let (mut mcu, mut host) = setup_comparator(0, 0);
+    let mut check = |gpcs: u8, expected: u32| {
+        mcu.state.comparator.set_select(gpcs);
+        run(&mut mcu, &mut host, 1);
+        assert_eq!(expected as u16, mcu.state.comparator.internal_reference());
+    };
+    check(0b00_0000, 0xFFFF * 9 / 32);
+    check(0b00_1111, 0xFFFF * 24 / 32);
+    check(0b01_0000, 0xFFFF / 24);
+    check(0b01_1111, 0xFFFF * 16 / 24);
+    check(0b10_0011, 0xFFFF * 10 / 27);
+    check(0b11_0100, 0xFFFF * 5 / 21);
+}
+
+#[test]
+fn comparator_output_clocks_timer2() {
+    let (mut mcu, mut host) = setup_comparator(0b0000_0111, 0b1000_0111);
+    // Comparator clock source, period mode, no output
+    mcu.state.timer2.set_bound(0xFF);
+    mcu.state.timer2.set_control(0b0101_0000);
+    for _ in 0..3 {
+        set_analog(&mut host, pins::PA4, 0xF000);
+        run(&mut mcu, &mut host, 1);
+        set_analog(&mut host, pins::PA4, 0x1000);
+        run(&mut mcu, &mut host, 1);
+    }
+    assert_eq!(3, mcu.state.timer2.counter());
+}
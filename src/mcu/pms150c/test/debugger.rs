@@ -0,0 +1,275 @@
+use super::setup_asm;
+use crate::{
+    isa::pdk13::regs::{IO_ADDR_FLAGS, IO_ADDR_INTEN, IO_ADDR_INTRQ, IO_ADDR_PA},
+    mcu::pms150c::{
+        debugger::{Access, Debugger, Space, StopReason, WatchHit, WatchKind, Watchpoint},
+        Emulator,
+    },
+};
+
+const SUBROUTINES: &str = "
+        call sub
+        mov a, #0x11
+    loop:
+        goto loop
+    sub:
+        inc 0x10
+        call nested
+        ret
+    nested:
+        inc 0x11
+        ret
+";
+
+#[test]
+fn breakpoint_stops_execution() {
+    let (mcu, mut host) = setup_asm(
+        "
+        loop:
+            inc 0x10
+        target:
+            goto loop
+        ",
+    );
+    let mut debugger = Debugger::new(mcu);
+    let id = debugger.add_breakpoint(1).unwrap();
+    assert_eq!(Some(id), debugger.find_breakpoint(1));
+    assert_eq!(
+        StopReason::Breakpoint(id),
+        debugger.resume(&mut host, 100).unwrap()
+    );
+    assert_eq!(1, debugger.pc());
+    assert_eq!(1, debugger.read_ram(0x10));
+    // Breakpoint at the current PC does not stop resumed execution
+    assert_eq!(
+        StopReason::Breakpoint(id),
+        debugger.resume(&mut host, 100).unwrap()
+    );
+    assert_eq!(2, debugger.read_ram(0x10));
+    assert_eq!(Some(2), debugger.breakpoint_hits(id));
+
+    assert!(debugger.remove_breakpoint(id));
+    assert!(!debugger.remove_breakpoint(id));
+    assert_eq!(None, debugger.find_breakpoint(1));
+    assert_eq!(
+        StopReason::CycleLimit,
+        debugger.resume(&mut host, 100).unwrap()
+    );
+}
+
+#[test]
+fn breakpoint_ignore_count() {
+    let (mcu, mut host) = setup_asm(
+        "
+        loop:
+            inc 0x10
+            goto loop
+        ",
+    );
+    let mut debugger = Debugger::new(mcu);
+    let id = debugger.add_breakpoint(0).unwrap();
+    assert!(debugger.set_ignore_count(id, 2));
+    assert_eq!(
+        StopReason::Breakpoint(id),
+        debugger.resume(&mut host, 100).unwrap()
+    );
+    assert_eq!(Some(3), debugger.breakpoint_hits(id));
+    assert_eq!(3, debugger.read_ram(0x10));
+}
+
+#[test]
+fn breakpoint_slots_are_limited() {
+    let (mcu, _) = setup_asm("goto 0");
+    let mut debugger = Debugger::new(mcu);
+    for address in 0..16 {
+        assert!(debugger.add_breakpoint(address).is_some());
+    }
+    assert_eq!(None, debugger.add_breakpoint(0x20));
+}
+
+#[test]
+fn ram_watchpoints() {
+    let (mcu, mut host) = setup_asm(
+        "
+            mov a, #0x42
+            mov 0x10, a
+            mov a, 0x11
+            mov a, 0x10
+        loop:
+            goto loop
+        ",
+    );
+    let mut debugger = Debugger::new(mcu);
+    let write = debugger
+        .add_watchpoint(Watchpoint {
+            space: Space::Ram,
+            address: 0x10,
+            kind: WatchKind::Write,
+        })
+        .unwrap();
+    let read = debugger
+        .add_watchpoint(Watchpoint {
+            space: Space::Ram,
+            address: 0x10,
+            kind: WatchKind::Read,
+        })
+        .unwrap();
+    let hit = WatchHit {
+        space: Space::Ram,
+        address: 0x10,
+        access: Access::Write,
+        value: 0x42,
+    };
+    assert_eq!(
+        StopReason::Watchpoint(write, hit),
+        debugger.resume(&mut host, 100).unwrap()
+    );
+    assert_eq!(2, debugger.pc());
+    let hit = WatchHit {
+        access: Access::Read,
+        ..hit
+    };
+    assert_eq!(
+        StopReason::Watchpoint(read, hit),
+        debugger.resume(&mut host, 100).unwrap()
+    );
+    assert_eq!(4, debugger.pc());
+
+    assert!(debugger.remove_watchpoint(read));
+    assert!(debugger.remove_watchpoint(write));
+    debugger.mcu_mut().core.reset();
+    assert_eq!(
+        StopReason::CycleLimit,
+        debugger.resume(&mut host, 100).unwrap()
+    );
+}
+
+#[test]
+fn io_watchpoint() {
+    let (mcu, mut host) = setup_asm(
+        "
+            mov a, #0x08
+            mov pa, a
+        loop:
+            goto loop
+        ",
+    );
+    let mut debugger = Debugger::new(mcu);
+    let id = debugger
+        .add_watchpoint(Watchpoint {
+            space: Space::Io,
+            address: IO_ADDR_PA,
+            kind: WatchKind::ReadWrite,
+        })
+        .unwrap();
+    let hit = WatchHit {
+        space: Space::Io,
+        address: IO_ADDR_PA,
+        access: Access::Write,
+        value: 0x08,
+    };
+    assert_eq!(
+        StopReason::Watchpoint(id, hit),
+        debugger.resume(&mut host, 100).unwrap()
+    );
+    assert_eq!(0x08, debugger.read_io(IO_ADDR_PA));
+}
+
+#[test]
+fn internal_io_accesses_do_not_hit_watchpoints() {
+    let (mcu, mut host) = setup_asm(
+        "
+            mov a, #0x28
+            mov t16m, a
+        loop:
+            inc 0x10
+            goto loop
+        ",
+    );
+    let mut debugger = Debugger::new(mcu);
+    for (address, kind) in [
+        (IO_ADDR_INTRQ, WatchKind::ReadWrite),
+        (IO_ADDR_INTEN, WatchKind::ReadWrite),
+        (IO_ADDR_FLAGS, WatchKind::Read),
+    ]
+    .iter()
+    .copied()
+    {
+        debugger
+            .add_watchpoint(Watchpoint {
+                space: Space::Io,
+                address,
+                kind,
+            })
+            .unwrap();
+    }
+    // Timer is counting, core checks pending interrupts and snapshots flags on every step
+    assert_eq!(
+        StopReason::CycleLimit,
+        debugger.resume(&mut host, 100).unwrap()
+    );
+    assert_ne!(0, debugger.read_ram(0x10));
+}
+
+#[test]
+fn step_over_executes_subroutine() {
+    let (mcu, mut host) = setup_asm(SUBROUTINES);
+    let mut debugger = Debugger::new(mcu);
+    assert_eq!(
+        StopReason::Step,
+        debugger.step_over(&mut host, 100).unwrap()
+    );
+    assert_eq!(1, debugger.pc());
+    assert_eq!(0, debugger.sp());
+    assert_eq!(1, debugger.read_ram(0x10));
+    assert_eq!(1, debugger.read_ram(0x11));
+    // Instructions other than call are single stepped
+    assert_eq!(
+        StopReason::Step,
+        debugger.step_over(&mut host, 100).unwrap()
+    );
+    assert_eq!(2, debugger.pc());
+    assert_eq!(0x11, debugger.acc());
+}
+
+#[test]
+fn step_over_stops_on_breakpoint_inside_subroutine() {
+    let (mcu, mut host) = setup_asm(SUBROUTINES);
+    let mut debugger = Debugger::new(mcu);
+    let id = debugger.add_breakpoint(7).unwrap();
+    assert_eq!(
+        StopReason::Breakpoint(id),
+        debugger.step_over(&mut host, 100).unwrap()
+    );
+    assert_eq!(7, debugger.pc());
+}
+
+#[test]
+fn step_out_returns_from_subroutine() {
+    let (mcu, mut host) = setup_asm(SUBROUTINES);
+    let mut debugger = Debugger::new(mcu);
+    debugger.step(&mut host).unwrap();
+    debugger.step(&mut host).unwrap();
+    debugger.step(&mut host).unwrap();
+    assert_eq!(6, debugger.pc());
+    assert_eq!(4, debugger.sp());
+    assert_eq!(StopReason::Step, debugger.step_out(&mut host, 100).unwrap());
+    assert_eq!(5, debugger.pc());
+    assert_eq!(2, debugger.sp());
+    assert_eq!(StopReason::Step, debugger.step_out(&mut host, 100).unwrap());
+    assert_eq!(1, debugger.pc());
+    assert_eq!(0, debugger.sp());
+}
+
+#[test]
+fn step_is_finished_while_mcu_is_sleeping() {
+    let (mcu, mut host) = setup_asm("stopsys");
+    let mut debugger = Debugger::new(mcu);
+    assert_eq!(StopReason::Step, debugger.step(&mut host).unwrap());
+    assert!(debugger.mcu().is_sleeping());
+    assert_eq!(StopReason::Step, debugger.step(&mut host).unwrap());
+    assert_eq!(
+        StopReason::CycleLimit,
+        debugger.resume(&mut host, 10).unwrap()
+    );
+}
@@ -0,0 +1,29 @@
+use super::{movak, setup};
+use crate::{
+    isa::pdk13::{DecodeMode, Pdk13Error, Word},
+    mcu::pms150c::Emulator,
+};
+
+const ILLEGAL: Word = 0x003E;
+
+#[test]
+fn strict_mode_stops_on_illegal_instruction() {
+    let (mut mcu, mut host) = setup(&[movak(0x42), ILLEGAL]);
+    mcu.set_decode_mode(DecodeMode::Strict);
+    match mcu.run_cycles(&mut host, 10) {
+        Err(Pdk13Error::IllegalInstruction { pc, word }) => {
+            assert_eq!(1, pc);
+            assert_eq!(ILLEGAL, word);
+        }
+        _ => panic!("Illegal instruction should be reported"),
+    }
+    assert_eq!(1, mcu.cycles());
+    assert_eq!(0x42, mcu.core.acc());
+}
+
+#[test]
+fn lenient_mode_executes_illegal_instruction_as_nop() {
+    let (mut mcu, mut host) = setup(&[movak(0x42), ILLEGAL, movak(0x24)]);
+    assert_eq!(3, mcu.run_cycles(&mut host, 3).unwrap());
+    assert_eq!(0x24, mcu.core.acc());
+}
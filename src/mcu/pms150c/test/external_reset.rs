@@ -0,0 +1,62 @@
+use super::{goto, movak, movioa, run, set_input, setup};
+use crate::{
+    isa::pdk13::{regs::*, Word},
+    mcu::pms150c::{pins, test::mock_host::MockHost, Emulator, Pms150c, ResetCause},
+};
+
+// inc 0x10
+const INC_COUNTER: Word = 0x0900 | 0x10;
+
+fn setup_pa5_reset(clkmd: u8) -> (Pms150c, MockHost) {
+    let (mut mcu, mut host) = setup(&[movak(clkmd), movioa(IO_ADDR_CLKMD), INC_COUNTER, goto(2)]);
+    set_input(&mut mcu, pins::PA5, true);
+    run(&mut mcu, &mut host, 4);
+    (mcu, host)
+}
+
+#[test]
+fn pa5_low_level_is_ignored_when_reset_function_is_disabled() {
+    let (mut mcu, mut host) = setup_pa5_reset(0b1111_0110);
+    set_input(&mut mcu, pins::PA5, false);
+    run(&mut mcu, &mut host, 10);
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+    assert_ne!(0, mcu.core.pc());
+}
+
+#[test]
+fn pa5_low_level_holds_mcu_in_reset() {
+    let (mut mcu, mut host) = setup_pa5_reset(0b1111_0111);
+    let counter = mcu.state.ram[0x10];
+    set_input(&mut mcu, pins::PA5, false);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(ResetCause::ExternalPin, mcu.last_reset_cause());
+    run(&mut mcu, &mut host, 100);
+    assert_eq!(0, mcu.core.pc());
+    assert_eq!(counter, mcu.state.ram[0x10]);
+}
+
+#[test]
+fn held_reset_is_applied_once() {
+    let (mut mcu, mut host) = setup_pa5_reset(0b1111_0111);
+    set_input(&mut mcu, pins::PA5, false);
+    run(&mut mcu, &mut host, 1);
+    let callbacks = host.pin_callbacks;
+    mcu.state.io[IO_ADDR_INTEN as usize] = 0x01;
+    run(&mut mcu, &mut host, 100);
+    assert_eq!(callbacks, host.pin_callbacks);
+    assert_eq!(0x01, mcu.state.io[IO_ADDR_INTEN as usize]);
+    assert_eq!(0, mcu.core.pc());
+}
+
+#[test]
+fn pa5_high_level_releases_reset() {
+    let (mut mcu, mut host) = setup_pa5_reset(0b1111_0111);
+    set_input(&mut mcu, pins::PA5, false);
+    run(&mut mcu, &mut host, 10);
+    set_input(&mut mcu, pins::PA5, true);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(1, mcu.core.pc());
+    assert_eq!(ResetCause::ExternalPin, mcu.last_reset_cause());
+    // Reset function is kept enabled after external reset
+    assert_eq!(1, mcu.state.io[IO_ADDR_CLKMD as usize] & 0x01);
+}
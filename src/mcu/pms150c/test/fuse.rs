@@ -0,0 +1,102 @@
+use super::run;
+use crate::{
+    firmware::easypdk::EasyPdkImage,
+    isa::pdk13::asm::assemble,
+    mcu::pms150c::{
+        fuse::{BootUp, FuseOptions, IoDrive, LvrLevel},
+        test::mock_host::MockHost,
+        Emulator, Pms150c, ResetCause,
+    },
+};
+
+#[test]
+fn fuse_word_is_decoded() {
+    let options = FuseOptions::from_word(0x1FEB);
+    assert!(!options.security);
+    assert_eq!(LvrLevel::V3_0, options.lvr);
+    assert_eq!(IoDrive::Normal, options.io_drive);
+    assert_eq!(BootUp::Fast, options.boot_up);
+
+    let options = FuseOptions::from_word(0x0000);
+    assert!(options.security);
+    assert_eq!(LvrLevel::V4_0, options.lvr);
+    assert_eq!(IoDrive::Low, options.io_drive);
+    assert_eq!(BootUp::Slow, options.boot_up);
+}
+
+#[test]
+fn blank_fuse_selects_default_options() {
+    let options = FuseOptions::default();
+    assert!(!options.security);
+    assert_eq!(LvrLevel::V2_0, options.lvr);
+    assert_eq!(IoDrive::Normal, options.io_drive);
+    assert_eq!(BootUp::Fast, options.boot_up);
+    assert_eq!(0x1FFF, options.to_word());
+}
+
+#[test]
+fn fuse_word_round_trip() {
+    for word in 0..0x2000 {
+        let options = FuseOptions::from_word(word);
+        assert_eq!(options, FuseOptions::from_word(options.to_word()));
+    }
+    assert_eq!(0x1FEB, FuseOptions::from_word(0x1FEB).to_word());
+}
+
+#[test]
+fn lvr_level_voltages() {
+    let levels = [
+        (0b000, 4_000),
+        (0b001, 3_500),
+        (0b010, 3_000),
+        (0b011, 2_750),
+        (0b100, 2_500),
+        (0b101, 1_800),
+        (0b110, 2_200),
+        (0b111, 2_000),
+    ];
+    for (bits, millivolts) in levels.iter().copied() {
+        let options = FuseOptions::from_word(0x1FE3 | bits << 2);
+        assert_eq!(millivolts, options.lvr.millivolts());
+    }
+}
+
+#[test]
+fn easypdk_image_fuse_is_applied() {
+    // Fuse selects 3.0V LVR, IHRC calibration placeholder is followed by counter loop
+    let rom = assemble(
+        "
+        and a, #0x52
+        and a, #0x43
+        and a, #1
+        and a, #0x00
+        and a, #0x12
+        and a, #0x7A
+        and a, #0x00
+        and a, #0x88
+        and a, #0x13
+        and a, #0x0B
+    loop:
+        inc 0x10
+        goto loop
+        .org 0x3FF
+        .dw 0x1FEB
+        ",
+    )
+    .unwrap();
+    let image = EasyPdkImage::from_rom(rom).unwrap();
+    let mut mcu = Pms150c::new();
+    let mut host = MockHost::new();
+    assert_eq!(None, mcu.fuse_options());
+    mcu.load_easypdk_image(&image).unwrap();
+    assert_eq!(Some(FuseOptions::from_word(0x1FEB)), mcu.fuse_options());
+    assert_eq!(0x1FEB, mcu.state.rom[0x3FF].original_word());
+    mcu.init(&mut host);
+
+    run(&mut mcu, &mut host, 12);
+    assert!(mcu.state.ram[0x10] > 0);
+
+    mcu.set_supply_voltage(2_900);
+    run(&mut mcu, &mut host, 2);
+    assert_eq!(ResetCause::LowVoltage, mcu.last_reset_cause());
+}
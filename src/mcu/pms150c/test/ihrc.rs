@@ -0,0 +1,153 @@
+use super::{mock_host::MockHost, run, setup_asm};
+use crate::{
+    firmware::easypdk::EasyPdkImage,
+    isa::pdk13::{asm::assemble, regs::*},
+    mcu::pms150c::{Emulator, Pms150c, ResetCause, RomMode},
+};
+
+// Switches system clock to IHRC/2 and requests calibration to 8 MHz at 5V
+const CALIBRATED_PROGRAM: &str = "
+        mov a, #0x34
+        mov clkmd, a
+        and a, #0x52
+        and a, #0x43
+        and a, #1
+        and a, #0x00
+        and a, #0x12
+        and a, #0x7A
+        and a, #0x00
+        and a, #0x88
+        and a, #0x13
+        and a, #0x0B
+    loop:
+        goto loop
+";
+
+fn setup_calibrated(error_ppm: i32, target: Option<u32>) -> (Pms150c, MockHost) {
+    let image = EasyPdkImage::from_rom(assemble(CALIBRATED_PROGRAM).unwrap()).unwrap();
+    let mut mcu = Pms150c::new();
+    let mut host = MockHost::new();
+    mcu.set_ihrc_error_ppm(error_ppm);
+    mcu.set_calibration_target(target);
+    mcu.load_easypdk_image(&image).unwrap();
+    mcu.init(&mut host);
+    (mcu, host)
+}
+
+#[test]
+fn ihrcr_is_kept_across_reset() {
+    let (mut mcu, mut host) = setup_asm(
+        "
+            mov a, #0x90
+            mov ihrcr, a
+            reset
+        ",
+    );
+    assert_eq!(0x80, mcu.state.io[IO_ADDR_IHRCR as usize]);
+    run(&mut mcu, &mut host, 4);
+    assert_eq!(ResetCause::Software, mcu.last_reset_cause());
+    assert_eq!(0x90, mcu.state.io[IO_ADDR_IHRCR as usize]);
+    mcu.init(&mut host);
+    assert_eq!(0x80, mcu.state.io[IO_ADDR_IHRCR as usize]);
+}
+
+#[test]
+fn ihrcr_trims_system_clock() {
+    let (mut mcu, mut host) = setup_asm(
+        "
+            mov a, #0x34
+            mov clkmd, a
+            mov a, #0x85
+            mov ihrcr, a
+            mov a, #0x7B
+            mov ihrcr, a
+        ",
+    );
+    run(&mut mcu, &mut host, 2);
+    assert_eq!(8_000_000, mcu.get_frequency());
+    run(&mut mcu, &mut host, 2);
+    assert_eq!(8_080_000, mcu.get_frequency());
+    run(&mut mcu, &mut host, 2);
+    assert_eq!(7_920_000, mcu.get_frequency());
+}
+
+#[test]
+fn oscillator_error_shifts_untrimmed_frequency() {
+    let (mut mcu, mut host) = setup_asm(
+        "
+            mov a, #0x34
+            mov clkmd, a
+        ",
+    );
+    mcu.set_ihrc_error_ppm(-10_000);
+    mcu.init(&mut host);
+    run(&mut mcu, &mut host, 2);
+    assert_eq!(7_920_000, mcu.get_frequency());
+}
+
+#[test]
+fn calibration_placeholder_is_patched_with_tuned_value() {
+    let (mut mcu, mut host) = setup_calibrated(10_000, None);
+    run(&mut mcu, &mut host, 10);
+    // mov a, #0x7B; mov ihrcr, a
+    assert_eq!(0x177B, mcu.state.rom[2].original_word());
+    assert_eq!(0x008B, mcu.state.rom[3].original_word());
+    assert_eq!(0x0000, mcu.state.rom[4].original_word());
+    assert_eq!(0x7B, mcu.state.io[IO_ADDR_IHRCR as usize]);
+    assert_eq!(7_999_200, mcu.get_frequency());
+}
+
+#[test]
+fn calibration_target_could_be_overridden() {
+    let (mut mcu, mut host) = setup_calibrated(0, Some(8_400_000));
+    run(&mut mcu, &mut host, 10);
+    assert_eq!(0x99, mcu.state.io[IO_ADDR_IHRCR as usize]);
+    assert_eq!(8_400_000, mcu.get_frequency());
+}
+
+#[test]
+fn uncalibrated_image_keeps_placeholder() {
+    let image = EasyPdkImage::from_rom(assemble(CALIBRATED_PROGRAM).unwrap()).unwrap();
+    let mut mcu = Pms150c::new();
+    let mut host = MockHost::new();
+    mcu.load_firmware(image.code()).unwrap();
+    mcu.init(&mut host);
+    run(&mut mcu, &mut host, 14);
+    assert_eq!(0x1452, mcu.state.rom[2].original_word());
+    assert_eq!(0x80, mcu.state.io[IO_ADDR_IHRCR as usize]);
+}
+
+#[test]
+fn reloaded_firmware_drops_calibrations() {
+    let (mut mcu, mut host) = setup_calibrated(0, None);
+    // New firmware leaves old stub and placeholder words in ROM, they should not be patched
+    mcu.load_firmware(&assemble("mov a, #0x34\nmov clkmd, a").unwrap()).unwrap();
+    mcu.init(&mut host);
+    run(&mut mcu, &mut host, 14);
+    assert_eq!(0x17FF, mcu.state.rom[2].original_word());
+    assert_eq!(0x1401, mcu.state.rom[4].original_word());
+    assert_eq!(0xFF, mcu.state.io[IO_ADDR_IHRCR as usize]);
+}
+
+#[test]
+fn rom_erase_drops_calibrations() {
+    let (mut mcu, _) = setup_calibrated(0, None);
+    mcu.set_rom_mode(RomMode::Rewritable);
+    assert!(mcu.state.calibrations.iter().all(Option::is_none));
+}
+
+#[test]
+fn calibration_patch_is_programmed_to_otp_rom() {
+    let image = EasyPdkImage::from_rom(assemble(CALIBRATED_PROGRAM).unwrap()).unwrap();
+    let mut mcu = Pms150c::new();
+    let mut host = MockHost::new();
+    mcu.set_rom_mode(RomMode::Otp);
+    mcu.set_ihrc_error_ppm(10_000);
+    mcu.load_easypdk_image(&image).unwrap();
+    mcu.init(&mut host);
+    mcu.run_cycles(&mut host, 10).unwrap();
+    assert_eq!(0x177B, mcu.state.rom[2].original_word());
+    assert_eq!(0x008B, mcu.state.rom[3].original_word());
+    assert_eq!(0x0000, mcu.state.rom[4].original_word());
+    assert_eq!(0x7B, mcu.state.io[IO_ADDR_IHRCR as usize]);
+}
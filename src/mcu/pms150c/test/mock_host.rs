@@ -0,0 +1,71 @@
+use crate::mcu::host_adapter::{AnalogSignal, HostAdapter, Pin, Timestamp};
+
+pub struct MockHost {
+    pub outputs: u8,
+    pub output_enabled: u8,
+    pub pull_up_enabled: u8,
+    pub analog: [AnalogSignal; 8],
+    // Timestamps of the last output level change for each pin
+    pub output_timestamps: [Option<Timestamp>; 8],
+    // Count of digital output, direction and pull-up callbacks
+    pub pin_callbacks: usize,
+}
+
+impl MockHost {
+    pub fn new() -> Self {
+        Self {
+            outputs: 0,
+            output_enabled: 0,
+            pull_up_enabled: 0,
+            analog: [AnalogSignal::from_u16(0); 8],
+            output_timestamps: [None; 8],
+            pin_callbacks: 0,
+        }
+    }
+
+    pub fn output(&self, pin: Pin) -> bool {
+        self.outputs & pin.port_bit_mask() != 0
+    }
+
+    pub fn output_timestamp(&self, pin: Pin) -> Option<Timestamp> {
+        self.output_timestamps[pin_index(pin)]
+    }
+}
+
+fn pin_index(pin: Pin) -> usize {
+    pin.port_bit_mask().trailing_zeros() as usize
+}
+
+fn update_mask(mask: &mut u8, pin: Pin, value: bool) {
+    if value {
+        *mask |= pin.port_bit_mask();
+    } else {
+        *mask &= !pin.port_bit_mask();
+    }
+}
+
+impl HostAdapter for MockHost {
+    fn write_pin_digital(&mut self, pin: Pin, value: bool, timestamp: Timestamp) {
+        self.pin_callbacks += 1;
+        if self.output(pin) != value {
+            self.output_timestamps[pin_index(pin)] = Some(timestamp);
+        }
+        update_mask(&mut self.outputs, pin, value);
+    }
+
+    fn read_pin_analog(&self, pin: Pin, _timestamp: Timestamp) -> AnalogSignal {
+        self.analog[pin_index(pin)]
+    }
+
+    fn write_pin_analog(&mut self, _pin: Pin, _value: AnalogSignal, _timestamp: Timestamp) {}
+
+    fn set_pin_output_enabled(&mut self, pin: Pin, enabled: bool, _timestamp: Timestamp) {
+        self.pin_callbacks += 1;
+        update_mask(&mut self.output_enabled, pin, enabled);
+    }
+
+    fn set_pin_pull_up_enabled(&mut self, pin: Pin, enabled: bool, _timestamp: Timestamp) {
+        self.pin_callbacks += 1;
+        update_mask(&mut self.pull_up_enabled, pin, enabled);
+    }
+}
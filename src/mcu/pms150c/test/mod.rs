@@ -0,0 +1,75 @@
+mod mock_host;
+
+mod comparator;
+mod debugger;
+mod decode_mode;
+mod external_reset;
+mod fuse;
+mod ihrc;
+mod otp;
+mod pin_input;
+mod pin_interrupt;
+mod power_down;
+mod reset;
+mod timer16;
+mod timer2;
+mod timing;
+mod watchdog;
+
+use crate::{
+    isa::pdk13::{asm::assemble, Word},
+    mcu::{
+        host_adapter::Pin,
+        pms150c::{Emulator, Pms150c},
+    },
+};
+use self::mock_host::MockHost;
+
+// mov a, #k
+const fn movak(k: u8) -> Word {
+    0x1700 | k as Word
+}
+
+// mov io, a
+const fn movioa(addr: u8) -> Word {
+    0x0080 | addr as Word
+}
+
+// goto addr
+const fn goto(addr: Word) -> Word {
+    0x1800 | addr
+}
+
+fn setup(program: &[Word]) -> (Pms150c, MockHost) {
+    let mut mcu = Pms150c::new();
+    let mut host = MockHost::new();
+    for (address, word) in program.iter().copied().enumerate() {
+        mcu.write_rom(address, word).unwrap();
+    }
+    mcu.init(&mut host);
+    (mcu, host)
+}
+
+/// Assembles program and initializes emulator with it
+fn setup_asm(source: &str) -> (Pms150c, MockHost) {
+    let mut mcu = Pms150c::new();
+    let mut host = MockHost::new();
+    mcu.load_firmware(&assemble(source).unwrap()).unwrap();
+    mcu.init(&mut host);
+    (mcu, host)
+}
+
+/// Runs emulation for at least `cycles` system clock cycles. Steps with stopped system
+/// clock are counted as a single cycle
+fn run(mcu: &mut Pms150c, host: &mut MockHost, cycles: usize) {
+    let mut elapsed = 0;
+    while elapsed < cycles {
+        elapsed += mcu.step(host).unwrap().max(1) as usize;
+    }
+}
+
+/// Pushes pin input level change at the current simulated time
+fn set_input(mcu: &mut Pms150c, pin: Pin, value: bool) {
+    let time = mcu.elapsed();
+    mcu.set_pin_input(pin, value, time).unwrap();
+}
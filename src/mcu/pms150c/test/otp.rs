@@ -0,0 +1,105 @@
+use super::{mock_host::MockHost, run};
+use crate::{
+    isa::pdk13::{asm::assemble, Pdk13Error},
+    mcu::pms150c::{Emulator, Pms150c, RomMode},
+};
+
+fn otp_mcu() -> Pms150c {
+    let mut mcu = Pms150c::new();
+    mcu.set_rom_mode(RomMode::Otp);
+    mcu
+}
+
+#[test]
+fn blank_otp_rom_reads_all_ones() {
+    let mcu = otp_mcu();
+    assert_eq!(0x1FFF, mcu.state.rom[0x000].original_word());
+    assert_eq!(0x1FFF, mcu.state.rom[0x3FF].original_word());
+}
+
+#[test]
+fn blank_rewritable_rom_reads_all_ones() {
+    let mcu = Pms150c::new();
+    assert_eq!(0x1FFF, mcu.state.rom[0x000].original_word());
+    assert_eq!(0x1FFF, mcu.state.rom[0x3FF].original_word());
+}
+
+#[test]
+fn rewritable_mode_overwrites_words() {
+    let mut mcu = otp_mcu();
+    mcu.write_rom(0x010, 0x1755).unwrap();
+    mcu.set_rom_mode(RomMode::Rewritable);
+    assert_eq!(0x1FFF, mcu.state.rom[0x010].original_word());
+    mcu.write_rom(0x010, 0x1755).unwrap();
+    mcu.write_rom(0x010, 0x18AA).unwrap();
+    assert_eq!(0x18AA, mcu.state.rom[0x010].original_word());
+}
+
+#[test]
+fn otp_programming_clears_bits() {
+    let mut mcu = otp_mcu();
+    mcu.write_rom(0x010, 0x1755).unwrap();
+    mcu.write_rom(0x010, 0x1700).unwrap();
+    assert_eq!(0x1700, mcu.state.rom[0x010].original_word());
+    // Writing the same value again succeeds
+    mcu.write_rom(0x010, 0x1700).unwrap();
+}
+
+#[test]
+fn otp_bits_could_not_be_set() {
+    let mut mcu = otp_mcu();
+    mcu.write_rom(0x010, 0x1700).unwrap();
+    match mcu.write_rom(0x010, 0x1755) {
+        Err(Pdk13Error::RomVerifyFailed {
+            address,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(0x010, address);
+            assert_eq!(0x1755, expected);
+            assert_eq!(0x1700, actual);
+        }
+        _ => panic!("Verify error should be reported"),
+    }
+    assert_eq!(0x1700, mcu.state.rom[0x010].original_word());
+}
+
+#[test]
+fn otp_rolling_update() {
+    // Entry point jumps to the first firmware version; blank words are kept for the update
+    let v1 = assemble(
+        "
+            goto v1
+            .org 0x100
+        v1:
+            mov a, #0x11
+            goto v1 + 1
+        ",
+    )
+    .unwrap();
+    // Entry point is patched to nop, execution falls through to the second version
+    let v2 = assemble(
+        "
+            nop
+            mov a, #0x22
+            goto 2
+        ",
+    )
+    .unwrap();
+
+    let mut mcu = otp_mcu();
+    let mut host = MockHost::new();
+    mcu.load_firmware(&v1).unwrap();
+    mcu.init(&mut host);
+    run(&mut mcu, &mut host, 10);
+    assert_eq!(0x11, mcu.core.acc());
+
+    mcu.load_firmware(&v2).unwrap();
+    mcu.init(&mut host);
+    run(&mut mcu, &mut host, 10);
+    assert_eq!(0x22, mcu.core.acc());
+
+    // Second update could not reuse programmed words
+    let v3 = assemble("goto 0x200").unwrap();
+    assert!(mcu.load_firmware(&v3).is_err());
+}
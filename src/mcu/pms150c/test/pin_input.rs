@@ -0,0 +1,70 @@
+use super::{goto, movak, movioa, run, setup};
+use crate::{
+    isa::pdk13::{regs::*, Pdk13Error},
+    mcu::pms150c::{pins, test::mock_host::MockHost, Emulator, Pms150c},
+};
+use core::time::Duration;
+
+fn setup_rising_edge_interrupt() -> (Pms150c, MockHost) {
+    let (mut mcu, mut host) = setup(&[movak(0b01), movioa(IO_ADDR_INTEGS), goto(2)]);
+    run(&mut mcu, &mut host, 2);
+    (mcu, host)
+}
+
+fn pa0_requested(mcu: &Pms150c) -> bool {
+    mcu.state.io[IO_ADDR_INTRQ as usize] & INT_PA0_MASK != 0
+}
+
+#[test]
+fn queued_change_is_applied_at_its_time() {
+    let (mut mcu, mut host) = setup_rising_edge_interrupt();
+    // ILRC system clock cycle is ~16.13us, change is due on the 4th cycle from now
+    let time = mcu.elapsed() + Duration::from_micros(64);
+    mcu.set_pin_input(pins::PA0, true, time).unwrap();
+    run(&mut mcu, &mut host, 4);
+    assert!(!pa0_requested(&mcu));
+    assert_eq!(0, mcu.state.input_pins);
+    run(&mut mcu, &mut host, 1);
+    assert!(pa0_requested(&mcu));
+    assert_eq!(pins::PA0.port_bit_mask(), mcu.state.input_pins);
+}
+
+#[test]
+fn pulse_shorter_than_cycle_is_latched() {
+    let (mut mcu, mut host) = setup_rising_edge_interrupt();
+    let time = mcu.elapsed();
+    mcu.set_pin_input(pins::PA0, true, time).unwrap();
+    mcu.set_pin_input(pins::PA0, false, time + Duration::from_micros(1)).unwrap();
+    run(&mut mcu, &mut host, 2);
+    assert!(pa0_requested(&mcu));
+    assert_eq!(0, mcu.state.input_pins);
+}
+
+#[test]
+fn changes_in_the_past_are_applied_on_the_next_cycle() {
+    let (mut mcu, mut host) = setup_rising_edge_interrupt();
+    mcu.set_pin_input(pins::PA0, true, Duration::from_nanos(0)).unwrap();
+    run(&mut mcu, &mut host, 1);
+    assert!(pa0_requested(&mcu));
+}
+
+#[test]
+fn queue_overflow_is_reported() {
+    let (mut mcu, mut host) = setup_rising_edge_interrupt();
+    let time = mcu.elapsed() + Duration::from_micros(64);
+    for i in 0..32 {
+        mcu.set_pin_input(pins::PA0, i % 2 == 0, time).unwrap();
+    }
+    match mcu.set_pin_input(pins::PA0, true, time) {
+        Err(Pdk13Error::InputQueueFull(dropped)) => assert_eq!(time, dropped),
+        _ => panic!("Change should be dropped when queue is full"),
+    }
+    // Queued changes are kept until their time
+    run(&mut mcu, &mut host, 4);
+    assert!(!pa0_requested(&mcu));
+    assert_eq!(0, mcu.state.input_pins);
+    run(&mut mcu, &mut host, 1);
+    assert!(pa0_requested(&mcu));
+    assert_eq!(0, mcu.state.input_pins);
+    mcu.set_pin_input(pins::PA0, true, time).unwrap();
+}
@@ -0,0 +1,109 @@
+use super::{goto, movak, movioa, run, set_input, setup};
+use crate::{
+    isa::pdk13::{regs::*, Word},
+    mcu::pms150c::{pins, test::mock_host::MockHost, Pms150c},
+};
+
+const ENGINT: Word = 0x0038;
+const RETI: Word = 0x003B;
+const NOP: Word = 0x0000;
+// inc 0x10
+const INC_COUNTER: Word = 0x0900 | 0x10;
+// mov a, pa
+const MOVA_PA: Word = 0x00A0 | IO_ADDR_PA as Word;
+
+fn setup_integs(integs: u8) -> (Pms150c, MockHost) {
+    let (mut mcu, mut host) = setup(&[movak(integs), movioa(IO_ADDR_INTEGS), goto(2)]);
+    run(&mut mcu, &mut host, 2);
+    (mcu, host)
+}
+
+fn requested(mcu: &Pms150c, mask: u8) -> bool {
+    mcu.state.io[IO_ADDR_INTRQ as usize] & mask != 0
+}
+
+#[test]
+fn both_edges_are_latched_by_default() {
+    let (mut mcu, mut host) = setup_integs(0);
+    set_input(&mut mcu, pins::PA0, true);
+    run(&mut mcu, &mut host, 1);
+    assert!(requested(&mcu, INT_PA0_MASK));
+    mcu.state.io[IO_ADDR_INTRQ as usize] = 0;
+    set_input(&mut mcu, pins::PA0, false);
+    run(&mut mcu, &mut host, 1);
+    assert!(requested(&mcu, INT_PA0_MASK));
+    assert!(!requested(&mcu, INT_PA4_MASK));
+}
+
+#[test]
+fn pa0_rising_edge_selection() {
+    let (mut mcu, mut host) = setup_integs(0b01);
+    set_input(&mut mcu, pins::PA0, true);
+    run(&mut mcu, &mut host, 1);
+    assert!(requested(&mcu, INT_PA0_MASK));
+    mcu.state.io[IO_ADDR_INTRQ as usize] = 0;
+    set_input(&mut mcu, pins::PA0, false);
+    run(&mut mcu, &mut host, 1);
+    assert!(!requested(&mcu, INT_PA0_MASK));
+}
+
+#[test]
+fn pa4_falling_edge_selection() {
+    let (mut mcu, mut host) = setup_integs(0b10_00);
+    set_input(&mut mcu, pins::PA4, true);
+    run(&mut mcu, &mut host, 1);
+    assert!(!requested(&mcu, INT_PA4_MASK));
+    set_input(&mut mcu, pins::PA4, false);
+    run(&mut mcu, &mut host, 1);
+    assert!(requested(&mcu, INT_PA4_MASK));
+    assert!(!requested(&mcu, INT_PA0_MASK));
+}
+
+#[test]
+fn pins_disabled_in_padier_do_not_request_interrupts() {
+    let (mut mcu, mut host) = setup(&[movak(0), movioa(IO_ADDR_PADIER), goto(2)]);
+    run(&mut mcu, &mut host, 2);
+    set_input(&mut mcu, pins::PA0, true);
+    run(&mut mcu, &mut host, 1);
+    assert!(!requested(&mcu, INT_PA0_MASK));
+}
+
+#[test]
+fn pin_edges_dispatch_interrupt_without_pa_reads() {
+    let mut program = [NOP; 0x15];
+    program[0] = movak(INT_PA0_MASK);
+    program[1] = movioa(IO_ADDR_INTEN);
+    program[2] = ENGINT;
+    program[3] = goto(3);
+    program[0x10] = movak(0);
+    program[0x11] = movioa(IO_ADDR_INTRQ);
+    program[0x12] = INC_COUNTER;
+    program[0x13] = RETI;
+    let (mut mcu, mut host) = setup(&program);
+    run(&mut mcu, &mut host, 5);
+    set_input(&mut mcu, pins::PA0, true);
+    run(&mut mcu, &mut host, 20);
+    assert_eq!(1, mcu.state.ram[0x10]);
+    set_input(&mut mcu, pins::PA0, false);
+    run(&mut mcu, &mut host, 20);
+    assert_eq!(2, mcu.state.ram[0x10]);
+    assert!(!requested(&mcu, INT_PA0_MASK));
+}
+
+#[test]
+fn pa_read_returns_sampled_input_levels() {
+    let (mut mcu, mut host) = setup(&[
+        movak(pins::PA3.port_bit_mask() | pins::PA6.port_bit_mask()),
+        movioa(IO_ADDR_PA),
+        movak(pins::PA3.port_bit_mask()),
+        movioa(IO_ADDR_PAC),
+        MOVA_PA,
+    ]);
+    set_input(&mut mcu, pins::PA4, true);
+    set_input(&mut mcu, pins::PA6, false);
+    run(&mut mcu, &mut host, 5);
+    assert_eq!(
+        pins::PA3.port_bit_mask() | pins::PA4.port_bit_mask(),
+        mcu.core.acc()
+    );
+}
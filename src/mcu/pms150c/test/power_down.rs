@@ -0,0 +1,87 @@
+use super::{goto, movak, movioa, run, set_input, setup};
+use crate::{
+    isa::pdk13::{regs::*, Word},
+    mcu::pms150c::{pins, Emulator, ResetCause},
+};
+
+const STOPSYS: Word = 0x0036;
+const STOPEXE: Word = 0x0037;
+// inc 0x10
+const INC_COUNTER: Word = 0x0900 | 0x10;
+
+#[test]
+fn stopsys_halts_core() {
+    let (mut mcu, mut host) = setup(&[STOPSYS, INC_COUNTER, goto(1)]);
+    assert!(!mcu.is_sleeping());
+    run(&mut mcu, &mut host, 1);
+    assert!(mcu.is_sleeping());
+    run(&mut mcu, &mut host, 100);
+    assert_eq!(0, mcu.state.ram[0x10]);
+    assert_eq!(1, mcu.core.pc());
+}
+
+#[test]
+fn pin_change_wakes_up_from_stopsys() {
+    let (mut mcu, mut host) = setup(&[STOPSYS, INC_COUNTER, goto(2)]);
+    run(&mut mcu, &mut host, 10);
+    set_input(&mut mcu, pins::PA0, true);
+    run(&mut mcu, &mut host, 1);
+    assert!(!mcu.is_sleeping());
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(1, mcu.state.ram[0x10]);
+}
+
+#[test]
+fn only_padier_enabled_pins_wake_up_mcu() {
+    let (mut mcu, mut host) = setup(&[
+        movak(pins::PA3.port_bit_mask()),
+        movioa(IO_ADDR_PADIER),
+        STOPSYS,
+        INC_COUNTER,
+        goto(4),
+    ]);
+    run(&mut mcu, &mut host, 3);
+    set_input(&mut mcu, pins::PA4, true);
+    run(&mut mcu, &mut host, 10);
+    assert!(mcu.is_sleeping());
+    set_input(&mut mcu, pins::PA3, true);
+    run(&mut mcu, &mut host, 1);
+    assert!(!mcu.is_sleeping());
+}
+
+#[test]
+fn stopsys_stops_timers_and_watchdog() {
+    let (mut mcu, mut host) = setup(&[movak(0b0010_0000), movioa(IO_ADDR_T16M), STOPSYS]);
+    run(&mut mcu, &mut host, 3);
+    let counter = mcu.state.timer16.counter();
+    run(&mut mcu, &mut host, 64 * 1024);
+    assert_eq!(counter, mcu.state.timer16.counter());
+    assert!(mcu.is_sleeping());
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+}
+
+#[test]
+fn timer_event_wakes_up_from_stopexe() {
+    let (mut mcu, mut host) = setup(&[
+        movak(0b0010_0000),
+        movioa(IO_ADDR_T16M),
+        STOPEXE,
+        INC_COUNTER,
+        goto(4),
+    ]);
+    run(&mut mcu, &mut host, 3);
+    assert!(mcu.is_sleeping());
+    run(&mut mcu, &mut host, 200);
+    assert!(mcu.is_sleeping());
+    run(&mut mcu, &mut host, 100);
+    assert!(!mcu.is_sleeping());
+    assert_eq!(1, mcu.state.ram[0x10]);
+}
+
+#[test]
+fn watchdog_resets_mcu_in_stopexe() {
+    let (mut mcu, mut host) = setup(&[STOPEXE]);
+    run(&mut mcu, &mut host, 8 * 1024);
+    assert_eq!(ResetCause::Watchdog, mcu.last_reset_cause());
+    assert!(!mcu.is_sleeping());
+}
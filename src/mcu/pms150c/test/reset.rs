@@ -0,0 +1,71 @@
+use super::{goto, movak, movioa, run, setup};
+use crate::{
+    isa::pdk13::{regs::*, Word},
+    mcu::pms150c::{Emulator, ResetCause},
+};
+
+const RESET: Word = 0x0035;
+// inc 0x10
+const INC_COUNTER: Word = 0x0900 | 0x10;
+
+#[test]
+fn init_resets_core() {
+    let (mut mcu, mut host) = setup(&[movak(0x42), goto(1)]);
+    run(&mut mcu, &mut host, 3);
+    assert_eq!(0x42, mcu.core.acc());
+    mcu.init(&mut host);
+    assert_eq!(0, mcu.core.pc());
+    assert_eq!(0, mcu.core.acc());
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+}
+
+#[test]
+fn power_on_reset_clears_ram() {
+    let (mut mcu, mut host) = setup(&[goto(0)]);
+    mcu.state.ram[0x20] = 0x55;
+    mcu.init(&mut host);
+    assert_eq!(0, mcu.state.ram[0x20]);
+}
+
+#[test]
+fn software_reset_applies_register_reset_values() {
+    let (mut mcu, mut host) = setup(&[
+        INC_COUNTER,
+        movak(0xFF),
+        movioa(IO_ADDR_PAC),
+        movioa(IO_ADDR_PAPH),
+        movioa(IO_ADDR_PA),
+        movioa(IO_ADDR_INTEN),
+        movioa(IO_ADDR_SP),
+        movioa(IO_ADDR_PADIER),
+        RESET,
+    ]);
+    run(&mut mcu, &mut host, 8);
+    assert_eq!(0b1111_1001, host.output_enabled);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(ResetCause::Software, mcu.last_reset_cause());
+    assert_eq!(0, mcu.core.pc());
+    assert_eq!(0, mcu.state.io[IO_ADDR_INTEN as usize]);
+    assert_eq!(0, mcu.state.io[IO_ADDR_SP as usize]);
+    assert_eq!(0, mcu.state.io[IO_ADDR_PA as usize]);
+    assert_eq!(0b1111_1001, mcu.state.io[IO_ADDR_PADIER as usize]);
+    assert_eq!(0b1111_0110, mcu.state.io[IO_ADDR_CLKMD as usize]);
+    assert_eq!(0, host.output_enabled);
+    assert_eq!(0, host.pull_up_enabled);
+    assert_eq!(0, host.outputs);
+    // RAM is kept after software reset
+    assert_eq!(1, mcu.state.ram[0x10]);
+}
+
+#[test]
+fn low_supply_voltage_holds_mcu_in_reset() {
+    let (mut mcu, mut host) = setup(&[INC_COUNTER, goto(0)]);
+    mcu.set_low_voltage_reset_level(2_500);
+    mcu.set_supply_voltage(2_200);
+    run(&mut mcu, &mut host, 10);
+    assert_eq!(ResetCause::LowVoltage, mcu.last_reset_cause());
+    assert_eq!(0, mcu.state.ram[0x10]);
+    mcu.set_supply_voltage(3_300);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(1, mcu.state.ram[0x10]);
+}
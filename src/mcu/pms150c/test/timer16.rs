@@ -0,0 +1,105 @@
+use super::{goto, movak, movioa, run, set_input, setup};
+use crate::{
+    isa::pdk13::regs::*,
+    mcu::pms150c::{pins, timer16::Timer16},
+};
+
+#[test]
+fn timer_is_disabled_after_reset() {
+    let (mut mcu, mut host) = setup(&[goto(0)]);
+    run(&mut mcu, &mut host, 100);
+    assert_eq!(0, mcu.state.timer16.counter());
+}
+
+#[test]
+fn timer_counts_system_clock_with_prescaler() {
+    let (mut mcu, mut host) = setup(&[movak(0b0010_1000), movioa(IO_ADDR_T16M), goto(2)]);
+    // First pulse is counted right after T16M write
+    run(&mut mcu, &mut host, 2 + 39);
+    assert_eq!(10, mcu.state.timer16.counter());
+}
+
+#[test]
+fn timer_raises_interrupt_on_selected_bit_rising_edge() {
+    let (mut mcu, mut host) = setup(&[movak(0b0010_0000), movioa(IO_ADDR_T16M), goto(2)]);
+    run(&mut mcu, &mut host, 256);
+    assert_eq!(255, mcu.state.timer16.counter());
+    assert_eq!(0, mcu.state.io[IO_ADDR_INTRQ as usize] & INT_T16_MASK);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(INT_T16_MASK, mcu.state.io[IO_ADDR_INTRQ as usize] & INT_T16_MASK);
+}
+
+#[test]
+fn timer_uses_integs_edge_selection() {
+    let mut timer = Timer16::new();
+    timer.set_mode(0b0010_0001);
+    // Bit 9 rising edge happens at 512, falling edge at 1024
+    assert!(!timer.clock(511, true));
+    assert!(!timer.clock(1, true));
+    assert!(!timer.clock(511, true));
+    assert!(timer.clock(1, true));
+}
+
+#[test]
+fn timer_detects_edge_in_a_single_large_pulse_batch() {
+    let mut timer = Timer16::new();
+    timer.set_mode(0b0010_0000);
+    timer.set_counter(0xFFF0);
+    assert!(timer.clock(0x120, false));
+    assert_eq!(0x0110, timer.counter());
+}
+
+#[test]
+fn timer_counter_wraps_around() {
+    let mut timer = Timer16::new();
+    timer.set_mode(0b0011_1111);
+    timer.set_counter(0xFFFF);
+    assert!(timer.clock(64, true));
+    assert_eq!(0x0000, timer.counter());
+}
+
+#[test]
+fn stt16_and_ldt16_access_timer_counter() {
+    let (mut mcu, mut host) = setup(&[
+        movak(0x34),
+        0x05C0 | 0x10, // mov 0x10, a
+        movak(0x12),
+        0x05C0 | 0x11, // mov 0x11, a
+        0x00C0 | 0x10, // stt16 0x10
+        0x00C1 | 0x12, // ldt16 0x12
+    ]);
+    run(&mut mcu, &mut host, 5);
+    assert_eq!(0x1234, mcu.state.timer16.counter());
+    mcu.state.timer16.set_counter(0xBEEF);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(0xEF, mcu.state.ram[0x12]);
+    assert_eq!(0xBE, mcu.state.ram[0x13]);
+}
+
+#[test]
+fn timer_counts_pa0_falling_edges() {
+    let (mut mcu, mut host) = setup(&[movak(0b1110_0000), movioa(IO_ADDR_T16M), goto(2)]);
+    run(&mut mcu, &mut host, 2);
+    for _ in 0..5 {
+        set_input(&mut mcu, pins::PA0, true);
+        run(&mut mcu, &mut host, 3);
+        set_input(&mut mcu, pins::PA0, false);
+        run(&mut mcu, &mut host, 3);
+    }
+    assert_eq!(5, mcu.state.timer16.counter());
+}
+
+#[test]
+fn timer_counts_ilrc_pulses_with_ihrc_system_clock() {
+    let (mut mcu, mut host) = setup(&[
+        // IHRC/4 system clock, both oscillators enabled
+        movak(0b0001_0100),
+        movioa(IO_ADDR_CLKMD),
+        movak(0b1100_0000),
+        movioa(IO_ADDR_T16M),
+        goto(4),
+    ]);
+    run(&mut mcu, &mut host, 4 + 4_000_000);
+    // One second of 4 MHz system clock is 62000 ILRC pulses
+    assert_eq!(62_000, mcu.state.timer16.counter());
+}
@@ -0,0 +1,111 @@
+use super::{goto, movak, movioa, run, setup};
+use crate::{
+    isa::pdk13::{regs::*, Word},
+    mcu::{
+        host_adapter::Pin,
+        pms150c::{pins, test::mock_host::MockHost, Emulator, Pms150c},
+    },
+};
+
+const CONFIGURATION_STEPS: usize = 6;
+
+fn setup_timer2(tm2b: u8, tm2s: u8, tm2c: u8) -> (Pms150c, MockHost) {
+    let program: [Word; 7] = [
+        movak(tm2b),
+        movioa(IO_ADDR_TM2B),
+        movak(tm2s),
+        movioa(IO_ADDR_TM2S),
+        movak(tm2c),
+        movioa(IO_ADDR_TM2C),
+        goto(6),
+    ];
+    let (mut mcu, mut host) = setup(&program);
+    run(&mut mcu, &mut host, CONFIGURATION_STEPS);
+    (mcu, host)
+}
+
+fn count_high_levels(mcu: &mut Pms150c, host: &mut MockHost, pin: Pin, cycles: usize) -> usize {
+    let mut high = 0;
+    let mut elapsed = 0;
+    while elapsed < cycles {
+        let step_cycles = mcu.step(host).unwrap() as usize;
+        elapsed += step_cycles;
+        high += host.output(pin) as usize * step_cycles;
+    }
+    high
+}
+
+fn tm2_requested(mcu: &Pms150c) -> bool {
+    mcu.state.io[IO_ADDR_INTRQ as usize] & INT_TM2_MASK != 0
+}
+
+#[test]
+fn period_mode_toggles_output_on_bound_match() {
+    // SYSCLK, PA3 output, period mode
+    let (mut mcu, mut host) = setup_timer2(3, 0x00, 0b0001_1000);
+    // Counter is incremented once during configuration, bound is matched in 3 cycles
+    assert!(!host.output(pins::PA3));
+    run(&mut mcu, &mut host, 2);
+    assert!(!host.output(pins::PA3));
+    assert!(!tm2_requested(&mcu));
+    run(&mut mcu, &mut host, 2);
+    assert!(host.output(pins::PA3));
+    assert!(tm2_requested(&mcu));
+    // Output is toggled each 4 cycles
+    assert_eq!(40, count_high_levels(&mut mcu, &mut host, pins::PA3, 80));
+}
+
+#[test]
+fn pwm_8bit_mode_generates_duty_cycle() {
+    // SYSCLK, PA4 output, PWM mode
+    let (mut mcu, mut host) = setup_timer2(63, 0x00, 0b0001_1110);
+    assert_eq!(256, count_high_levels(&mut mcu, &mut host, pins::PA4, 256 * 4));
+    assert!(tm2_requested(&mcu));
+}
+
+#[test]
+fn pwm_6bit_mode_generates_duty_cycle() {
+    // SYSCLK, PA4 output, PWM mode, 6 bit resolution
+    let (mut mcu, mut host) = setup_timer2(15, 0x80, 0b0001_1110);
+    assert_eq!(64, count_high_levels(&mut mcu, &mut host, pins::PA4, 64 * 4));
+}
+
+#[test]
+fn pwm_output_can_be_inverted() {
+    // SYSCLK, PA3 output, PWM mode, inverted
+    let (mut mcu, mut host) = setup_timer2(63, 0x00, 0b0001_1011);
+    assert_eq!(192 * 4, count_high_levels(&mut mcu, &mut host, pins::PA3, 256 * 4));
+    assert_eq!(0, count_high_levels(&mut mcu, &mut host, pins::PA4, 256));
+}
+
+#[test]
+fn prescaler_and_scaler_divide_timer_clock() {
+    // SYSCLK, PA3 output, period mode; prescaler = 4, scaler = 2
+    let (mut mcu, mut host) = setup_timer2(0, 0b0010_0001, 0b0001_1000);
+    assert_eq!(80, count_high_levels(&mut mcu, &mut host, pins::PA3, 160));
+}
+
+#[test]
+fn disabled_output_restores_pa_data_level() {
+    // Full duty PWM on PA3, then timer output is disabled
+    let (mut mcu, mut host) = setup(&[
+        movak(0xFF),
+        movioa(IO_ADDR_TM2B),
+        movak(0b0001_1010),
+        movioa(IO_ADDR_TM2C),
+        movak(0x00),
+        movioa(IO_ADDR_TM2C),
+    ]);
+    run(&mut mcu, &mut host, 4);
+    assert!(host.output(pins::PA3));
+    run(&mut mcu, &mut host, 2);
+    assert!(!host.output(pins::PA3));
+}
+
+#[test]
+fn tm2ct_reads_counter_value() {
+    let (mut mcu, mut host) = setup(&[0x00A0 | IO_ADDR_TM2CT as Word]);
+    mcu.state.timer2.set_counter(0x42);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(0x42, mcu.core.acc());
+}
@@ -0,0 +1,125 @@
+use super::{goto, movak, movioa, set_input, setup};
+use crate::{
+    isa::pdk13::{regs::*, Word},
+    mcu::pms150c::{pins, Emulator},
+};
+use core::time::Duration;
+
+const STOPSYS: Word = 0x0036;
+const STOPEXE: Word = 0x0037;
+
+#[test]
+fn step_returns_instruction_cycles() {
+    let (mut mcu, mut host) = setup(&[movak(0x42), goto(0)]);
+    assert_eq!(1, mcu.step(&mut host).unwrap());
+    assert_eq!(2, mcu.step(&mut host).unwrap());
+    assert_eq!(1, mcu.step(&mut host).unwrap());
+    assert_eq!(4, mcu.cycles());
+}
+
+#[test]
+fn system_clock_is_stopped_in_stopsys() {
+    let (mut mcu, mut host) = setup(&[STOPSYS]);
+    mcu.step(&mut host).unwrap();
+    assert_eq!(0, mcu.get_frequency());
+    let elapsed = mcu.elapsed();
+    assert_eq!(0, mcu.step(&mut host).unwrap());
+    assert_eq!(1, mcu.cycles());
+    assert!(mcu.elapsed() > elapsed);
+}
+
+#[test]
+fn cycles_are_counted_in_stopexe() {
+    let (mut mcu, mut host) = setup(&[STOPEXE]);
+    mcu.step(&mut host).unwrap();
+    assert_eq!(1, mcu.step(&mut host).unwrap());
+    assert_eq!(2, mcu.cycles());
+}
+
+#[test]
+fn time_base_has_no_rounding_error() {
+    // ILRC period is not a whole number of nanoseconds
+    let (mut mcu, mut host) = setup(&[goto(0)]);
+    assert_eq!(62_000, mcu.run_cycles(&mut host, 62_000).unwrap());
+    assert_eq!(Duration::from_secs(1), mcu.elapsed());
+}
+
+#[test]
+fn run_cycles_returns_early_when_clock_is_stopped() {
+    let (mut mcu, mut host) = setup(&[movak(0), STOPSYS]);
+    assert_eq!(2, mcu.run_cycles(&mut host, 100).unwrap());
+}
+
+#[test]
+fn run_for_follows_clock_switch() {
+    // Switch to IHRC/4 (4 MHz) system clock
+    let (mut mcu, mut host) = setup(&[movak(0b0001_0100), movioa(IO_ADDR_CLKMD), goto(2)]);
+    mcu.run_for(&mut host, Duration::from_millis(1)).unwrap();
+    assert!(mcu.elapsed() >= Duration::from_millis(1));
+    // Two instructions are executed on ILRC clock, the rest on 4 MHz clock
+    let cycles = mcu.cycles();
+    assert!(cycles > 3_800 && cycles < 4_000, "cycles: {}", cycles);
+}
+
+#[test]
+fn run_for_wakes_up_from_stopsys() {
+    let (mut mcu, mut host) = setup(&[STOPSYS, goto(1)]);
+    mcu.run_for(&mut host, Duration::from_millis(1)).unwrap();
+    assert!(mcu.is_sleeping());
+    assert!(mcu.elapsed() >= Duration::from_millis(1));
+    set_input(&mut mcu, pins::PA0, true);
+    mcu.run_for(&mut host, Duration::from_millis(1)).unwrap();
+    assert!(!mcu.is_sleeping());
+}
+
+#[test]
+fn run_until_stops_on_predicate() {
+    let (mut mcu, mut host) = setup(&[movak(0), movak(1), movak(2), STOPSYS]);
+    assert!(
+        mcu.run_until(&mut host, Duration::from_secs(1), |mcu| mcu.is_sleeping())
+            .unwrap()
+    );
+    assert_eq!(4, mcu.cycles());
+    assert!(
+        !mcu.run_until(&mut host, Duration::from_millis(1), |mcu| !mcu.is_sleeping())
+            .unwrap()
+    );
+}
+
+#[test]
+fn host_callbacks_receive_event_timestamp() {
+    let (mut mcu, mut host) = setup(&[
+        movak(pins::PA3.port_bit_mask()),
+        movioa(IO_ADDR_PAC),
+        movioa(IO_ADDR_PA),
+    ]);
+    mcu.run_cycles(&mut host, 3).unwrap();
+    let timestamp = host.output_timestamp(pins::PA3).unwrap();
+    assert_eq!(2, timestamp.cycle);
+    // Two ILRC periods
+    assert_eq!(Duration::from_nanos(32_258), timestamp.time);
+}
+
+#[test]
+fn events_inside_multi_cycle_instruction_are_timestamped_precisely() {
+    // Timer2 period mode on PA3 toggles output each 3 cycles, while the main loop
+    // consists of two-cycle instructions only
+    let (mut mcu, mut host) = setup(&[
+        movak(2),
+        movioa(IO_ADDR_TM2B),
+        movak(0b0001_1000),
+        movioa(IO_ADDR_TM2C),
+        goto(4),
+    ]);
+    let mut edges = [0; 4];
+    for edge in edges.iter_mut() {
+        let previous = host.output_timestamp(pins::PA3);
+        while host.output_timestamp(pins::PA3) == previous {
+            mcu.step(&mut host).unwrap();
+        }
+        *edge = host.output_timestamp(pins::PA3).unwrap().cycle;
+    }
+    assert_eq!(3, edges[1] - edges[0]);
+    assert_eq!(3, edges[2] - edges[1]);
+    assert_eq!(3, edges[3] - edges[2]);
+}
@@ -0,0 +1,55 @@
+use super::{goto, movak, movioa, run, setup};
+use crate::{
+    isa::pdk13::regs::*,
+    mcu::pms150c::{Emulator, ResetCause},
+};
+
+// inc 0x10
+const INC_BOOT_COUNTER: u16 = 0x0900 | 0x10;
+const WDRESET: u16 = 0x0030;
+
+#[test]
+fn watchdog_is_enabled_after_reset_and_resets_mcu() {
+    let (mut mcu, mut host) = setup(&[INC_BOOT_COUNTER, goto(1)]);
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+    // System clock is ILRC after reset, default timeout is 8K ILRC clocks
+    run(&mut mcu, &mut host, 8 * 1024 - 1);
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+    assert_eq!(1, mcu.state.ram[0x10]);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(ResetCause::Watchdog, mcu.last_reset_cause());
+    assert_eq!(0, mcu.core.pc());
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(2, mcu.state.ram[0x10]);
+}
+
+#[test]
+fn feeding_watchdog_prevents_reset() {
+    let (mut mcu, mut host) = setup(&[INC_BOOT_COUNTER, WDRESET, goto(1)]);
+    run(&mut mcu, &mut host, 64 * 1024);
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+    assert_eq!(1, mcu.state.ram[0x10]);
+}
+
+#[test]
+fn misc_register_selects_watchdog_period() {
+    let (mut mcu, mut host) = setup(&[movak(0b01), movioa(IO_ADDR_MISC), goto(2)]);
+    run(&mut mcu, &mut host, 16 * 1024 - 2);
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+    run(&mut mcu, &mut host, 2);
+    assert_eq!(ResetCause::Watchdog, mcu.last_reset_cause());
+}
+
+#[test]
+fn watchdog_can_be_disabled_via_clkmd() {
+    let (mut mcu, mut host) = setup(&[movak(0b11110100), movioa(IO_ADDR_CLKMD), goto(2)]);
+    run(&mut mcu, &mut host, 64 * 1024);
+    assert_eq!(ResetCause::PowerOn, mcu.last_reset_cause());
+}
+
+#[test]
+fn reset_instruction_is_reported_as_software_reset() {
+    let (mut mcu, mut host) = setup(&[0x0035]);
+    run(&mut mcu, &mut host, 1);
+    assert_eq!(ResetCause::Software, mcu.last_reset_cause());
+}
@@ -0,0 +1,50 @@
+use core::time::Duration;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Simulated time base. Time is advanced in clock periods without rounding errors: the
+/// fractional nanosecond part is carried between the calls and rescaled on clock switches
+#[derive(Copy, Clone)]
+pub(super) struct TimeBase {
+    nanos: u64,
+    // Fractional part of nanoseconds in 1 / `frequency` units
+    remainder: u64,
+    frequency: u32,
+}
+
+impl TimeBase {
+    pub fn new() -> Self {
+        Self {
+            nanos: 0,
+            remainder: 0,
+            frequency: 0,
+        }
+    }
+
+    /// Advances time by `cycles` periods of the `frequency` clock
+    pub fn advance_cycles(&mut self, cycles: u32, frequency: u32) {
+        if frequency == 0 {
+            return;
+        }
+        if frequency != self.frequency {
+            self.remainder = if self.frequency == 0 {
+                0
+            } else {
+                self.remainder * frequency as u64 / self.frequency as u64
+            };
+            self.frequency = frequency;
+        }
+        let total = self.remainder + cycles as u64 * NANOS_PER_SECOND;
+        self.nanos += total / frequency as u64;
+        self.remainder = total % frequency as u64;
+    }
+
+    /// Advances time by the given amount of nanoseconds
+    pub fn advance_nanos(&mut self, nanos: u64) {
+        self.nanos += nanos;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.nanos)
+    }
+}
@@ -0,0 +1,107 @@
+//! 16 bit timer (T16) emulation. Timer is configured via T16M register:
+//!
+//! --3-2--3
+//! sssppiii
+//!   s p  i
+//! s => clock source
+//! p => clock prescaler (1, 4, 16, 64)
+//! i => counter bit which triggers interrupt (bit 8 ..= bit 15)
+
+use crate::isa::pdk13::{Byte, Word};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(super) enum Timer16Clock {
+    Disabled,
+    SysClk,
+    Pa4Falling,
+    Ihrc,
+    Ilrc,
+    Pa0Falling,
+}
+
+pub(super) struct Timer16 {
+    counter: Word,
+    mode: Byte,
+    prescaler_counter: u32,
+}
+
+impl Timer16 {
+    pub fn new() -> Self {
+        Self {
+            counter: 0,
+            mode: 0,
+            prescaler_counter: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.counter = 0;
+        self.mode = 0;
+        self.prescaler_counter = 0;
+    }
+
+    pub fn set_mode(&mut self, t16m: Byte) {
+        self.mode = t16m;
+    }
+
+    pub fn counter(&self) -> Word {
+        self.counter
+    }
+
+    pub fn set_counter(&mut self, value: Word) {
+        self.counter = value;
+    }
+
+    pub fn clock_source(&self) -> Timer16Clock {
+        match (self.mode >> 5) & 0x07 {
+            0b001 => Timer16Clock::SysClk,
+            0b011 => Timer16Clock::Pa4Falling,
+            0b100 => Timer16Clock::Ihrc,
+            0b110 => Timer16Clock::Ilrc,
+            0b111 => Timer16Clock::Pa0Falling,
+            // 0b000 is disabled timer, other values are reserved
+            _ => Timer16Clock::Disabled,
+        }
+    }
+
+    fn prescaler(&self) -> u32 {
+        match (self.mode >> 3) & 0x03 {
+            0b00 => 1,
+            0b01 => 4,
+            0b10 => 16,
+            _ => 64,
+        }
+    }
+
+    fn interrupt_bit(&self) -> u32 {
+        8 + (self.mode & 0x07) as u32
+    }
+
+    /// Feeds `pulses` of the selected clock source to the timer. Returns true if selected
+    /// interrupt bit had at least one transition on the specified edge
+    pub fn clock(&mut self, pulses: u32, falling_edge: bool) -> bool {
+        if pulses == 0 {
+            return false;
+        }
+
+        let prescaled = self.prescaler_counter + pulses;
+        let increments = prescaled / self.prescaler();
+        self.prescaler_counter = prescaled % self.prescaler();
+        if increments == 0 {
+            return false;
+        }
+
+        let old = self.counter as u64;
+        let new = old + increments as u64;
+        self.counter = new as Word;
+
+        // Counter values where selected bit becomes set (rising) or cleared (falling) are
+        // repeated with the period of 2^(bit + 1)
+        let bit = self.interrupt_bit();
+        let period = 1u64 << (bit + 1);
+        let edge_value = if falling_edge { 0 } else { 1u64 << bit };
+        let edges_before = (old + period - edge_value) / period;
+        let edges_after = (new + period - edge_value) / period;
+        edges_after != edges_before
+    }
+}
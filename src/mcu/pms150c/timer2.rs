@@ -0,0 +1,161 @@
+//! 8 bit timer with PWM generation (Timer2). Timer is configured via TM2C register:
+//!
+//! [7:4] => clock source
+//! [3:2] => output pin (disabled, PA3, PA4)
+//! [1]   => mode (period, PWM)
+//! [0]   => inverse output
+//!
+//! TM2S register selects PWM resolution (6 or 8 bit; PMS150C has no 7 bit resolution),
+//! clock prescaler (1, 4, 16, 64) and clock scaler (1 ..= 32). TM2B holds counter bound
+//! for the period mode or duty for the PWM mode
+
+use crate::{
+    isa::pdk13::Byte,
+    mcu::{host_adapter::Pin, pms150c::pins},
+};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(super) enum Timer2Clock {
+    Disabled,
+    SysClk,
+    Ihrc,
+    Ilrc,
+    Comparator,
+    Pa0Rising,
+    Pa0Falling,
+    Pa4Rising,
+    Pa4Falling,
+}
+
+pub(super) struct Timer2 {
+    control: Byte,
+    scaler: Byte,
+    bound: Byte,
+    counter: Byte,
+    divider_counter: u32,
+    period_output: bool,
+}
+
+impl Timer2 {
+    pub fn new() -> Self {
+        Self {
+            control: 0,
+            scaler: 0,
+            bound: 0,
+            counter: 0,
+            divider_counter: 0,
+            period_output: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn set_control(&mut self, tm2c: Byte) {
+        self.control = tm2c;
+    }
+
+    pub fn set_scaler(&mut self, tm2s: Byte) {
+        self.scaler = tm2s;
+    }
+
+    pub fn set_bound(&mut self, tm2b: Byte) {
+        self.bound = tm2b;
+    }
+
+    pub fn counter(&self) -> Byte {
+        self.counter
+    }
+
+    pub fn set_counter(&mut self, value: Byte) {
+        self.counter = value;
+    }
+
+    pub fn clock_source(&self) -> Timer2Clock {
+        match self.control >> 4 {
+            0b0001 => Timer2Clock::SysClk,
+            0b0010 => Timer2Clock::Ihrc,
+            0b0100 => Timer2Clock::Ilrc,
+            0b0101 => Timer2Clock::Comparator,
+            0b1000 => Timer2Clock::Pa0Rising,
+            0b1001 => Timer2Clock::Pa0Falling,
+            0b1100 => Timer2Clock::Pa4Rising,
+            0b1101 => Timer2Clock::Pa4Falling,
+            // 0b0000 is disabled timer, other values are reserved (e.g. EOSC or PB0)
+            _ => Timer2Clock::Disabled,
+        }
+    }
+
+    pub fn output_pin(&self) -> Option<Pin> {
+        match (self.control >> 2) & 0x03 {
+            0b10 => Some(pins::PA3),
+            0b11 => Some(pins::PA4),
+            // PB2 output is not present on PMS150C
+            _ => None,
+        }
+    }
+
+    pub fn output(&self) -> bool {
+        let inverse = self.control & 0x01 != 0;
+        let output = if self.pwm_mode() {
+            self.counter <= self.pwm_duty()
+        } else {
+            self.period_output
+        };
+        output ^ inverse
+    }
+
+    fn pwm_mode(&self) -> bool {
+        self.control & 0x02 != 0
+    }
+
+    fn pwm_counter_mask(&self) -> Byte {
+        if self.scaler & 0x80 != 0 {
+            0x3F
+        } else {
+            0xFF
+        }
+    }
+
+    fn pwm_duty(&self) -> Byte {
+        self.bound & self.pwm_counter_mask()
+    }
+
+    fn divider(&self) -> u32 {
+        let prescaler = match (self.scaler >> 5) & 0x03 {
+            0b00 => 1,
+            0b01 => 4,
+            0b10 => 16,
+            _ => 64,
+        };
+        prescaler * ((self.scaler & 0x1F) as u32 + 1)
+    }
+
+    /// Feeds `pulses` of the selected clock source to the timer. Returns true if the counter
+    /// has matched TM2B at least once, which triggers TM2 interrupt request
+    pub fn clock(&mut self, pulses: u32) -> bool {
+        if pulses == 0 {
+            return false;
+        }
+
+        let divided = self.divider_counter + pulses;
+        let increments = divided / self.divider();
+        self.divider_counter = divided % self.divider();
+
+        let mut matched = false;
+        for _ in 0..increments {
+            if self.pwm_mode() {
+                self.counter = self.counter.wrapping_add(1) & self.pwm_counter_mask();
+                matched |= self.counter == self.pwm_duty();
+            } else if self.counter == self.bound {
+                self.counter = 0;
+                self.period_output = !self.period_output;
+                matched = true;
+            } else {
+                self.counter = self.counter.wrapping_add(1);
+            }
+        }
+        matched
+    }
+}
@@ -0,0 +1,52 @@
+//! Watchdog timer emulation. Watchdog is enabled via CLKMD[1], clocked by ILRC and its timeout
+//! period is selected by MISC[1:0] (8K, 16K, 64K or 256K ILRC clocks)
+
+use crate::isa::pdk13::Byte;
+
+pub(super) struct Watchdog {
+    enabled: bool,
+    counter: u32,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            counter: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.counter = 0;
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.counter = 0;
+        }
+        self.enabled = enabled;
+    }
+
+    fn timeout(misc: Byte) -> u32 {
+        match misc & 0x03 {
+            0b00 => 8 * 1024,
+            0b01 => 16 * 1024,
+            0b10 => 64 * 1024,
+            _ => 256 * 1024,
+        }
+    }
+
+    /// Feeds ILRC pulses to the watchdog. Returns true when watchdog timeout has expired
+    pub fn clock(&mut self, ilrc_pulses: u32, misc: Byte) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        self.counter += ilrc_pulses;
+        if self.counter >= Self::timeout(misc) {
+            self.counter = 0;
+            return true;
+        }
+        false
+    }
+}
@@ -0,0 +1,36 @@
+//! Helpers shared by unit tests of different modules
+
+use core::fmt::{self, Write};
+
+const TEXT_BUFFER_CAPACITY: usize = 4096;
+
+/// Fixed capacity text buffer, crate is built without allocator
+pub struct TextBuffer {
+    data: [u8; TEXT_BUFFER_CAPACITY],
+    len: usize,
+}
+
+impl TextBuffer {
+    pub fn new() -> Self {
+        Self {
+            data: [0; TEXT_BUFFER_CAPACITY],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.data[..self.len]).unwrap()
+    }
+}
+
+impl Write for TextBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.data.len() {
+            return Err(fmt::Error);
+        }
+        self.data[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+//! Debug Adapter Protocol server for IDE integration. Firmware is presented as the ROM
+//! listing with a single line per word, which is used for breakpoints and stepping
+
+pub mod pins;
+pub mod protocol;
+pub mod server;
+#[cfg(test)]
+mod test;
+
+pub use server::DapServer;
@@ -0,0 +1,27 @@
+use std::{
+    io::{self, BufReader},
+    process,
+    sync::mpsc,
+    thread,
+};
+
+use vpadauk_dap::{protocol::read_message, DapServer};
+
+fn main() {
+    let (sender, requests) = mpsc::channel();
+    // Requests are read in the separate thread, so running target could be paused
+    thread::spawn(move || {
+        let mut input = BufReader::new(io::stdin());
+        while let Ok(Some(request)) = read_message(&mut input) {
+            if sender.send(request).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut server = DapServer::new(io::stdout());
+    if let Err(err) = server.serve(requests) {
+        eprintln!("Debug session failed: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,98 @@
+//! Host which keeps track of pin states reported by the emulator. Input levels are driven by
+//! the debugger client through the pins scope
+
+use vpadauk::mcu::{
+    host_adapter::{AnalogSignal, HostAdapter, Pin, Timestamp},
+    pms150c::pins::ALL_PINS,
+};
+
+#[derive(Copy, Clone, Default)]
+pub struct PinState {
+    pub output: bool,
+    pub output_enabled: bool,
+    pub pull_up_enabled: bool,
+    /// Level driven to the pin by the client, `None` if the pin is not driven
+    pub input: Option<bool>,
+}
+
+impl PinState {
+    /// Returns pin level, input which is not driven is pulled high by the enabled pull-up
+    pub fn level(&self) -> bool {
+        if self.output_enabled {
+            self.output
+        } else {
+            self.input.unwrap_or(self.pull_up_enabled)
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let level = if self.level() { "high" } else { "low" };
+        if self.output_enabled {
+            format!("{} (output)", level)
+        } else if self.pull_up_enabled {
+            format!("{} (input, pull-up)", level)
+        } else {
+            format!("{} (input)", level)
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PinHost {
+    states: [PinState; ALL_PINS.len()],
+}
+
+impl PinHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns state of the pin, unknown pins are reported as not connected
+    pub fn state(&self, pin: Pin) -> PinState {
+        pin_index(pin).map_or_else(PinState::default, |index| self.states[index])
+    }
+
+    pub fn set_input(&mut self, pin: Pin, value: bool) {
+        if let Some(state) = self.state_mut(pin) {
+            state.input = Some(value);
+        }
+    }
+
+    fn state_mut(&mut self, pin: Pin) -> Option<&mut PinState> {
+        pin_index(pin).map(move |index| &mut self.states[index])
+    }
+}
+
+fn pin_index(pin: Pin) -> Option<usize> {
+    ALL_PINS.iter().position(|(_, known)| *known == pin)
+}
+
+impl HostAdapter for PinHost {
+    fn write_pin_digital(&mut self, pin: Pin, value: bool, _timestamp: Timestamp) {
+        if let Some(state) = self.state_mut(pin) {
+            state.output = value;
+        }
+    }
+
+    fn read_pin_analog(&self, pin: Pin, _timestamp: Timestamp) -> AnalogSignal {
+        if self.state(pin).level() {
+            AnalogSignal::from_u16(u16::MAX)
+        } else {
+            AnalogSignal::from_u16(0)
+        }
+    }
+
+    fn write_pin_analog(&mut self, _pin: Pin, _value: AnalogSignal, _timestamp: Timestamp) {}
+
+    fn set_pin_output_enabled(&mut self, pin: Pin, enabled: bool, _timestamp: Timestamp) {
+        if let Some(state) = self.state_mut(pin) {
+            state.output_enabled = enabled;
+        }
+    }
+
+    fn set_pin_pull_up_enabled(&mut self, pin: Pin, enabled: bool, _timestamp: Timestamp) {
+        if let Some(state) = self.state_mut(pin) {
+            state.pull_up_enabled = enabled;
+        }
+    }
+}
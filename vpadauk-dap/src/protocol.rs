@@ -0,0 +1,44 @@
+//! DAP base protocol framing: JSON messages prefixed with `Content-Length` header
+
+use std::io::{self, BufRead, ErrorKind, Write};
+
+use serde_json::Value;
+
+const CONTENT_LENGTH: &str = "Content-Length:";
+
+/// Reads the next message. Returns `None` if client has closed the stream
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix(CONTENT_LENGTH) {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = content_length
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "Missing Content-Length header"))?;
+    let mut content = vec![0u8; length];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "{} {}\r\n\r\n{}",
+        CONTENT_LENGTH,
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
@@ -0,0 +1,481 @@
+//! DAP request handling. The client sees a single thread with a single stack frame; source
+//! of the frame is the ROM listing, where line `n` holds the word at address `n - 1`
+
+use std::{
+    convert::TryFrom,
+    fs,
+    io::{self, Write},
+    sync::mpsc::{Receiver, TryRecvError},
+};
+
+use serde_json::{json, Value};
+use vpadauk::{
+    firmware::FirmwareFile,
+    isa::pdk13::{
+        disasm::{disassemble_word, io_register_name},
+        regs::{
+            FLAG_AUX_CARRY_MASK, FLAG_CARRY_MASK, FLAG_OVERFLOW_MASK, FLAG_ZERO_MASK,
+            IO_ADDR_FLAGS, IO_ADDR_SP,
+        },
+        Byte, IoAddr, Pdk13Result, RamAddr, RomAddr,
+    },
+    mcu::pms150c::{
+        debugger::{BreakpointId, Debugger, StopReason},
+        pins::ALL_PINS,
+        Emulator, Pms150c,
+    },
+};
+
+use crate::{
+    pins::PinHost,
+    protocol::write_message,
+};
+
+const THREAD_ID: u64 = 1;
+const LISTING_REFERENCE: u64 = 1;
+const LISTING_NAME: &str = "rom.lst";
+
+const ROM_WORDS: RomAddr = 0x400;
+const RAM_BYTES: RamAddr = 0x40;
+const IO_BYTES: IoAddr = 0x20;
+
+/// Cycles emulated between checks for incoming requests while target is running
+const CONTINUE_SLICE_CYCLES: u64 = 100_000;
+/// Cycle budget of step over and step out, target is paused if it is exhausted
+const STEP_CYCLES: u64 = 10_000_000;
+
+const SCOPE_REGISTERS: u64 = 1;
+const SCOPE_RAM: u64 = 2;
+const SCOPE_IO: u64 = 3;
+const SCOPE_PINS: u64 = 4;
+
+const FLAG_NAMES: [(Byte, &str); 4] = [
+    (FLAG_ZERO_MASK, "Z"),
+    (FLAG_CARRY_MASK, "C"),
+    (FLAG_AUX_CARRY_MASK, "AC"),
+    (FLAG_OVERFLOW_MASK, "OV"),
+];
+
+type RequestResult = Result<Value, String>;
+
+pub struct DapServer<W: Write> {
+    output: W,
+    seq: u64,
+    debugger: Debugger,
+    host: PinHost,
+    /// Breakpoints in the listing, DAP breakpoint id is index + 1
+    breakpoints: Vec<(BreakpointId, RomAddr)>,
+    stop_on_entry: bool,
+    running: bool,
+    /// Events which are sent after the response to the current request
+    events: Vec<Value>,
+}
+
+impl<W: Write> DapServer<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            output,
+            seq: 0,
+            debugger: Debugger::new(Pms150c::new()),
+            host: PinHost::new(),
+            breakpoints: Vec::new(),
+            stop_on_entry: false,
+            running: false,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    /// Handles requests until client disconnects or closes the request channel. Incoming
+    /// requests are checked between emulation slices while target is running
+    pub fn serve(&mut self, requests: Receiver<Value>) -> io::Result<()> {
+        loop {
+            let request = if self.running {
+                match requests.try_recv() {
+                    Ok(request) => Some(request),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            } else {
+                match requests.recv() {
+                    Ok(request) => Some(request),
+                    Err(_) => return Ok(()),
+                }
+            };
+            if let Some(request) = request {
+                if !self.handle(&request)? {
+                    return Ok(());
+                }
+            }
+            if self.running {
+                let result = self.debugger.resume(&mut self.host, CONTINUE_SLICE_CYCLES);
+                if !matches!(result, Ok(StopReason::CycleLimit)) {
+                    self.running = false;
+                    let event = self.stopped_event(result);
+                    self.send(event)?;
+                }
+            }
+        }
+    }
+
+    /// Responds to the request, returns false if client has disconnected
+    fn handle(&mut self, request: &Value) -> io::Result<bool> {
+        let command = request["command"].as_str().unwrap_or_default();
+        let args = &request["arguments"];
+        let result = match command {
+            "initialize" => self.initialize(),
+            "launch" => self.launch(args),
+            "setBreakpoints" => self.set_breakpoints(args),
+            "configurationDone" => self.configuration_done(),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "PMS150C" }] })),
+            "stackTrace" => Ok(self.stack_trace()),
+            "source" => self.source(args),
+            "scopes" => Ok(scopes()),
+            "variables" => self.variables(args),
+            "setVariable" => self.set_variable(args),
+            "continue" => {
+                self.running = true;
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "pause" => self.pause(),
+            "next" | "stepIn" | "stepOut" => self.step(command),
+            "disconnect" | "terminate" => Ok(json!({})),
+            _ => Err(format!("Unsupported request: {}", command)),
+        };
+        self.respond(request, command, result)?;
+        for event in std::mem::take(&mut self.events) {
+            self.send(event)?;
+        }
+        Ok(!matches!(command, "disconnect" | "terminate"))
+    }
+
+    fn initialize(&mut self) -> RequestResult {
+        self.events.push(event("initialized", json!({})));
+        Ok(json!({
+            "supportsConfigurationDoneRequest": true,
+            "supportsSetVariable": true,
+            "supportsTerminateRequest": true,
+        }))
+    }
+
+    fn launch(&mut self, args: &Value) -> RequestResult {
+        let program = args["program"]
+            .as_str()
+            .ok_or_else(|| "Missing program path".to_string())?;
+        self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+        let mcu = self.debugger.mcu_mut();
+        let content = fs::read(program).map_err(|err| err.to_string())?;
+        let file = FirmwareFile::parse(program, &content).map_err(|err| err.to_string())?;
+        mcu.load_firmware_file(&file).map_err(|err| err.to_string())?;
+        mcu.init(&mut self.host);
+        Ok(json!({}))
+    }
+
+    /// Replaces all breakpoints, only ROM listing lines are accepted
+    fn set_breakpoints(&mut self, args: &Value) -> RequestResult {
+        for (id, _) in self.breakpoints.drain(..) {
+            self.debugger.remove_breakpoint(id);
+        }
+        let is_listing = args["source"]["sourceReference"].as_u64() == Some(LISTING_REFERENCE);
+        let lines: Vec<u64> = args["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                let lines = breakpoints.iter().map(|breakpoint| &breakpoint["line"]);
+                lines.filter_map(Value::as_u64).collect()
+            })
+            .unwrap_or_default();
+
+        let mut result = Vec::with_capacity(lines.len());
+        for line in lines {
+            let address = line.wrapping_sub(1);
+            let added = if !is_listing {
+                Err("Breakpoints are supported only in the ROM listing")
+            } else if address >= ROM_WORDS as u64 {
+                Err("Line is outside of ROM")
+            } else {
+                let address = address as RomAddr;
+                self.debugger
+                    .add_breakpoint(address)
+                    .map(|id| (id, address))
+                    .ok_or("All breakpoint slots are used")
+            };
+            result.push(match added {
+                Ok(breakpoint) => {
+                    self.breakpoints.push(breakpoint);
+                    json!({ "id": self.breakpoints.len(), "verified": true, "line": line })
+                }
+                Err(message) => json!({ "verified": false, "line": line, "message": message }),
+            });
+        }
+        Ok(json!({ "breakpoints": result }))
+    }
+
+    fn configuration_done(&mut self) -> RequestResult {
+        if self.stop_on_entry {
+            self.events.push(stopped("entry", None, None));
+        } else {
+            self.running = true;
+        }
+        Ok(json!({}))
+    }
+
+    fn stack_trace(&self) -> Value {
+        let pc = self.debugger.pc();
+        let mut name = String::new();
+        // Writing to String can't fail
+        let _ = disassemble_word(&mut name, self.debugger.read_rom(pc));
+        json!({
+            "stackFrames": [{
+                "id": 0,
+                "name": name,
+                "source": listing_source(),
+                "line": pc as u64 + 1,
+                "column": 1,
+                "instructionPointerReference": format!("{:#05x}", pc),
+            }],
+            "totalFrames": 1,
+        })
+    }
+
+    fn source(&self, args: &Value) -> RequestResult {
+        let reference = args["sourceReference"]
+            .as_u64()
+            .or_else(|| args["source"]["sourceReference"].as_u64());
+        if reference != Some(LISTING_REFERENCE) {
+            return Err("Unknown source".into());
+        }
+        let mut content = String::new();
+        for address in 0..ROM_WORDS {
+            let word = self.debugger.read_rom(address);
+            content.push_str(&format!("{:03x}: {:04x}    ", address, word));
+            let _ = disassemble_word(&mut content, word);
+            content.push('\n');
+        }
+        Ok(json!({ "content": content, "mimeType": "text/x-pdk13-asm" }))
+    }
+
+    fn variables(&self, args: &Value) -> RequestResult {
+        let variables: Vec<Value> = match args["variablesReference"].as_u64() {
+            Some(SCOPE_REGISTERS) => vec![
+                variable("ACC", hex_byte(self.debugger.acc())),
+                variable("FLAGS", self.describe_flags()),
+                variable("SP", hex_byte(self.debugger.sp())),
+                variable("PC", format!("{:#05x}", self.debugger.pc())),
+            ],
+            Some(SCOPE_RAM) => (0..RAM_BYTES)
+                .map(|address| {
+                    let value = hex_byte(self.debugger.read_ram(address));
+                    variable(&hex_byte(address), value)
+                })
+                .collect(),
+            Some(SCOPE_IO) => (0..IO_BYTES)
+                .filter_map(|address| {
+                    let name = io_register_name(address)?;
+                    Some(variable(name, hex_byte(self.debugger.read_io(address))))
+                })
+                .collect(),
+            Some(SCOPE_PINS) => ALL_PINS
+                .iter()
+                .map(|(name, pin)| variable(name, self.host.state(*pin).describe()))
+                .collect(),
+            _ => return Err("Unknown variables reference".into()),
+        };
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn set_variable(&mut self, args: &Value) -> RequestResult {
+        let name = args["name"].as_str().unwrap_or_default();
+        let text = args["value"].as_str().unwrap_or_default().trim();
+        if args["variablesReference"].as_u64() == Some(SCOPE_PINS) {
+            return self.set_pin(name, text);
+        }
+        let value = parse_number(text).ok_or_else(|| format!("Invalid value: {}", text))?;
+        let byte = || Byte::try_from(value).map_err(|_| format!("Value is too big: {}", text));
+
+        match args["variablesReference"].as_u64() {
+            Some(SCOPE_REGISTERS) => match name {
+                "ACC" => self.debugger.set_acc(byte()?),
+                "FLAGS" => self.debugger.write_io(IO_ADDR_FLAGS, byte()?),
+                "SP" => self.debugger.write_io(IO_ADDR_SP, byte()?),
+                "PC" => {
+                    let pc = RomAddr::try_from(value).ok().filter(|pc| *pc < ROM_WORDS);
+                    self.debugger.set_pc(pc.ok_or("PC is outside of ROM")?);
+                }
+                _ => return Err(format!("Unknown register: {}", name)),
+            },
+            Some(SCOPE_RAM) => {
+                let address = parse_number(name)
+                    .and_then(|address| RamAddr::try_from(address).ok())
+                    .filter(|address| *address < RAM_BYTES)
+                    .ok_or_else(|| format!("Unknown RAM address: {}", name))?;
+                self.debugger.write_ram(address, byte()?);
+            }
+            Some(SCOPE_IO) => {
+                let address = (0..IO_BYTES)
+                    .find(|address| io_register_name(*address) == Some(name))
+                    .ok_or_else(|| format!("Unknown IO register: {}", name))?;
+                self.debugger.write_io(address, byte()?);
+            }
+            _ => return Err("Unknown variables reference".into()),
+        }
+        let variables = self.variables(args)?;
+        let updated = variables["variables"]
+            .as_array()
+            .and_then(|variables| variables.iter().find(|variable| variable["name"] == name))
+            .map(|variable| variable["value"].clone())
+            .unwrap_or_default();
+        Ok(json!({ "value": updated }))
+    }
+
+    /// Drives input level of the pin, accepts `high`/`low` or `1`/`0`
+    fn set_pin(&mut self, name: &str, text: &str) -> RequestResult {
+        let pin = ALL_PINS
+            .iter()
+            .find(|(pin_name, _)| pin_name.eq_ignore_ascii_case(name))
+            .map(|(_, pin)| *pin)
+            .ok_or_else(|| format!("Unknown pin: {}", name))?;
+        let value = match text.to_ascii_lowercase().as_str() {
+            "high" | "1" => true,
+            "low" | "0" => false,
+            _ => return Err(format!("Invalid pin level: {}", text)),
+        };
+        let mcu = self.debugger.mcu_mut();
+        let now = mcu.elapsed();
+        mcu.set_pin_input(pin, value, now).map_err(|err| err.to_string())?;
+        self.host.set_input(pin, value);
+        Ok(json!({ "value": self.host.state(pin).describe() }))
+    }
+
+    fn pause(&mut self) -> RequestResult {
+        if self.running {
+            self.running = false;
+            self.events.push(stopped("pause", None, None));
+        }
+        Ok(json!({}))
+    }
+
+    fn step(&mut self, command: &str) -> RequestResult {
+        self.running = false;
+        let result = match command {
+            "next" => self.debugger.step_over(&mut self.host, STEP_CYCLES),
+            "stepOut" => self.debugger.step_out(&mut self.host, STEP_CYCLES),
+            _ => self.debugger.step(&mut self.host),
+        };
+        let event = self.stopped_event(result);
+        self.events.push(event);
+        Ok(json!({}))
+    }
+
+    fn stopped_event(&self, result: Pdk13Result<StopReason>) -> Value {
+        match result {
+            Ok(StopReason::Step) => stopped("step", None, None),
+            Ok(StopReason::Breakpoint(id)) => {
+                let index = self.breakpoints.iter().position(|(known, _)| *known == id);
+                stopped("breakpoint", index.map(|index| index + 1), None)
+            }
+            Ok(StopReason::Watchpoint(_, hit)) => {
+                let text = format!(
+                    "{:?} access to {:?} {:#04x}",
+                    hit.access, hit.space, hit.address
+                );
+                stopped("data breakpoint", None, Some(text))
+            }
+            Ok(StopReason::CycleLimit) => stopped("pause", None, None),
+            Err(err) => stopped("exception", None, Some(err.to_string())),
+        }
+    }
+
+    fn describe_flags(&self) -> String {
+        let flags = self.debugger.read_io(IO_ADDR_FLAGS);
+        let set: Vec<&str> = FLAG_NAMES
+            .iter()
+            .filter(|(mask, _)| flags & mask != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        format!("{} [{}]", hex_byte(flags), set.join(" "))
+    }
+
+    fn respond(&mut self, request: &Value, command: &str, result: RequestResult) -> io::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = Value::String(message),
+        }
+        self.send(response)
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        write_message(&mut self.output, &message)
+    }
+}
+
+fn scopes() -> Value {
+    let scope = |name: &str, reference: u64, count: usize| {
+        json!({
+            "name": name,
+            "variablesReference": reference,
+            "namedVariables": count,
+            "expensive": false,
+        })
+    };
+    let io_count = (0..IO_BYTES).filter_map(io_register_name).count();
+    let mut registers = scope("Registers", SCOPE_REGISTERS, 4);
+    registers["presentationHint"] = json!("registers");
+    json!({
+        "scopes": [
+            registers,
+            scope("RAM", SCOPE_RAM, RAM_BYTES as usize),
+            scope("IO", SCOPE_IO, io_count),
+            scope("Pins", SCOPE_PINS, ALL_PINS.len()),
+        ]
+    })
+}
+
+fn listing_source() -> Value {
+    json!({ "name": LISTING_NAME, "sourceReference": LISTING_REFERENCE })
+}
+
+fn event(name: &str, body: Value) -> Value {
+    json!({ "type": "event", "event": name, "body": body })
+}
+
+fn stopped(reason: &str, breakpoint: Option<usize>, text: Option<String>) -> Value {
+    let mut body = json!({
+        "reason": reason,
+        "threadId": THREAD_ID,
+        "allThreadsStopped": true,
+    });
+    if let Some(id) = breakpoint {
+        body["hitBreakpointIds"] = json!([id]);
+    }
+    if let Some(text) = text {
+        body["text"] = json!(text);
+    }
+    event("stopped", body)
+}
+
+fn variable(name: &str, value: String) -> Value {
+    json!({ "name": name, "value": value, "variablesReference": 0 })
+}
+
+fn hex_byte(value: Byte) -> String {
+    format!("{:#04x}", value)
+}
+
+/// Parses decimal or `0x`-prefixed hexadecimal number
+fn parse_number(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    env, fs,
+    io::{self, BufReader, Read, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+use serde_json::{json, Value};
+use vpadauk::isa::pdk13::asm::assemble;
+
+use crate::{protocol::read_message, DapServer};
+
+const PROGRAM: &str = "
+        mov a, #0x10
+        mov pac, a
+        mov pa, a
+        mov a, #0x5a
+    loop:
+        call sub
+        goto loop
+    sub:
+        inc 0x10
+        ret
+";
+
+/// Server output, each write is sent to the client as a separate chunk
+struct ChannelWriter(Sender<Vec<u8>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads chunks written by `ChannelWriter`, end of stream is reached when the writer is dropped
+struct ChannelReader {
+    chunks: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let count = buf.len().min(self.chunk.len() - self.position);
+        buf[..count].copy_from_slice(&self.chunk[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+/// Scripted DAP client
+struct Client {
+    requests: Sender<Value>,
+    responses: BufReader<ChannelReader>,
+    seq: u64,
+    server: Option<JoinHandle<()>>,
+}
+
+impl Client {
+    fn start() -> Self {
+        let (writer, chunks) = mpsc::channel();
+        let (requests, incoming) = mpsc::channel();
+        let server = thread::spawn(move || {
+            DapServer::new(ChannelWriter(writer)).serve(incoming).unwrap();
+        });
+        let reader = ChannelReader {
+            chunks,
+            chunk: Vec::new(),
+            position: 0,
+        };
+        Self {
+            requests,
+            responses: BufReader::new(reader),
+            seq: 0,
+            server: Some(server),
+        }
+    }
+
+    /// Starts session stopped at the entry point of the assembled program
+    fn launch(name: &str, source: &str) -> Self {
+        let mut client = Self::start();
+        client.request("initialize", json!({ "adapterID": "vpadauk" }));
+        client.expect_event("initialized");
+        let program = write_program(name, source);
+        let launched = client.request("launch", json!({ "program": program, "stopOnEntry": true }));
+        fs::remove_file(program).unwrap();
+        assert_eq!(json!(true), launched["success"]);
+        client.request("configurationDone", json!({}));
+        assert_eq!("entry", client.expect_event("stopped")["body"]["reason"]);
+        client
+    }
+
+    fn send(&mut self, command: &str, arguments: Value) {
+        self.seq += 1;
+        let request = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+        self.requests.send(request).unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        read_message(&mut self.responses).unwrap().unwrap()
+    }
+
+    /// Sends request and waits for the response, events in between are not expected
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.send(command, arguments);
+        let response = self.receive();
+        assert_eq!("response", response["type"]);
+        assert_eq!(json!(self.seq), response["request_seq"]);
+        assert_eq!(command, response["command"]);
+        response
+    }
+
+    fn expect_event(&mut self, name: &str) -> Value {
+        let event = self.receive();
+        assert_eq!("event", event["type"]);
+        assert_eq!(name, event["event"]);
+        event
+    }
+
+    fn line(&mut self) -> Value {
+        self.request("stackTrace", json!({ "threadId": 1 }))["body"]["stackFrames"][0]["line"]
+            .clone()
+    }
+
+    fn variable(&mut self, reference: u64, name: &str) -> Value {
+        let response = self.request("variables", json!({ "variablesReference": reference }));
+        let variables = response["body"]["variables"].as_array().unwrap();
+        variables
+            .iter()
+            .find(|variable| variable["name"] == name)
+            .map(|variable| variable["value"].clone())
+            .unwrap()
+    }
+
+    fn step(&mut self, command: &str) -> Value {
+        assert_eq!(
+            json!(true),
+            self.request(command, json!({ "threadId": 1 }))["success"]
+        );
+        self.expect_event("stopped")["body"].clone()
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            assert_eq!(
+                json!(true),
+                self.request("disconnect", json!({}))["success"]
+            );
+            self.server.take().unwrap().join().unwrap();
+        }
+    }
+}
+
+/// Writes assembled program to Intel HEX file in the temporary directory
+fn write_program(name: &str, source: &str) -> PathBuf {
+    let mut text = String::new();
+    assemble(source)
+        .unwrap()
+        .write_intel_hex(&mut text)
+        .unwrap();
+    let path = env::temp_dir().join(format!("vpadauk-dap-{}-{}.ihx", std::process::id(), name));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn initialize_reports_capabilities() {
+    let mut client = Client::start();
+    let response = client.request("initialize", json!({ "adapterID": "vpadauk" }));
+    assert_eq!(
+        json!(true),
+        response["body"]["supportsConfigurationDoneRequest"]
+    );
+    assert_eq!(json!(true), response["body"]["supportsSetVariable"]);
+    client.expect_event("initialized");
+    let response = client.request("evaluate", json!({ "expression": "a" }));
+    assert_eq!(json!(false), response["success"]);
+}
+
+#[test]
+fn launch_fails_for_missing_program() {
+    let mut client = Client::start();
+    client.request("initialize", json!({}));
+    client.expect_event("initialized");
+    let response = client.request("launch", json!({ "program": "/nonexistent/firmware.bin" }));
+    assert_eq!(json!(false), response["success"]);
+}
+
+#[test]
+fn stack_frame_points_to_rom_listing() {
+    let mut client = Client::launch("listing", PROGRAM);
+    let response = client.request("stackTrace", json!({ "threadId": 1 }));
+    let frame = &response["body"]["stackFrames"][0];
+    assert_eq!(json!(1), frame["line"]);
+    assert_eq!("mov a, #0x10", frame["name"]);
+    let reference = frame["source"]["sourceReference"].clone();
+
+    let response = client.request("source", json!({ "sourceReference": reference }));
+    let content = response["body"]["content"].as_str().unwrap().to_string();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(0x400, lines.len());
+    assert!(lines[3].starts_with("003: 175a"));
+    assert!(lines[4].ends_with("call 0x006"));
+}
+
+#[test]
+fn breakpoints_in_listing() {
+    let mut client = Client::start();
+    client.request("initialize", json!({}));
+    client.expect_event("initialized");
+    // Breakpoints are set before the program is loaded
+    let response = client.request(
+        "setBreakpoints",
+        json!({
+            "source": { "name": "rom.lst", "sourceReference": 1 },
+            "breakpoints": [{ "line": 7 }, { "line": 0x401 }],
+        }),
+    );
+    let breakpoints = &response["body"]["breakpoints"];
+    assert_eq!(json!(true), breakpoints[0]["verified"]);
+    assert_eq!(json!(false), breakpoints[1]["verified"]);
+
+    let program = write_program("breakpoints", PROGRAM);
+    client.request("launch", json!({ "program": program }));
+    fs::remove_file(program).unwrap();
+    client.request("configurationDone", json!({}));
+    let stopped = client.expect_event("stopped");
+    assert_eq!("breakpoint", stopped["body"]["reason"]);
+    assert_eq!(json!([1]), stopped["body"]["hitBreakpointIds"]);
+    assert_eq!(json!(7), client.line());
+
+    client.request("continue", json!({ "threadId": 1 }));
+    assert_eq!(
+        "breakpoint",
+        client.expect_event("stopped")["body"]["reason"]
+    );
+    assert_eq!("0x01", client.variable(2, "0x10"));
+
+    let response = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": "/tmp/blink.asm" }, "breakpoints": [{ "line": 7 }] }),
+    );
+    assert_eq!(json!(false), response["body"]["breakpoints"][0]["verified"]);
+}
+
+#[test]
+fn stepping() {
+    let mut client = Client::launch("stepping", PROGRAM);
+    for _ in 0..4 {
+        assert_eq!("step", client.step("stepIn")["reason"]);
+    }
+    assert_eq!(json!(5), client.line());
+    // Step over the call executes the whole subroutine
+    client.step("next");
+    assert_eq!(json!(6), client.line());
+    assert_eq!("0x01", client.variable(2, "0x10"));
+
+    client.step("next");
+    client.step("stepIn");
+    assert_eq!(json!(7), client.line());
+    client.step("stepOut");
+    assert_eq!(json!(6), client.line());
+    assert_eq!("0x02", client.variable(2, "0x10"));
+}
+
+#[test]
+fn registers_memory_and_pins_scopes() {
+    let mut client = Client::launch("variables", PROGRAM);
+    let response = client.request("scopes", json!({ "frameId": 0 }));
+    let names: Vec<&str> = response["body"]["scopes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|scope| scope["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(vec!["Registers", "RAM", "IO", "Pins"], names);
+
+    assert_eq!("low (input)", client.variable(4, "PA4"));
+    for _ in 0..4 {
+        client.step("stepIn");
+    }
+    assert_eq!("0x5a", client.variable(1, "ACC"));
+    assert_eq!("0x004", client.variable(1, "PC"));
+    assert_eq!("0x10", client.variable(3, "pac"));
+    assert_eq!("0x10", client.variable(3, "pa"));
+    assert_eq!("high (output)", client.variable(4, "PA4"));
+    assert_eq!("low (input)", client.variable(4, "PA3"));
+}
+
+#[test]
+fn set_variables() {
+    let mut client = Client::launch("set_variables", PROGRAM);
+    let set = |client: &mut Client, reference: u64, name: &str, value: &str| {
+        let arguments = json!({ "variablesReference": reference, "name": name, "value": value });
+        client.request("setVariable", arguments)
+    };
+    assert_eq!("0x33", set(&mut client, 1, "ACC", "0x33")["body"]["value"]);
+    assert_eq!(
+        "0x03 [Z C]",
+        set(&mut client, 1, "FLAGS", "3")["body"]["value"]
+    );
+    assert_eq!("0x7f", set(&mut client, 2, "0x20", "127")["body"]["value"]);
+    assert_eq!("0x01", set(&mut client, 3, "padier", "1")["body"]["value"]);
+    let response = set(&mut client, 4, "PA3", "high");
+    assert_eq!("high (input)", response["body"]["value"]);
+    assert_eq!(json!(false), set(&mut client, 1, "ACC", "0x100")["success"]);
+    assert_eq!(json!(false), set(&mut client, 4, "PA1", "high")["success"]);
+
+    assert_eq!("0x33", client.variable(1, "ACC"));
+    assert_eq!("0x7f", client.variable(2, "0x20"));
+    assert_eq!("high (input)", client.variable(4, "PA3"));
+}
+
+#[test]
+fn driven_input_overrides_pull_up() {
+    let mut client = Client::launch("pull_up", "mov a, #0x08\n mov paph, a\n stopsys");
+    client.step("stepIn");
+    client.step("stepIn");
+    assert_eq!("high (input, pull-up)", client.variable(4, "PA3"));
+    let arguments = json!({ "variablesReference": 4, "name": "PA3", "value": "low" });
+    let response = client.request("setVariable", arguments);
+    assert_eq!("low (input, pull-up)", response["body"]["value"]);
+    assert_eq!("low (input, pull-up)", client.variable(4, "PA3"));
+}
+
+#[test]
+fn pause_stops_running_target() {
+    let mut client = Client::launch("pause", PROGRAM);
+    client.request("continue", json!({ "threadId": 1 }));
+    client.request("pause", json!({ "threadId": 1 }));
+    assert_eq!("pause", client.expect_event("stopped")["body"]["reason"]);
+    let line = client.line().as_u64().unwrap();
+    assert!((5..=8).contains(&line));
+}
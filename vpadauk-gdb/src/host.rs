@@ -0,0 +1,14 @@
+use vpadauk::mcu::host_adapter::{AnalogSignal, HostAdapter, Pin, Timestamp};
+
+/// Host without anything connected to MCU pins
+pub struct UnconnectedHost;
+
+impl HostAdapter for UnconnectedHost {
+    fn write_pin_digital(&mut self, _pin: Pin, _value: bool, _timestamp: Timestamp) {}
+    fn read_pin_analog(&self, _pin: Pin, _timestamp: Timestamp) -> AnalogSignal {
+        AnalogSignal::from_u16(0)
+    }
+    fn write_pin_analog(&mut self, _pin: Pin, _value: AnalogSignal, _timestamp: Timestamp) {}
+    fn set_pin_output_enabled(&mut self, _pin: Pin, _enabled: bool, _timestamp: Timestamp) {}
+    fn set_pin_pull_up_enabled(&mut self, _pin: Pin, _enabled: bool, _timestamp: Timestamp) {}
+}
@@ -0,0 +1,11 @@
+//! GDB remote serial protocol server for emulated PMS150C
+
+pub mod host;
+pub mod packet;
+pub mod server;
+
+#[cfg(test)]
+mod test;
+
+pub use host::UnconnectedHost;
+pub use server::GdbServer;
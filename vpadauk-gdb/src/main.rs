@@ -0,0 +1,53 @@
+use std::{env, fs, net::TcpListener, process};
+
+use vpadauk::{
+    firmware::FirmwareFile,
+    mcu::pms150c::{debugger::Debugger, Emulator, Pms150c},
+};
+use vpadauk_gdb::{GdbServer, UnconnectedHost};
+
+const DEFAULT_PORT: u16 = 3333;
+
+/// Loads firmware file, the format is chosen by the file extension
+fn load_firmware(path: &str, mcu: &mut Pms150c) -> Result<(), String> {
+    let content = fs::read(path).map_err(|err| err.to_string())?;
+    let file = FirmwareFile::parse(path, &content).map_err(|err| err.to_string())?;
+    mcu.load_firmware_file(&file).map_err(|err| err.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args.len() > 3 {
+        eprintln!("Usage: {} <firmware.ihx|firmware.bin> [port]", args[0]);
+        process::exit(1);
+    }
+    let port = match args.get(2).map(|port| port.parse::<u16>()) {
+        Some(Ok(port)) => port,
+        Some(Err(_)) => {
+            eprintln!("Invalid port: {}", args[2]);
+            process::exit(1);
+        }
+        None => DEFAULT_PORT,
+    };
+
+    let mut mcu = Pms150c::new();
+    if let Err(err) = load_firmware(&args[1], &mut mcu) {
+        eprintln!("Can't load firmware: {}", err);
+        process::exit(1);
+    }
+    let mut host = UnconnectedHost;
+    mcu.init(&mut host);
+    let mut server = GdbServer::new(Debugger::new(mcu), host);
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).unwrap_or_else(|err| {
+        eprintln!("Can't listen on port {}: {}", port, err);
+        process::exit(1);
+    });
+    println!("Waiting for gdb connection on 127.0.0.1:{}", port);
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| server.serve(stream));
+        if let Err(err) = result {
+            eprintln!("Session failed: {}", err);
+        }
+    }
+}
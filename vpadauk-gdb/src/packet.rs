@@ -0,0 +1,160 @@
+//! GDB remote serial protocol framing: `$<data>#<checksum>` packets, `+`/`-` acknowledgements
+//! and `0x03` interrupt requests
+
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::TcpStream,
+};
+
+const INTERRUPT: u8 = 0x03;
+
+pub enum Incoming {
+    Packet(Vec<u8>),
+    /// Client requested to stop running target
+    Interrupt,
+}
+
+pub struct Connection {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    last_packet: Vec<u8>,
+    ack_mode: bool,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            buffer: Vec::new(),
+            last_packet: Vec::new(),
+            ack_mode: true,
+        }
+    }
+
+    /// Disables `+`/`-` acknowledgements, requested by `QStartNoAckMode`
+    pub fn disable_acks(&mut self) {
+        self.ack_mode = false;
+    }
+
+    /// Blocks until the next packet or interrupt request. Returns `None` if client has
+    /// closed the connection
+    pub fn read(&mut self) -> io::Result<Option<Incoming>> {
+        loop {
+            if let Some(incoming) = self.parse()? {
+                return Ok(Some(incoming));
+            }
+            let mut chunk = [0u8; 1024];
+            let len = self.stream.read(&mut chunk)?;
+            if len == 0 {
+                return Ok(None);
+            }
+            self.buffer.extend_from_slice(&chunk[..len]);
+        }
+    }
+
+    /// Checks without blocking whether client has sent interrupt request
+    pub fn poll_interrupt(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut chunk = [0u8; 1024];
+        let result = loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => break Ok(()),
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break Ok(()),
+                Err(err) => break Err(err),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        result?;
+        match self.buffer.iter().position(|byte| *byte == INTERRUPT) {
+            Some(position) => {
+                self.buffer.remove(position);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        let checksum = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        let mut packet = Vec::with_capacity(data.len() + 4);
+        packet.push(b'$');
+        packet.extend_from_slice(data);
+        packet.extend_from_slice(format!("#{:02x}", checksum).as_bytes());
+        self.stream.write_all(&packet)?;
+        self.last_packet = packet;
+        Ok(())
+    }
+
+    /// Extracts the next complete packet or interrupt request from the buffer
+    fn parse(&mut self) -> io::Result<Option<Incoming>> {
+        while let Some(&byte) = self.buffer.first() {
+            match byte {
+                b'$' => break,
+                INTERRUPT => {
+                    self.buffer.remove(0);
+                    return Ok(Some(Incoming::Interrupt));
+                }
+                b'-' => {
+                    self.buffer.remove(0);
+                    let packet = self.last_packet.clone();
+                    self.stream.write_all(&packet)?;
+                }
+                // Acknowledgements and garbage between packets
+                _ => {
+                    self.buffer.remove(0);
+                }
+            }
+        }
+        let end = match self.buffer.iter().position(|byte| *byte == b'#') {
+            Some(end) if self.buffer.len() >= end + 3 => end,
+            _ => return Ok(None),
+        };
+        let packet: Vec<u8> = self.buffer.drain(..end + 3).collect();
+        let data = &packet[1..end];
+        let checksum = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        let expected = decode_hex(&packet[end + 1..]).map(|bytes| bytes[0]);
+        if expected != Some(checksum) {
+            if self.ack_mode {
+                self.stream.write_all(b"-")?;
+            }
+            return Ok(None);
+        }
+        if self.ack_mode {
+            self.stream.write_all(b"+")?;
+        }
+        Ok(Some(Incoming::Packet(unescape(data))))
+    }
+}
+
+/// Removes `}` escapes of binary data
+fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'}' {
+            if let Some(&escaped) = bytes.next() {
+                result.push(escaped ^ 0x20);
+            }
+        } else {
+            result.push(byte);
+        }
+    }
+    result
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn decode_hex(text: &[u8]) -> Option<Vec<u8>> {
+    if text.len() & 1 != 0 {
+        return None;
+    }
+    text.chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect()
+}
@@ -0,0 +1,477 @@
+//! RSP command handling on top of pms150c debugger. Address spaces are mapped to a single
+//! memory map: ROM is byte addressed from `ROM_BASE` (little-endian words), RAM starts at
+//! `RAM_BASE` and IO registers at `IO_BASE`. PC register holds ROM byte address
+
+use std::{io, net::TcpStream};
+
+use vpadauk::{
+    isa::pdk13::{
+        regs::{IO_ADDR_FLAGS, IO_ADDR_SP},
+        RomAddr,
+    },
+    mcu::{
+        host_adapter::HostAdapter,
+        pms150c::debugger::{Debugger, Space, StopReason, WatchKind, Watchpoint, WatchpointId},
+    },
+};
+
+use crate::packet::{decode_hex, encode_hex, Connection, Incoming};
+
+pub const ROM_BASE: u32 = 0x0000_0000;
+pub const RAM_BASE: u32 = 0x0080_0000;
+pub const IO_BASE: u32 = 0x0081_0000;
+
+const ROM_BYTES: u32 = 0x800;
+const RAM_BYTES: u32 = 0x40;
+const IO_BYTES: u32 = 0x20;
+
+// Target runs in slices of this size while client could interrupt it
+const CONTINUE_SLICE_CYCLES: u64 = 100_000;
+
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+const REGISTER_COUNT: usize = 4;
+const REGISTER_PC: usize = 3;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.vpadauk.pdk13.core">
+    <reg name="a" bitsize="8" type="uint8" regnum="0"/>
+    <reg name="flags" bitsize="8" type="uint8" regnum="1"/>
+    <reg name="sp" bitsize="8" type="data_ptr" regnum="2"/>
+    <reg name="pc" bitsize="16" type="code_ptr" regnum="3"/>
+  </feature>
+</target>
+"#;
+
+/// Memory region of the mapped address
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Region {
+    Rom(u32),
+    Ram(u8),
+    Io(u8),
+}
+
+impl Region {
+    fn from_address(address: u32) -> Option<Self> {
+        if address.wrapping_sub(ROM_BASE) < ROM_BYTES {
+            Some(Region::Rom(address - ROM_BASE))
+        } else if address.wrapping_sub(RAM_BASE) < RAM_BYTES {
+            Some(Region::Ram((address - RAM_BASE) as u8))
+        } else if address.wrapping_sub(IO_BASE) < IO_BYTES {
+            Some(Region::Io((address - IO_BASE) as u8))
+        } else {
+            None
+        }
+    }
+}
+
+/// Reply of the command handler
+enum Reply {
+    Packet(String),
+    /// Resume target with optional single step
+    Resume {
+        step: bool,
+    },
+    /// Client has detached or killed the target
+    Close(Option<String>),
+}
+
+impl Reply {
+    fn ok() -> Self {
+        Reply::Packet("OK".into())
+    }
+
+    fn error(code: u8) -> Self {
+        Reply::Packet(format!("E{:02x}", code))
+    }
+
+    fn unsupported() -> Self {
+        Reply::Packet(String::new())
+    }
+}
+
+pub struct GdbServer<H: HostAdapter> {
+    debugger: Debugger,
+    host: H,
+    watchpoints: Vec<(u8, u32, WatchpointId)>,
+    last_stop: String,
+}
+
+impl<H: HostAdapter> GdbServer<H> {
+    pub fn new(debugger: Debugger, host: H) -> Self {
+        Self {
+            debugger,
+            host,
+            watchpoints: Vec::new(),
+            last_stop: format!("S{:02x}", SIGTRAP),
+        }
+    }
+
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    pub fn into_inner(self) -> (Debugger, H) {
+        (self.debugger, self.host)
+    }
+
+    /// Serves single client session until it detaches, kills target or disconnects
+    pub fn serve(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        let mut connection = Connection::new(stream);
+        while let Some(incoming) = connection.read()? {
+            let packet = match incoming {
+                Incoming::Packet(packet) => packet,
+                // Target is already stopped
+                Incoming::Interrupt => continue,
+            };
+            let data = match self.handle(&packet) {
+                Reply::Packet(data) => data,
+                Reply::Resume { step } => self.resume(&mut connection, step)?,
+                Reply::Close(data) => {
+                    if let Some(data) = data {
+                        connection.write(data.as_bytes())?;
+                    }
+                    return Ok(());
+                }
+            };
+            connection.write(data.as_bytes())?;
+            if packet == b"QStartNoAckMode" {
+                connection.disable_acks();
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, packet: &[u8]) -> Reply {
+        let text = match std::str::from_utf8(packet) {
+            Ok(text) => text,
+            Err(_) => return Reply::unsupported(),
+        };
+        let (command, args) = text.split_at(text.len().min(1));
+        match command {
+            "?" => Reply::Packet(self.last_stop.clone()),
+            "g" => Reply::Packet(encode_hex(&self.read_registers())),
+            "G" => match decode_hex(args.as_bytes()) {
+                Some(bytes) if bytes.len() == 5 => {
+                    self.write_registers(&bytes);
+                    Reply::ok()
+                }
+                _ => Reply::error(1),
+            },
+            "p" => match parse_hex(args).filter(|n| (*n as usize) < REGISTER_COUNT) {
+                Some(n) => Reply::Packet(encode_hex(&self.read_register(n as usize))),
+                None => Reply::error(1),
+            },
+            "P" => self.handle_write_register(args),
+            "m" => self.handle_read_memory(args),
+            "M" => self.handle_write_memory(args),
+            "Z" => self.handle_breakpoint(args, true),
+            "z" => self.handle_breakpoint(args, false),
+            "c" | "s" => {
+                if !args.is_empty() {
+                    match parse_hex(args) {
+                        Some(address) => self.debugger.set_pc((address / 2) as RomAddr),
+                        None => return Reply::error(1),
+                    }
+                }
+                Reply::Resume {
+                    step: command == "s",
+                }
+            }
+            "H" => Reply::ok(),
+            "T" => Reply::ok(),
+            "k" => Reply::Close(None),
+            "D" => Reply::Close(Some("OK".into())),
+            "q" | "Q" | "v" => self.handle_query(text),
+            _ => Reply::unsupported(),
+        }
+    }
+
+    fn handle_query(&mut self, text: &str) -> Reply {
+        if text.starts_with("qSupported") {
+            return Reply::Packet(
+                "PacketSize=1000;qXfer:features:read+;QStartNoAckMode+;swbreak+;hwbreak+".into(),
+            );
+        }
+        if let Some(args) = text.strip_prefix("qXfer:features:read:target.xml:") {
+            return match parse_pair(args) {
+                Some((offset, length)) => Reply::Packet(xfer_chunk(TARGET_XML, offset, length)),
+                None => Reply::error(1),
+            };
+        }
+        match text {
+            "QStartNoAckMode" => Reply::ok(),
+            "qAttached" => Reply::Packet("1".into()),
+            "qC" => Reply::Packet("QC1".into()),
+            "qfThreadInfo" => Reply::Packet("m1".into()),
+            "qsThreadInfo" => Reply::Packet("l".into()),
+            _ => Reply::unsupported(),
+        }
+    }
+
+    fn read_register(&self, n: usize) -> Vec<u8> {
+        match n {
+            0 => vec![self.debugger.acc()],
+            1 => vec![self.debugger.read_io(IO_ADDR_FLAGS)],
+            2 => vec![self.debugger.sp()],
+            _ => (self.debugger.pc() * 2).to_le_bytes().to_vec(),
+        }
+    }
+
+    fn read_registers(&self) -> Vec<u8> {
+        (0..REGISTER_COUNT)
+            .flat_map(|n| self.read_register(n))
+            .collect()
+    }
+
+    fn write_register(&mut self, n: usize, bytes: &[u8]) {
+        match n {
+            0 => self.debugger.set_acc(bytes[0]),
+            1 => self.debugger.write_io(IO_ADDR_FLAGS, bytes[0]),
+            2 => self.debugger.write_io(IO_ADDR_SP, bytes[0]),
+            _ => self
+                .debugger
+                .set_pc(u16::from_le_bytes([bytes[0], bytes[1]]) / 2),
+        }
+    }
+
+    fn write_registers(&mut self, bytes: &[u8]) {
+        for n in 0..REGISTER_PC {
+            self.write_register(n, &bytes[n..=n]);
+        }
+        self.write_register(REGISTER_PC, &bytes[REGISTER_PC..]);
+    }
+
+    fn handle_write_register(&mut self, args: &str) -> Reply {
+        let mut parts = args.splitn(2, '=');
+        let n = parts.next().and_then(parse_hex).map(|n| n as usize);
+        let bytes = parts.next().and_then(|value| decode_hex(value.as_bytes()));
+        match (n, bytes) {
+            (Some(n), Some(bytes)) if n < REGISTER_COUNT => {
+                let size = if n == REGISTER_PC { 2 } else { 1 };
+                if bytes.len() != size {
+                    return Reply::error(1);
+                }
+                self.write_register(n, &bytes);
+                Reply::ok()
+            }
+            _ => Reply::error(1),
+        }
+    }
+
+    fn read_byte(&self, address: u32) -> Option<u8> {
+        Some(match Region::from_address(address)? {
+            Region::Rom(offset) => {
+                let word = self.debugger.read_rom((offset / 2) as RomAddr);
+                (word >> (8 * (offset % 2))) as u8
+            }
+            Region::Ram(offset) => self.debugger.read_ram(offset),
+            Region::Io(offset) => self.debugger.read_io(offset),
+        })
+    }
+
+    fn write_byte(&mut self, address: u32, value: u8) -> bool {
+        match Region::from_address(address) {
+            Some(Region::Rom(offset)) => {
+                let word_address = (offset / 2) as RomAddr;
+                let word = self.debugger.read_rom(word_address);
+                let word = if offset % 2 == 0 {
+                    (word & 0xFF00) | value as u16
+                } else {
+                    (word & 0x00FF) | (value as u16) << 8
+                };
+                self.debugger.write_rom(word_address, word);
+            }
+            Some(Region::Ram(offset)) => self.debugger.write_ram(offset, value),
+            Some(Region::Io(offset)) => self.debugger.write_io(offset, value),
+            None => return false,
+        }
+        true
+    }
+
+    fn handle_read_memory(&mut self, args: &str) -> Reply {
+        let (address, length) = match parse_pair(args) {
+            Some(pair) => pair,
+            None => return Reply::error(1),
+        };
+        let bytes: Option<Vec<u8>> = (0..length)
+            .map(|offset| self.read_byte(address.wrapping_add(offset)))
+            .collect();
+        match bytes {
+            Some(bytes) => Reply::Packet(encode_hex(&bytes)),
+            None => Reply::error(14),
+        }
+    }
+
+    fn handle_write_memory(&mut self, args: &str) -> Reply {
+        let mut parts = args.splitn(2, ':');
+        let pair = parts.next().and_then(parse_pair);
+        let bytes = parts.next().and_then(|data| decode_hex(data.as_bytes()));
+        let ((address, length), bytes) = match (pair, bytes) {
+            (Some(pair), Some(bytes)) if bytes.len() == pair.1 as usize => (pair, bytes),
+            _ => return Reply::error(1),
+        };
+        let mapped =
+            (0..length).all(|offset| Region::from_address(address.wrapping_add(offset)).is_some());
+        if !mapped {
+            return Reply::error(14);
+        }
+        for (offset, byte) in bytes.iter().copied().enumerate() {
+            self.write_byte(address.wrapping_add(offset as u32), byte);
+        }
+        Reply::ok()
+    }
+
+    /// Handles `Z`/`z` packets. Software and hardware breakpoints are the same for emulator,
+    /// watchpoints are supported for RAM and IO regions
+    fn handle_breakpoint(&mut self, args: &str, insert: bool) -> Reply {
+        let mut parts = args.split(',');
+        let kind = parts.next().and_then(parse_hex);
+        let address = parts.next().and_then(parse_hex);
+        let length = parts.next().and_then(parse_hex);
+        let (kind, address, length) = match (kind, address, length) {
+            (Some(kind), Some(address), Some(length)) => (kind as u8, address, length),
+            _ => return Reply::error(1),
+        };
+        match kind {
+            0 | 1 => {
+                let word_address = match Region::from_address(address) {
+                    Some(Region::Rom(offset)) => (offset / 2) as RomAddr,
+                    _ => return Reply::error(14),
+                };
+                if insert {
+                    if self.debugger.find_breakpoint(word_address).is_some() {
+                        return Reply::ok();
+                    }
+                    match self.debugger.add_breakpoint(word_address) {
+                        Some(_) => Reply::ok(),
+                        None => Reply::error(28),
+                    }
+                } else {
+                    if let Some(id) = self.debugger.find_breakpoint(word_address) {
+                        self.debugger.remove_breakpoint(id);
+                    }
+                    Reply::ok()
+                }
+            }
+            2..=4 if insert => self.insert_watchpoint(kind, address, length),
+            2..=4 => {
+                self.remove_watchpoints(kind, address, length);
+                Reply::ok()
+            }
+            _ => Reply::unsupported(),
+        }
+    }
+
+    fn insert_watchpoint(&mut self, kind: u8, address: u32, length: u32) -> Reply {
+        let watch_kind = match kind {
+            2 => WatchKind::Write,
+            3 => WatchKind::Read,
+            _ => WatchKind::ReadWrite,
+        };
+        for offset in 0..length {
+            let byte_address = address.wrapping_add(offset);
+            let (space, region_address) = match Region::from_address(byte_address) {
+                Some(Region::Ram(offset)) => (Space::Ram, offset),
+                Some(Region::Io(offset)) => (Space::Io, offset),
+                _ => {
+                    self.remove_watchpoints(kind, address, offset);
+                    return Reply::error(14);
+                }
+            };
+            let watchpoint = Watchpoint {
+                space,
+                address: region_address,
+                kind: watch_kind,
+            };
+            match self.debugger.add_watchpoint(watchpoint) {
+                Some(id) => self.watchpoints.push((kind, byte_address, id)),
+                None => {
+                    self.remove_watchpoints(kind, address, offset);
+                    return Reply::error(28);
+                }
+            }
+        }
+        Reply::ok()
+    }
+
+    fn remove_watchpoints(&mut self, kind: u8, address: u32, length: u32) {
+        let debugger = &mut self.debugger;
+        self.watchpoints.retain(|(watch_kind, watch_address, id)| {
+            let matches = *watch_kind == kind && watch_address.wrapping_sub(address) < length;
+            if matches {
+                debugger.remove_watchpoint(*id);
+            }
+            !matches
+        });
+    }
+
+    /// Runs target until it stops or client interrupts it, returns stop reply
+    fn resume(&mut self, connection: &mut Connection, step: bool) -> io::Result<String> {
+        let result = if step {
+            self.debugger.step(&mut self.host)
+        } else {
+            loop {
+                match self.debugger.resume(&mut self.host, CONTINUE_SLICE_CYCLES) {
+                    Ok(StopReason::CycleLimit) => {
+                        if connection.poll_interrupt()? {
+                            self.last_stop = format!("S{:02x}", SIGINT);
+                            return Ok(self.last_stop.clone());
+                        }
+                    }
+                    result => break result,
+                }
+            }
+        };
+        self.last_stop = match result {
+            Ok(StopReason::Watchpoint(id, _)) => self.watch_stop_reply(id),
+            Ok(StopReason::Breakpoint(_)) => format!("T{:02x}swbreak:;", SIGTRAP),
+            Ok(_) => format!("S{:02x}", SIGTRAP),
+            // Illegal instruction in strict decode mode
+            Err(_) => format!("S{:02x}", SIGILL),
+        };
+        Ok(self.last_stop.clone())
+    }
+
+    fn watch_stop_reply(&self, id: WatchpointId) -> String {
+        let watch = self
+            .watchpoints
+            .iter()
+            .find(|(_, _, watch_id)| *watch_id == id);
+        match watch {
+            Some((kind, address, _)) => {
+                let name = match kind {
+                    2 => "watch",
+                    3 => "rwatch",
+                    _ => "awatch",
+                };
+                format!("T{:02x}{}:{:x};", SIGTRAP, name, address)
+            }
+            None => format!("S{:02x}", SIGTRAP),
+        }
+    }
+}
+
+fn parse_hex(text: &str) -> Option<u32> {
+    u32::from_str_radix(text, 16).ok()
+}
+
+/// Parses `<hex>,<hex>` pair
+fn parse_pair(text: &str) -> Option<(u32, u32)> {
+    let mut parts = text.splitn(2, ',');
+    Some((parse_hex(parts.next()?)?, parse_hex(parts.next()?)?))
+}
+
+/// Returns `qXfer` reply with the document chunk, `l` prefix marks the last chunk
+fn xfer_chunk(document: &str, offset: u32, length: u32) -> String {
+    let bytes = document.as_bytes();
+    let start = (offset as usize).min(bytes.len());
+    let end = start.saturating_add(length as usize).min(bytes.len());
+    let prefix = if end == bytes.len() { 'l' } else { 'm' };
+    format!("{}{}", prefix, String::from_utf8_lossy(&bytes[start..end]))
+}
@@ -0,0 +1,166 @@
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread::{self, JoinHandle},
+};
+
+use vpadauk::{
+    isa::pdk13::asm::assemble,
+    mcu::pms150c::{debugger::Debugger, Emulator, Pms150c},
+};
+
+use crate::{GdbServer, UnconnectedHost};
+
+const PROGRAM: &str = "
+        mov a, #0x5a
+    loop:
+        inc 0x10
+        goto loop
+";
+
+/// Scripted RSP client
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn send(&mut self, data: &str) {
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.stream, "${}#{:02x}", data, checksum).unwrap();
+    }
+
+    fn receive(&mut self) -> String {
+        let mut packet = Vec::new();
+        let mut byte = [0u8];
+        loop {
+            self.stream.read_exact(&mut byte).unwrap();
+            match byte[0] {
+                b'$' => packet.clear(),
+                b'#' => break,
+                b'+' if packet.is_empty() => {}
+                other => packet.push(other),
+            }
+        }
+        let mut checksum = [0u8; 2];
+        self.stream.read_exact(&mut checksum).unwrap();
+        self.stream.write_all(b"+").unwrap();
+        String::from_utf8(packet).unwrap()
+    }
+
+    fn request(&mut self, data: &str) -> String {
+        self.send(data);
+        self.receive()
+    }
+}
+
+/// Starts server for the single session in the background thread
+fn start(source: &str) -> (Client, JoinHandle<Debugger>) {
+    let mut mcu = Pms150c::new();
+    let mut host = UnconnectedHost;
+    mcu.load_firmware(&assemble(source).unwrap()).unwrap();
+    mcu.init(&mut host);
+    let mut server = GdbServer::new(Debugger::new(mcu), host);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        server.serve(stream).unwrap();
+        server.into_inner().0
+    });
+    let stream = TcpStream::connect(address).unwrap();
+    (Client { stream }, handle)
+}
+
+#[test]
+fn handshake_and_target_description() {
+    let (mut client, handle) = start(PROGRAM);
+    let supported = client.request("qSupported:swbreak+;xmlRegisters=i386");
+    assert!(supported.contains("qXfer:features:read+"));
+    assert!(supported.contains("swbreak+"));
+    let xml = client.request("qXfer:features:read:target.xml:0,1000");
+    assert!(xml.starts_with('l'));
+    assert!(xml.contains("name=\"pc\""));
+    assert_eq!("S05", client.request("?"));
+    assert_eq!("OK", client.request("QStartNoAckMode"));
+    assert_eq!("", client.request("vMustReplyEmpty"));
+    assert_eq!("OK", client.request("D"));
+    handle.join().unwrap();
+}
+
+#[test]
+fn registers_read_and_write() {
+    let (mut client, handle) = start(PROGRAM);
+    assert_eq!("S05", client.request("s"));
+    // a, flags, sp, pc as little-endian byte address
+    let registers = client.request("g");
+    assert_eq!(10, registers.len());
+    assert_eq!("5a", &registers[..2]);
+    assert_eq!("0200", &registers[6..]);
+    assert_eq!("5a", client.request("p0"));
+    assert_eq!("OK", client.request("P0=33"));
+    assert_eq!("OK", client.request("P3=0400"));
+    assert_eq!("0400", client.request("p3"));
+    assert_eq!("OK", client.request("G1100000600"));
+    assert_eq!("E01", client.request("p4"));
+    assert_eq!("OK", client.request("D"));
+
+    let debugger = handle.join().unwrap();
+    assert_eq!(0x11, debugger.acc());
+    assert_eq!(3, debugger.pc());
+}
+
+#[test]
+fn memory_read_and_write() {
+    let (mut client, handle) = start(PROGRAM);
+    // ROM words are little-endian pairs of bytes
+    assert_eq!("5a17", client.request("m0,2"));
+    assert_eq!("OK", client.request("M800010,2:abcd"));
+    assert_eq!("abcd", client.request("m800010,2"));
+    assert_eq!("OK", client.request("M4,2:0000"));
+    assert_eq!("0000", client.request("m4,2"));
+    assert_eq!("E0e", client.request("m900000,1"));
+    assert_eq!("OK", client.request("D"));
+
+    let debugger = handle.join().unwrap();
+    assert_eq!(0xAB, debugger.read_ram(0x10));
+    assert_eq!(0xCD, debugger.read_ram(0x11));
+    assert_eq!(0, debugger.read_rom(2));
+}
+
+#[test]
+fn breakpoint_and_step() {
+    let (mut client, handle) = start(PROGRAM);
+    assert_eq!("OK", client.request("Z0,4,2"));
+    assert_eq!("T05swbreak:;", client.request("c"));
+    assert_eq!("0400", client.request("p3"));
+    assert_eq!("T05swbreak:;", client.request("c"));
+    assert_eq!("OK", client.request("z0,4,2"));
+    assert_eq!("S05", client.request("s"));
+    assert_eq!("0200", client.request("p3"));
+    assert_eq!("OK", client.request("D"));
+
+    let debugger = handle.join().unwrap();
+    assert_eq!(2, debugger.read_ram(0x10));
+}
+
+#[test]
+fn watchpoint_stops_on_write() {
+    let (mut client, handle) = start(PROGRAM);
+    assert_eq!("OK", client.request("Z2,800010,1"));
+    assert_eq!("T05watch:800010;", client.request("c"));
+    assert_eq!("01", client.request("m800010,1"));
+    assert_eq!("OK", client.request("z2,800010,1"));
+    assert_eq!("OK", client.request("D"));
+    handle.join().unwrap();
+}
+
+#[test]
+fn interrupt_stops_running_target() {
+    let (mut client, handle) = start(PROGRAM);
+    client.send("c");
+    client.stream.write_all(&[0x03]).unwrap();
+    assert_eq!("S02", client.receive());
+    assert_eq!("OK", client.request("D"));
+    handle.join().unwrap();
+}
@@ -1,24 +1,26 @@
 use std::{
     fs,
+    io,
     path::Path,
     collections::LinkedList,
-    time::{Duration, Instant}
+    time::Duration
 };
 use plotters::prelude::*;
 
 use vpadauk::{
+    firmware::{FirmwareError, FirmwareFile},
     isa::pdk13::Pdk13Error,
     mcu::{
         pms150c::{Pms150c, pins},
-        host_adapter::{HostAdapter, Pin, AnalogSignal}
+        host_adapter::{HostAdapter, Pin, AnalogSignal, Timestamp}
     }
 };
 use vpadauk::mcu::pms150c::Emulator;
-use vpadauk::isa::pdk13::ir::IrOpcode::Incm;
 
 
 pub enum EmulationError {
-    InvalidRom(String),
+    Io(io::Error),
+    InvalidFirmware(FirmwareError),
     CoreFailed(Pdk13Error)
 }
 
@@ -105,81 +107,75 @@ pub struct PlottingHost {
 }
 
 impl PlottingHost {
+    /// Loads easy-pdk Intel HEX image from `.ihx`/`.hex` files, fuse options of the image are
+    /// applied to MCU. `.bin`/`.fw` files are loaded as raw binary
     fn load_rom(path: &Path, mcu: &mut Pms150c) -> Result<(), EmulationError> {
-        let rom = fs::read(path)
-            .map_err(|_| EmulationError::InvalidRom("Can't open file".into()))?;
-
-        if rom.len() % 2 != 0 {
-            return Err(EmulationError::InvalidRom("Rom size should be multiple of 2".into()));
-        }
+        let content = fs::read(path).map_err(EmulationError::Io)?;
+        let file = FirmwareFile::parse(&path.to_string_lossy(), &content)
+            .map_err(EmulationError::InvalidFirmware)?;
+        mcu.load_firmware_file(&file).map_err(EmulationError::CoreFailed)
+    }
 
-        for (address, word) in rom.chunks(2).enumerate() {
-            let instruction = (word[0] as u16) | ((word[1] as u16) << 8);
-            mcu.write_rom(address, instruction).map_err(|err| EmulationError::CoreFailed(err))?;
-        };
+    fn record_pins(&mut self, time: Duration) {
+        let plot_state = [
+            self.pa.pin0,
+            self.pa.pin3,
+            self.pa.pin4,
+            self.pa.pin5,
+            self.pa.pin6,
+            self.pa.pin7,
+        ];
+        self.plot_digital_pins.push(
+            ((time.as_nanos() as f64) / 1_000_000_000_f64, // in seconds
+             plot_state));
+    }
 
-        Ok(())
+    fn set_pin_level(&mut self, pin: Pin, value: bool) {
+        match pin {
+            pins::PA7 => self.pa.pin7 = value,
+            pins::PA6 => self.pa.pin6 = value,
+            pins::PA5 => self.pa.pin5 = value,
+            pins::PA4 => self.pa.pin4 = value,
+            pins::PA3 => self.pa.pin3 = value,
+            pins::PA0 => self.pa.pin0 = value,
+            _ => unreachable!(),
+        }
     }
 
     pub fn run(&mut self, rom_path: &Path, time: Duration) -> Result<(), EmulationError> {
         self.plot_digital_pins.clear();
         self.pa = HostPortA::default();
-        let mut actions = self.actions.clone();
-
 
         let mut mcu = Pms150c::new();
         Self::load_rom(rom_path, &mut mcu)?;
+        mcu.init(self);
+        self.record_pins(mcu.elapsed());
 
-        let mut time_passed = Duration::from_nanos(0);
-
-        let mut steps = 0;
-
-        loop {
-            if !actions.is_empty() {
-                if time_passed >= actions.front().unwrap().duration {
-                    match actions.front().unwrap().action {
-                        EmulationAction::ChangePinInput(pin, value) => {
-                            match pin {
-                                pins::PA7 => self.pa.pin7 = value,
-                                pins::PA6 => self.pa.pin6 = value,
-                                pins::PA5 => self.pa.pin5 = value,
-                                pins::PA4 => self.pa.pin4 = value,
-                                pins::PA3 => self.pa.pin3 = value,
-                                pins::PA0 => self.pa.pin0 = value,
-                                _ => unreachable!(),
-                            }
-                        },
-                    }
-                    actions.pop_front();
-                }
-            }
-
-            let plot_state = [
-                self.pa.pin0,
-                self.pa.pin3,
-                self.pa.pin4,
-                self.pa.pin5,
-                self.pa.pin6,
-                self.pa.pin7,
-            ];
-            self.plot_digital_pins.push(
-                ((time_passed.as_nanos() as f64) / 1000000000 as f64, // in seconds
-                 plot_state));
-
-            let step_frequency = mcu.get_frequency();
-            mcu.step(self);
-            let step_period_ns = 1_000_000_000_f64 / step_frequency as f64;
-            time_passed += Duration::from_nanos(step_period_ns as u64);
-            steps += 1;
-
-            if time_passed > time {
+        let actions = self.actions.clone();
+        for TimedEmulationAction { duration, action } in actions {
+            if duration > time {
                 break;
             }
+            mcu.run_for(self, duration.saturating_sub(mcu.elapsed()))
+                .map_err(EmulationError::CoreFailed)?;
+            match action {
+                EmulationAction::ChangePinInput(pin, value) => {
+                    mcu.set_pin_input(pin, value, duration)
+                        .map_err(EmulationError::CoreFailed)?;
+                    self.record_pins(duration);
+                    self.set_pin_level(pin, value);
+                    self.record_pins(duration);
+                },
+            }
         }
+        mcu.run_for(self, time.saturating_sub(mcu.elapsed()))
+            .map_err(EmulationError::CoreFailed)?;
+        let time_passed = mcu.elapsed();
+        self.record_pins(time_passed);
 
         // plotting
 
-        let passed_nanos = (time_passed.as_nanos() as f64) / 1000000000 as f64;
+        let passed_nanos = (time_passed.as_nanos() as f64) / 1_000_000_000_f64;
 
         let plot_path = self.plot_path.clone();
         let mut root_area =
@@ -337,32 +333,15 @@ impl PlottingHost {
 }
 
 impl HostAdapter for PlottingHost {
-    fn read_pin_digital(&self, pin: Pin) -> bool {
-        match pin {
-            pins::PA7 => self.pa.pin7,
-            pins::PA6 => self.pa.pin6,
-            pins::PA5 => self.pa.pin5,
-            pins::PA4 => self.pa.pin4,
-            pins::PA3 => self.pa.pin3,
-            pins::PA0 => self.pa.pin0,
-            _ => unreachable!(),
-        }
-    }
-
-    fn write_pin_digital(&mut self, pin: Pin, value: bool) {
-        match pin {
-            pins::PA7 => self.pa.pin7 = value,
-            pins::PA6 => self.pa.pin6 = value,
-            pins::PA5 => self.pa.pin5 = value,
-            pins::PA4 => self.pa.pin4 = value,
-            pins::PA3 => self.pa.pin3 = value,
-            pins::PA0 => self.pa.pin0 = value,
-            _ => unreachable!(),
-        }
+    fn write_pin_digital(&mut self, pin: Pin, value: bool, timestamp: Timestamp) {
+        // Edges are placed at the exact simulated time of the pin change
+        self.record_pins(timestamp.time);
+        self.set_pin_level(pin, value);
+        self.record_pins(timestamp.time);
     }
 
-    fn read_pin_analog(&self, pin: Pin) -> AnalogSignal { AnalogSignal::from_u16(0) }
-    fn write_pin_analog(&mut self, pin: Pin, value: AnalogSignal) {}
-    fn set_pin_output_enabled(&mut self, pin: Pin, enabled: bool) {}
-    fn set_pin_pull_up_enabled(&mut self, pin: Pin, enabled: bool) {}
+    fn read_pin_analog(&self, _pin: Pin, _timestamp: Timestamp) -> AnalogSignal { AnalogSignal::from_u16(0) }
+    fn write_pin_analog(&mut self, _pin: Pin, _value: AnalogSignal, _timestamp: Timestamp) {}
+    fn set_pin_output_enabled(&mut self, _pin: Pin, _enabled: bool, _timestamp: Timestamp) {}
+    fn set_pin_pull_up_enabled(&mut self, _pin: Pin, _enabled: bool, _timestamp: Timestamp) {}
 }
\ No newline at end of file